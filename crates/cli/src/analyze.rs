@@ -34,9 +34,10 @@ use std::sync::mpsc::channel;
 use crate::commands::apply_pattern::ApplyInput;
 use crate::commands::apply_pattern::ApplyPatternArgs;
 use crate::flags::OutputFormat;
+use crate::journal::Journal;
 use crate::resolver::RichPattern;
 use crate::ux::CheckResult;
-use marzano_messenger::emit::{ApplyDetails, Messager};
+use marzano_messenger::emit::{ApplyDetails, InteractiveState, Messager};
 
 impl<'b> RichPattern<'b> {
     #[instrument(skip(self, pattern_libs, targets))]
@@ -48,24 +49,30 @@ impl<'b> RichPattern<'b> {
         injected_limit: Option<usize>,
     ) -> Result<CompilationResult> {
         let lang = language.unwrap_or_default();
-        #[cfg(not(feature = "ai_builtins"))]
-        let injected_builtins: Option<BuiltIns> =
-            marzano_core::built_in_functions::get_ai_placeholder_functions();
-        #[cfg(feature = "ai_builtins")]
-        let injected_builtins = Some(ai_builtins::ai_builtins::get_ai_built_in_functions());
-
         src_to_problem_libs(
             self.body.to_owned(),
             pattern_libs,
             lang.try_into().unwrap(),
             self.name.to_owned(),
             targets,
-            injected_builtins,
+            injected_builtins(),
             injected_limit,
         )
     }
 }
 
+/// The built-in functions added to every pattern compiled by the CLI
+#[cfg(not(feature = "ai_builtins"))]
+pub fn injected_builtins() -> Option<BuiltIns> {
+    marzano_core::built_in_functions::get_ai_placeholder_functions()
+}
+
+/// The built-in functions added to every pattern compiled by the CLI
+#[cfg(feature = "ai_builtins")]
+pub fn injected_builtins() -> Option<BuiltIns> {
+    Some(ai_builtins::ai_builtins::get_ai_built_in_functions())
+}
+
 pub fn extract_rewritten_content(result: &MatchResult) -> Option<&String> {
     match result {
         MatchResult::AnalysisLog(_) => None,
@@ -151,6 +158,7 @@ pub async fn par_apply_pattern<M>(
     arg: &ApplyPatternArgs,
     context: &ExecutionContext,
     format: &OutputFormat,
+    journal: Option<&Journal>,
 ) -> M
 where
     M: Messager,
@@ -188,7 +196,7 @@ where
     );
     let cache_ref = &cache;
 
-    let mut interactive = arg.interactive;
+    let mut interactive = InteractiveState::new(arg.interactive);
 
     let (found_count, disk_paths) = match my_input {
         ApplyInput::Disk(ref my_input) => {
@@ -224,7 +232,16 @@ where
 
             drop(file_paths_tx);
 
-            let found_paths = file_paths_rx.iter().collect::<Vec<_>>();
+            let mut found_paths = file_paths_rx.iter().collect::<Vec<_>>();
+            if let Some(journal) = journal {
+                // Files finished by an earlier run are skipped, failed ones are retried
+                found_paths.retain(|path| !journal.is_done(path));
+                emit_error!(
+                    owned_emitter,
+                    &arg.visibility,
+                    journal.record_pending(&found_paths)
+                );
+            }
             (found_paths.len(), Some(found_paths))
         }
         ApplyInput::Virtual(ref virtual_info) => (virtual_info.files.len(), None),
@@ -270,12 +287,22 @@ where
                             let Some(path_hash) = done_file.file_hash else {
                                 continue;
                             };
-                            if Some(false) == done_file.has_results && !done_file.from_cache {
-                                cache_ref.put_no_matches(path_hash, compiled.hash).unwrap();
+                            if Some(false) == done_file.has_results
+                                && !done_file.from_cache
+                                && !done_file.depends_on_date
+                            {
+                                if let Err(e) = cache_ref.put_no_matches(path_hash, compiled.hash) {
+                                    log::debug!(
+                                        "Failed to cache no matches for {}: {}",
+                                        done_file.relative_file_path,
+                                        e
+                                    );
+                                }
                             }
                         }
                     }
                 }
+                let recorded = journal.map(|_| message.clone());
                 let user_decision = emitter.handle_results(
                     message,
                     details,
@@ -288,6 +315,14 @@ where
                     compiled_language,
                 );
 
+                if let (Some(journal), Some(recorded)) = (journal, recorded) {
+                    if let Err(e) = journal.record_results(&recorded) {
+                        let log =
+                            AnalysisLog::floating_error(format!("Failed to update journal: {}", e));
+                        emitter.emit(&MatchResult::AnalysisLog(log)).unwrap();
+                    }
+                }
+
                 if !user_decision {
                     should_continue.store(false, Ordering::SeqCst);
                     break;
@@ -0,0 +1,211 @@
+use crate::ux::CheckResult;
+use anyhow::{bail, Context as _, Result};
+use grit_util::Range;
+use marzano_gritmodule::utils::extract_path;
+use marzano_util::hasher::hash;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
+
+const BASELINE_VERSION: u32 = 1;
+
+/// A recorded violation. The fingerprint is built from the matched text and the lines around it,
+/// not the line number, so entries keep matching when unrelated code moves them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BaselineEntry {
+    pub file: String,
+    /// The line the violation was on when the baseline was written, for reference only
+    pub line: u32,
+    pub pattern: String,
+    pub fingerprint: String,
+}
+
+/// A set of pre-existing violations for `grit check` to ignore
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    version: u32,
+    pub entries: Vec<BaselineEntry>,
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Paths are stored relative to the directory grit check runs in, so the baseline is portable
+fn normalize_file(file: &str, root: &Path) -> String {
+    let path = Path::new(file);
+    let path = path.strip_prefix(root).unwrap_or(path);
+    let path = path.strip_prefix(".").unwrap_or(path);
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// The nearest non-blank line before `line`, or after it when `step` is positive
+fn context_line(lines: &[&str], line: usize, step: isize) -> String {
+    let mut index = line as isize + step;
+    while index >= 0 && (index as usize) < lines.len() {
+        let text = lines[index as usize].trim();
+        if !text.is_empty() {
+            return normalize_whitespace(text);
+        }
+        index += step;
+    }
+    String::new()
+}
+
+/// Fingerprint a violation from its pattern, file, normalized matched text, and surrounding lines
+pub fn fingerprint(pattern: &str, file: &str, content: &str, ranges: &[Range]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut combined = format!("{}\0{}", pattern, file);
+    for range in ranges {
+        let start = range.start.byte_index(content).min(content.len());
+        let end = range.end.byte_index(content).clamp(start, content.len());
+        let text = content.get(start..end).unwrap_or_default();
+        let start_line = range.start.line.saturating_sub(1) as usize;
+        let end_line = range.end.line.saturating_sub(1) as usize;
+        combined.push_str(&format!(
+            "\0{}\0{}\0{}",
+            normalize_whitespace(text),
+            context_line(&lines, start_line, -1),
+            context_line(&lines, end_line, 1)
+        ));
+    }
+    hash(&combined)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn entry_for_result(result: &CheckResult, root: &Path) -> Option<BaselineEntry> {
+    let path = extract_path(&result.result)?;
+    let file = normalize_file(path, root);
+    let ranges = result.result.get_ranges().cloned().unwrap_or_default();
+    let content = match result.result.extract_original_content() {
+        Some(content) => content.to_string(),
+        None => fs_err::read_to_string(path).unwrap_or_default(),
+    };
+    Some(BaselineEntry {
+        line: ranges.first().map(|r| r.start.line).unwrap_or(1),
+        fingerprint: fingerprint(&result.pattern.local_name, &file, &content, &ranges),
+        pattern: result.pattern.local_name.clone(),
+        file,
+    })
+}
+
+impl Baseline {
+    pub fn from_results<'a>(
+        results: impl IntoIterator<Item = &'a CheckResult<'a>>,
+        root: &Path,
+    ) -> Self {
+        let mut entries: Vec<_> = results
+            .into_iter()
+            .filter_map(|r| entry_for_result(r, root))
+            .collect();
+        // Keep the file stable between runs, so it diffs cleanly in version control
+        entries.sort();
+        Self {
+            version: BASELINE_VERSION,
+            entries,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs_err::read_to_string(path)?;
+        let baseline: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline file {}", path.display()))?;
+        if baseline.version != BASELINE_VERSION {
+            bail!(
+                "Unsupported baseline version {} in {}, please regenerate it with --write-baseline",
+                baseline.version,
+                path.display()
+            );
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        fs_err::write(path, content)?;
+        Ok(())
+    }
+
+    /// Drop results that are already in the baseline. Each entry suppresses at most one result,
+    /// so new copies of an existing violation are still reported.
+    /// Returns the remaining results, the number suppressed, and the entries that no longer match.
+    pub fn filter<'a>(
+        &self,
+        check_results: HashMap<String, Vec<CheckResult<'a>>>,
+        root: &Path,
+    ) -> (
+        HashMap<String, Vec<CheckResult<'a>>>,
+        usize,
+        Vec<&BaselineEntry>,
+    ) {
+        let mut remaining: HashMap<&str, Vec<&BaselineEntry>> = HashMap::new();
+        for entry in &self.entries {
+            remaining.entry(&entry.fingerprint).or_default().push(entry);
+        }
+        let mut suppressed = 0;
+        let mut new_results: HashMap<String, Vec<CheckResult<'a>>> = HashMap::new();
+        for (file, results) in check_results {
+            for result in results {
+                let baselined = entry_for_result(&result, root).is_some_and(|entry| {
+                    remaining
+                        .get_mut(entry.fingerprint.as_str())
+                        .and_then(|entries| entries.pop())
+                        .is_some()
+                });
+                if baselined {
+                    suppressed += 1;
+                } else {
+                    new_results.entry(file.clone()).or_default().push(result);
+                }
+            }
+        }
+        let mut fixed: Vec<_> = remaining.into_values().flatten().collect();
+        fixed.sort();
+        (new_results, suppressed, fixed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grit_util::ByteRange;
+
+    fn range_of(content: &str, needle: &str) -> Range {
+        let start = content.find(needle).unwrap();
+        let end = start + needle.len();
+        Range::from_byte_range(content, &ByteRange::new(start, end))
+    }
+
+    #[test]
+    fn fingerprints_ignore_line_shifts() {
+        let content = "class Button {\n  a = 1;\n}\n";
+        let shifted = "// A button\n\nclass Button {\n    a  =  1;\n}\n";
+        let original = fingerprint("test_js", "test.js", content, &[range_of(content, "a = 1")]);
+        let moved = fingerprint(
+            "test_js",
+            "test.js",
+            shifted,
+            &[range_of(shifted, "a  =  1")],
+        );
+        assert_eq!(original, moved);
+
+        let changed = "class Link {\n  a = 1;\n}\n";
+        assert_ne!(
+            original,
+            fingerprint("test_js", "test.js", changed, &[range_of(changed, "a = 1")])
+        );
+        assert_ne!(
+            original,
+            fingerprint("other", "test.js", content, &[range_of(content, "a = 1")])
+        );
+    }
+
+    #[test]
+    fn normalizes_paths_relative_to_root() {
+        let root = Path::new("/repo");
+        assert_eq!(normalize_file("./src/test.js", root), "src/test.js");
+        assert_eq!(normalize_file("/repo/src/test.js", root), "src/test.js");
+    }
+}
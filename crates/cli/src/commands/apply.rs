@@ -173,6 +173,7 @@ mod tests {
             matched: 0,
             rewritten: 0,
             named_pattern: None,
+            invalid_rewrites: 0,
         };
         let apply_migration_args = ApplyMigrationArgs::default();
         let apply_pattern_args = ApplyPatternArgs::default();
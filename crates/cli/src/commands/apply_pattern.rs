@@ -14,7 +14,7 @@ use tracing::span;
 use tracing_opentelemetry::OpenTelemetrySpanExt as _;
 
 use grit_pattern_matcher::has_rewrite;
-use grit_util::Position;
+use grit_util::{Position, Profiler};
 use indicatif::MultiProgress;
 use marzano_core::api::{AllDone, AllDoneReason, AnalysisLog, MatchResult};
 use marzano_core::pattern_compiler::CompilationResult;
@@ -30,14 +30,21 @@ use std::env;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use tokio::fs;
 
 use crate::commands::filters::extract_filter_ranges;
 
 use crate::flags::GlobalFormatFlags;
 use crate::{
-    analyze::par_apply_pattern, error::GoodError, flags::OutputFormat,
-    messenger_variant::create_emitter, result_formatting::get_human_error, updater::Updater,
+    analyze::par_apply_pattern,
+    error::GoodError,
+    flags::OutputFormat,
+    journal::Journal,
+    messenger_variant::create_emitter,
+    result_formatting::get_human_error,
+    updater::Updater,
+    ux::{log_explanation, log_profile},
 };
 
 use marzano_messenger::{
@@ -49,6 +56,7 @@ use crate::resolver::{get_grit_files_from_flags_or_cwd, GritModuleResolver};
 use crate::utils::has_uncommitted_changes;
 
 use super::filters::SharedFilterArgs;
+use super::limits::{set_file_limits, SharedLimitArgs};
 
 /// Apply a pattern to a set of paths on disk which will be rewritten in place
 #[derive(Deserialize)]
@@ -161,6 +169,30 @@ pub struct ApplyPatternArgs {
     /// Change the default language to use for the pattern (if unset, JavaScript is used by default)
     #[clap(long = "language", alias = "lang")]
     pub language: Option<PatternLanguage>,
+    /// Report the time spent in each pattern, predicate, function, and file
+    #[clap(long = "profile")]
+    pub profile: bool,
+    /// Explain how the pattern was evaluated against the code on a line, like `src/index.js:12`, instead of applying it
+    #[clap(
+        long = "explain",
+        value_name = "FILE:LINE",
+        conflicts_with = "interactive"
+    )]
+    pub explain: Option<String>,
+    /// Record the outcome for each file in a journal, so an interrupted apply can be resumed or reverted
+    #[clap(long = "journal", value_name = "FILE", conflicts_with = "dry_run")]
+    pub journal: Option<PathBuf>,
+    /// Continue the apply recorded in --journal, skipping files that were already processed and retrying failed ones
+    #[clap(long = "resume", requires = "journal", conflicts_with = "revert")]
+    pub resume: bool,
+    /// Restore the files rewritten in --journal to their original content
+    #[clap(long = "revert", requires = "journal")]
+    pub revert: bool,
+    /// Leave files unchanged when a rewrite would introduce a syntax error, instead of only warning about it
+    #[clap(long = "reject-invalid-rewrites")]
+    pub reject_invalid_rewrites: bool,
+    #[clap(flatten)]
+    pub limits: SharedLimitArgs,
 }
 
 impl Default for ApplyPatternArgs {
@@ -179,10 +211,28 @@ impl Default for ApplyPatternArgs {
             ai: Default::default(),
             language: Default::default(),
             stdin: Default::default(),
+            profile: Default::default(),
+            explain: Default::default(),
+            journal: Default::default(),
+            resume: Default::default(),
+            revert: Default::default(),
+            reject_invalid_rewrites: Default::default(),
+            limits: Default::default(),
         }
     }
 }
 
+/// Split an `--explain` target like `src/index.js:12` into its path and (1-based) line
+fn parse_explain_target(target: &str) -> Result<(PathBuf, u32)> {
+    let Some((path, line)) = target.rsplit_once(':') else {
+        bail!("--explain expects a file and a line, like src/index.js:12");
+    };
+    match line.parse::<u32>() {
+        Ok(line) if line > 0 && !path.is_empty() => Ok((PathBuf::from(path), line)),
+        _ => bail!("--explain expects a file and a line, like src/index.js:12"),
+    }
+}
+
 macro_rules! flushable_unwrap {
     ($flushable:expr, $expr:expr) => {
         match $expr {
@@ -214,6 +264,25 @@ pub(crate) async fn run_apply_pattern(
         .unwrap()
         .get_context()
         .unwrap();
+    let explain_target = arg
+        .explain
+        .as_deref()
+        .map(parse_explain_target)
+        .transpose()?;
+    if let (Some(journal), true) = (&arg.journal, arg.revert) {
+        let (reverted, errors) = Journal::revert(journal, &pattern)?;
+        for error in errors.iter() {
+            log::warn!("{}", error);
+        }
+        log::info!("Reverted {} files from {}", reverted, journal.display());
+        return Ok(());
+    }
+    context.reject_invalid_rewrites = arg.reject_invalid_rewrites;
+    set_file_limits(&arg.limits, &mut context, std::env::current_dir()?).await?;
+    let profiler = arg.profile.then(|| Arc::new(Profiler::new()));
+    if let Some(profiler) = &profiler {
+        context = context.with_profiler(profiler.clone());
+    }
 
     let format = OutputFormat::from_flags(
         format_flags,
@@ -463,6 +532,7 @@ pub(crate) async fn run_apply_pattern(
             processed: 0,
             found: 0,
             reason: AllDoneReason::NoInputPaths,
+            invalid_rewrites: 0,
         });
         emitter.emit(&all_done).unwrap();
         emitter.flush().await?;
@@ -531,6 +601,30 @@ pub(crate) async fn run_apply_pattern(
             .unwrap();
     }
 
+    if let Some((path, line)) = explain_target {
+        let file = match &final_input {
+            ApplyInput::Virtual(input) => input
+                .files
+                .iter()
+                .find(|file| PathBuf::from(&file.path) == path)
+                .cloned(),
+            ApplyInput::Disk(_) => None,
+        };
+        let file = match file {
+            Some(file) => file,
+            None => RichFile::new(
+                path.to_string_lossy().to_string(),
+                flushable_unwrap!(emitter, fs_err::read_to_string(&path)),
+            ),
+        };
+        let explanation = compiled.explain_file(&file, line, &context);
+        emitter.flush().await?;
+        return log_explanation(
+            &explanation,
+            matches!(format, OutputFormat::Json | OutputFormat::Jsonl),
+        );
+    }
+
     let warn_uncommitted = !arg.dry_run && !arg.force && has_uncommitted_changes(cwd.clone()).await;
     if warn_uncommitted && has_rewrite(&compiled.pattern, &compiled.definitions()) {
         let term = console::Term::stderr();
@@ -548,6 +642,14 @@ pub(crate) async fn run_apply_pattern(
         }
     }
 
+    let journal = flushable_unwrap!(
+        emitter,
+        arg.journal
+            .as_deref()
+            .map(|path| Journal::open(path, &pattern, arg.resume))
+            .transpose()
+    );
+
     let processed = AtomicI32::new(0);
 
     let mut emitter = par_apply_pattern(
@@ -560,6 +662,7 @@ pub(crate) async fn run_apply_pattern(
         &arg,
         &context,
         &format,
+        journal.as_ref(),
     )
     .await;
 
@@ -567,12 +670,20 @@ pub(crate) async fn run_apply_pattern(
         processed: processed.load(Ordering::SeqCst),
         found: details.matched,
         reason: AllDoneReason::AllMatchesFound,
+        invalid_rewrites: details.invalid_rewrites,
     });
 
     emitter.emit(&all_done).unwrap();
 
     emitter.flush().await?;
 
+    if let Some(profiler) = &profiler {
+        log_profile(
+            profiler,
+            matches!(format, OutputFormat::Json | OutputFormat::Jsonl),
+        )?;
+    }
+
     match emitter.get_fatal_error() {
         Some(e) => match format.is_always_ok() {
             (true, _) => return Ok(()),
@@ -1,27 +1,39 @@
 use anyhow::{bail, Result};
+use chrono::Utc;
 use clap::Args;
 use dashmap::DashMap;
-use grit_cache::paths::cache_for_cwd;
+use grit_cache::{
+    compiled::CompiledPatternCache,
+    paths::{cache_for_cwd, compiled_cache_for_cwd},
+};
+use grit_util::Profiler;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use log::info;
+use log::{debug, info};
 use marzano_core::{
     api::{
-        is_match, AllDone, AllDoneReason, EnforcementLevel, MatchReason, MatchResult, RewriteSource,
+        is_match, AllDone, AllDoneReason, EnforcementLevel, MatchResult, INVALID_REWRITE_LOG_LEVEL,
     },
     fs::apply_rewrite,
     problem::Problem,
+    suppress::SuppressComment,
+};
+use marzano_gritmodule::{
+    config::ResolvedGritDefinition, levels::PathLevels, searcher::find_root_grit_dir_from,
+    utils::extract_path,
 };
-use marzano_gritmodule::{config::ResolvedGritDefinition, utils::extract_path};
 use marzano_language::target_language::{expand_paths, PatternLanguage};
 use marzano_messenger::emit::{FlushableMessenger as _, VisibilityLevels};
 use marzano_util::cache::GritCache;
+use marzano_util::hasher::hash as hash_str;
 use marzano_util::rich_path::RichPath;
-use marzano_util::{finder::get_input_files, rich_path::RichFile};
+use marzano_util::{finder::get_input_files, rich_path::RichFile, runtime::ExecutionContext};
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use serde::Serialize;
 use std::{
-    collections::{HashMap, HashSet},
-    path::PathBuf,
+    collections::{BTreeMap, HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 use tokio::try_join;
 
@@ -31,6 +43,8 @@ use marzano_messenger::emit::Messager;
 use cli_server::check::CheckMessenger;
 
 use crate::{
+    analyze::injected_builtins,
+    baseline::Baseline,
     error::GoodError,
     flags::{GlobalFormatFlags, OutputFormat},
     github::{log_check_annotations, write_check_summary},
@@ -39,12 +53,14 @@ use crate::{
         get_grit_files_from, get_grit_files_from_flags_or_cwd, resolve_from, resolve_from_cwd,
         GritModuleResolver, Source,
     },
+    sarif::SarifMessenger,
     scan::log_check_json,
     updater::Updater,
-    ux::{get_check_summary, log_file, print_config, CheckResult},
+    ux::{get_check_summary, log_file, log_profile, print_config, CheckResult},
 };
 
 use super::filters::{extract_filter_ranges, SharedFilterArgs};
+use super::limits::{set_file_limits, SharedLimitArgs};
 
 #[derive(Args, Serialize, Debug)]
 pub struct CheckArg {
@@ -69,8 +85,200 @@ pub struct CheckArg {
     /// Output annotations for a GitHub actions workflow
     #[clap(long = "github-actions")]
     pub github_actions: bool,
+    /// Record every current violation in a baseline file, to suppress them in later runs with --baseline
+    #[clap(long = "write-baseline", conflicts_with = "baseline")]
+    pub write_baseline: Option<PathBuf>,
+    /// Only report violations that are not in the given baseline file
+    #[clap(long = "baseline", conflicts_with = "write_baseline")]
+    pub baseline: Option<PathBuf>,
+    /// Only honor grit-ignore comments that give a reason, like `grit-ignore: reason`
+    #[clap(long = "require-ignore-reason")]
+    pub require_ignore_reason: bool,
+    /// Report grit-ignore comments that no longer suppress anything
+    #[clap(long = "report-unused-ignores")]
+    pub report_unused_ignores: bool,
+    /// Report the time spent in each pattern, predicate, function, and file
+    #[clap(long = "profile")]
+    pub profile: bool,
     #[clap(flatten)]
     pub shared_filters: SharedFilterArgs,
+    #[clap(flatten)]
+    pub limits: SharedLimitArgs,
+}
+
+/// Load a compiled pattern from the cache, compiling and caching it on a miss.
+/// Patterns that can't be serialized, or cache entries that can't be loaded, are just compiled.
+fn get_cached_problem(
+    compiled_cache: &CompiledPatternCache,
+    key: &str,
+    body: &str,
+    lang: Option<PatternLanguage>,
+    compile: impl FnOnce() -> Result<Problem>,
+) -> Result<Problem> {
+    if let Some(bytes) = compiled_cache.get(key) {
+        let default_lang = lang.unwrap_or_default().try_into()?;
+        match Problem::from_cached(&bytes, body, default_lang, injected_builtins()) {
+            Ok(problem) => return Ok(problem),
+            Err(e) => debug!("Failed to load cached pattern {}: {}", key, e),
+        }
+    }
+    let problem = compile()?;
+    if let Err(e) = problem
+        .to_cached()
+        .and_then(|bytes| compiled_cache.put(key, &bytes))
+    {
+        debug!("Failed to cache compiled pattern {}: {}", key, e);
+    }
+    Ok(problem)
+}
+
+/// Matches are cached by file path as well as content, since the results include the path
+fn matches_cache_key(path: &RichPath, hash: [u8; 32]) -> [u8; 32] {
+    hash_str(&format!("{}\0{:?}", path.path.display(), hash))
+}
+
+/// Replay the results of a previous run on an unchanged file.
+/// Multifile patterns depend on more than one file, so their results are never cached.
+fn get_cached_matches(
+    cache: &impl GritCache,
+    path: &RichPath,
+    hash: [u8; 32],
+    pattern: &Problem,
+    pattern_hash: [u8; 32],
+) -> Option<Vec<MatchResult>> {
+    if pattern.is_multifile {
+        return None;
+    }
+    let serialized = cache.get_matches(matches_cache_key(path, hash), pattern_hash)?;
+    serde_json::from_slice(serialized).ok()
+}
+
+/// Matches and analysis logs are what a run reports for a file, so both are cached
+fn is_cached_result(result: &MatchResult) -> bool {
+    is_match(result) || matches!(result, MatchResult::AnalysisLog(_))
+}
+
+/// Cache the results for each file that was executed, so unchanged files can be replayed next time
+fn put_cached_matches(
+    cache: &impl GritCache,
+    files: &[&RichPath],
+    pattern: &Problem,
+    pattern_hash: [u8; 32],
+    results: &[MatchResult],
+) {
+    if pattern.is_multifile {
+        return;
+    }
+    for path in files {
+        let Some(hash) = path.hash else { continue };
+        let name = path.path.to_string_lossy();
+        let file_results: Vec<_> = results
+            .iter()
+            .filter(|m| m.file_name() == Some(name.as_ref()))
+            .cloned()
+            .collect();
+        // Files that were skipped run again next time, since running out of time depends on the machine
+        let skipped = file_results
+            .iter()
+            .any(|r| matches!(r, MatchResult::AnalysisLog(log) if log.level == 310));
+        if file_results.is_empty() || skipped {
+            continue;
+        }
+        let Ok(serialized) = serde_json::to_vec(&file_results) else {
+            continue;
+        };
+        // Only cache results that survive a round trip, so replays are identical to running the pattern
+        let round_trip: Option<Vec<MatchResult>> = serde_json::from_slice(&serialized).ok();
+        if round_trip.as_ref() != Some(&file_results) {
+            continue;
+        }
+        if let Err(e) = cache.put_matches(matches_cache_key(path, hash), pattern_hash, serialized) {
+            debug!("Failed to cache results for {}: {}", path.path.display(), e);
+        }
+    }
+}
+
+/// Suppression settings and file limits change which results are reported, so they are part of the cache key
+fn pattern_cache_hash(pattern: &Problem, context: &ExecutionContext) -> [u8; 32] {
+    let mut settings = String::new();
+    if context.require_suppress_reason {
+        settings.push_str("\0require_ignore_reason");
+    }
+    if let Some(millis) = context.max_file_millis {
+        settings.push_str(&format!("\0max_file_millis={}", millis));
+    }
+    if let Some(steps) = context.max_file_steps {
+        settings.push_str(&format!("\0max_file_steps={}", steps));
+    }
+    if settings.is_empty() {
+        pattern.hash
+    } else {
+        hash_str(&format!("{:?}{}", pattern.hash, settings))
+    }
+}
+
+/// Find grit-ignore comments that don't suppress a match for any of the patterns
+fn find_unused_ignores(
+    problems: &[&Problem],
+    found_files: &DashMap<String, Vec<RichPath>>,
+    context: &ExecutionContext,
+) -> Result<Vec<(String, SuppressComment)>> {
+    let mut comments: BTreeMap<(String, u32), (SuppressComment, bool)> = BTreeMap::new();
+    for entry in found_files.iter() {
+        let language_problems: Vec<_> = problems
+            .iter()
+            .filter(|p| p.language.to_string() == *entry.key())
+            .collect();
+        for path in entry.value() {
+            let Ok(content) = fs_err::read_to_string(&path.path) else {
+                continue;
+            };
+            if !content.contains("grit-ignore") {
+                continue;
+            }
+            let file = RichFile::new(path.path.to_string_lossy().to_string(), content);
+            for problem in &language_problems {
+                for (comment, used) in problem.audit_suppressions(&file, context)? {
+                    let (_, any_used) = comments
+                        .entry((file.path.clone(), comment.range.start_byte))
+                        .or_insert((comment, false));
+                    *any_used |= used;
+                }
+            }
+        }
+    }
+    Ok(comments
+        .into_iter()
+        .filter(|(_, (_, used))| !used)
+        .map(|((path, _), (comment, _))| (path, comment))
+        .collect())
+}
+
+fn log_unused_ignores(unused: &[(String, SuppressComment)], require_reason: bool) {
+    if unused.is_empty() {
+        return;
+    }
+    let today = Utc::now().date_naive();
+    info!("{} unused grit-ignore comments:", unused.len());
+    for (path, comment) in unused {
+        let explanation = match comment.directive() {
+            Some(directive) if directive.is_expired(today) => {
+                format!("expired on {}", directive.until.unwrap_or_default())
+            }
+            Some(directive) if require_reason && directive.reason.is_none() => {
+                "is missing a reason".to_string()
+            }
+            _ => "no longer suppresses anything".to_string(),
+        };
+        info!(
+            "  {}:{}    {}    {}",
+            path,
+            comment.range.start.line,
+            comment.text.trim(),
+            explanation
+        );
+    }
+    info!("\n");
 }
 
 pub(crate) async fn run_check(
@@ -83,10 +291,18 @@ pub(crate) async fn run_check(
     if format.json && arg.github_actions {
         bail!("--github-actions is not compatible with --json");
     }
+    if format.sarif && arg.github_actions {
+        bail!("--github-actions is not compatible with --sarif");
+    }
 
-    let context = Updater::from_current_bin().await?.get_context()?;
+    let mut context = Updater::from_current_bin().await?.get_context()?;
+    context.require_suppress_reason = arg.require_ignore_reason;
+    let profiler = arg.profile.then(|| Arc::new(Profiler::new()));
+    if let Some(profiler) = &profiler {
+        context = context.with_profiler(profiler.clone());
+    }
 
-    let (cache, manager) = cache_for_cwd(arg.refresh_cache, arg.no_cache).await?;
+    let compiled_cache = compiled_cache_for_cwd(arg.refresh_cache, arg.no_cache).await?;
 
     let paths = arg.paths;
     let ((resolved_patterns, _), grit_files) = if plumbing {
@@ -108,45 +324,72 @@ pub(crate) async fn run_check(
         ]?
     };
 
+    let current_dir = if plumbing {
+        paths.first().unwrap().to_owned()
+    } else {
+        std::env::current_dir()?
+    };
+
+    set_file_limits(&arg.limits, &mut context, current_dir.clone()).await?;
+
+    // Levels can be overridden for some paths, so run every pattern that is enforced anywhere
+    let path_levels = match find_root_grit_dir_from(current_dir.clone()).await {
+        Some(grit_dir) => match grit_dir.parent() {
+            Some(repo_dir) => PathLevels::load(repo_dir)?,
+            None => PathLevels::default(),
+        },
+        None => PathLevels::default(),
+    };
+    let min_level = arg.level.clone().unwrap_or(EnforcementLevel::Warn);
     let enforced = resolved_patterns
         .iter()
         .filter(|p| {
-            &p.level() >= arg.level.as_ref().unwrap_or(&EnforcementLevel::Warn)
+            path_levels.max_level(p) >= min_level
                 && !matches!(p.language, PatternLanguage::Universal)
         })
         .collect::<Vec<_>>();
 
-    let current_dir = if plumbing {
-        paths.first().unwrap().to_owned()
-    } else {
-        std::env::current_dir()?
-    };
-
     let filter_range = extract_filter_ranges(&arg.shared_filters, Some(&current_dir))?;
 
     // Construct a resolver
     let resolver = GritModuleResolver::new();
 
     let mut pattern_lookup: HashMap<[u8; 32], &ResolvedGritDefinition> = HashMap::new();
+    let mut libs_hashes: HashMap<Option<PatternLanguage>, [u8; 32]> = HashMap::new();
     let compile_tasks: Result<HashMap<String, Problem>, _> = enforced
         .iter()
         .map(|p| {
-            let body = format!("{}()", p.local_name);
+            let body = p.call();
             let lang = PatternLanguage::get_language(&p.body);
             let grit_files = grit_files.get_language_directory_or_default(lang)?;
             let rich_pattern = resolver
                 .make_pattern(&body, Some(p.local_name.to_string()))
                 .unwrap();
-            let lang = PatternLanguage::get_language(&p.body);
-            match rich_pattern.compile(&grit_files, lang, filter_range.clone(), None) {
-                Ok(c) => {
-                    pattern_lookup.insert(c.problem.hash, p);
-                    Ok((p.local_name.clone(), c.problem))
-                }
-                Err(e) => {
-                    bail!("Unable to compile pattern {}:\n{}", p.local_name, e);
+            let compile =
+                || match rich_pattern.compile(&grit_files, lang, filter_range.clone(), None) {
+                    Ok(c) => Ok(c.problem),
+                    Err(e) => {
+                        bail!("Unable to compile pattern {}:\n{}", p.local_name, e);
+                    }
+                };
+            // Filter ranges are compiled into the pattern, so only unfiltered runs are cached
+            let problem = match (&compiled_cache, &filter_range) {
+                (Some(compiled_cache), None) => {
+                    let libs_hash = *libs_hashes
+                        .entry(lang)
+                        .or_insert_with(|| CompiledPatternCache::hash_libs(&grit_files));
+                    let key = CompiledPatternCache::key(
+                        env!("CARGO_PKG_VERSION"),
+                        Some(&p.local_name),
+                        &body,
+                        libs_hash,
+                    );
+                    get_cached_problem(compiled_cache, &key, &body, lang, compile)?
                 }
-            }
+                _ => compile()?,
+            };
+            pattern_lookup.insert(problem.hash, p);
+            Ok((p.local_name.clone(), problem))
         })
         .collect();
     let compiled_map = compile_tasks?;
@@ -184,6 +427,8 @@ pub(crate) async fn run_check(
     pg.set_style(style);
     pg.set_prefix("Checking");
 
+    // Only the checks below write to the cache, so it is joined before any result is reported
+    let (cache, manager) = cache_for_cwd(arg.refresh_cache, arg.no_cache).await?;
     problems.par_iter().for_each(|pattern| {
         if let Some(name) = &pattern.name {
             pg.set_message(name.to_string());
@@ -192,25 +437,75 @@ pub(crate) async fn run_check(
             Some(files) => files,
             None => return,
         };
+        let pattern_hash = pattern_cache_hash(pattern, &context);
+        let mut cached_results = vec![];
         let un_cached_input_files: Vec<_> = language_files
             .iter()
             .filter(|path| {
                 let Some(hash) = path.hash else { return true };
-                !cache.has_no_matches(hash, pattern.hash)
+                if cache.has_no_matches(hash, pattern_hash) {
+                    return false;
+                }
+                match get_cached_matches(&cache, path, hash, pattern, pattern_hash) {
+                    Some(matches) => {
+                        cached_results.extend(matches);
+                        false
+                    }
+                    None => true,
+                }
+            })
+            .collect();
+        let (result, no_match) = pattern.execute_paths(un_cached_input_files.clone(), &context);
+        // Results that depend on an expiring grit-ignore comment can change without the file changing
+        let depends_on_date: HashSet<&str> = result
+            .iter()
+            .filter_map(|r| match r {
+                MatchResult::DoneFile(done) if done.depends_on_date => {
+                    Some(done.relative_file_path.as_str())
+                }
+                _ => None,
             })
             .collect();
-        let (result, no_match) = pattern.execute_paths(un_cached_input_files, &context);
-        if !no_match.is_empty() {
-            for path in no_match.into_iter() {
-                let hash = path.hash.unwrap();
-                cache.put_no_matches(hash, pattern.hash).unwrap();
+        let is_cacheable = |path: &RichPath| {
+            !arg.no_cache && !depends_on_date.contains(path.path.to_string_lossy().as_ref())
+        };
+        let cacheable_files: Vec<_> = un_cached_input_files
+            .into_iter()
+            .filter(|path| is_cacheable(path))
+            .collect();
+        let reported: Vec<_> = result.into_iter().filter(is_cached_result).collect();
+        // Files that only produced logs are cached with their logs, rather than as having no matches
+        let logged: HashSet<&str> = reported.iter().filter_map(|r| r.file_name()).collect();
+        for path in no_match.into_iter().filter(|path| is_cacheable(path)) {
+            if logged.contains(path.path.to_string_lossy().as_ref()) {
+                continue;
+            }
+            let hash = path.hash.unwrap();
+            if let Err(e) = cache.put_no_matches(hash, pattern_hash) {
+                debug!(
+                    "Failed to cache no matches for {}: {}",
+                    path.path.display(),
+                    e
+                );
             }
         }
+        put_cached_matches(&cache, &cacheable_files, pattern, pattern_hash, &reported);
         let mut entry = results.entry(pattern.hash).or_default();
-        entry.extend(result.into_iter().filter(is_match));
+        entry.extend(reported);
+        entry.extend(cached_results);
         pg.inc(1);
     });
 
+    drop(cache);
+    if let Some(manager) = manager {
+        match manager.join() {
+            Ok(_) => {}
+            Err(e) => {
+                bail!("Error joining cache manager: {:?}", e);
+            }
+        }
+    }
+
     let mut check_results: HashMap<String, Vec<CheckResult>> = HashMap::new();
 
     for result in results.iter() {
@@ -223,10 +518,19 @@ pub(crate) async fn run_check(
         let relevant_results = match_results
             .par_iter()
             .filter_map(|r| {
+                if let MatchResult::AnalysisLog(log) = r {
+                    debug!("{}: {}", log.file, log.message);
+                    return None;
+                }
                 let path = extract_path(r)?;
+                let level = path_levels.level_for(pattern, Path::new(path));
+                if level < min_level {
+                    return None;
+                }
                 let check_result = CheckResult {
                     pattern,
                     result: r.clone(),
+                    level,
                 };
                 Some((path.to_string(), check_result))
             })
@@ -239,6 +543,47 @@ pub(crate) async fn run_check(
 
     pg.finish_and_clear();
 
+    // Results read from the cache are not profiled, so use --no-cache for a complete profile
+    if let Some(profiler) = &profiler {
+        log_profile(profiler, format.json || format.jsonl || plumbing)?;
+    }
+
+    if let Some(baseline_path) = &arg.write_baseline {
+        let baseline = Baseline::from_results(check_results.values().flatten(), &current_dir);
+        baseline.write(baseline_path)?;
+        info!(
+            "Wrote {} violations to baseline {}",
+            baseline.entries.len(),
+            baseline_path.display()
+        );
+        return Ok(());
+    }
+
+    let check_results = match &arg.baseline {
+        Some(baseline_path) => {
+            let baseline = Baseline::read(baseline_path)?;
+            let (new_results, suppressed, fixed) = baseline.filter(check_results, &current_dir);
+            if suppressed > 0 {
+                info!(
+                    "Suppressed {} violations found in baseline {}",
+                    suppressed,
+                    baseline_path.display()
+                );
+            }
+            if !fixed.is_empty() {
+                info!(
+                    "{} baseline entries no longer match and can be pruned with --write-baseline:",
+                    fixed.len()
+                );
+                for entry in fixed {
+                    info!("  {}:{}    {}", entry.file, entry.line, entry.pattern);
+                }
+            }
+            new_results
+        }
+        None => check_results,
+    };
+
     if plumbing {
         let format = OutputFormat::from(format);
         let format = if format == OutputFormat::Standard {
@@ -269,6 +614,9 @@ pub(crate) async fn run_check(
             | crate::messenger_variant::MessengerVariant::JsonLine(_) => {
                 info!("Local only, skipping check registration.");
             }
+            crate::messenger_variant::MessengerVariant::Sarif(ref mut m) => {
+                m.register_patterns(&enforced)?
+            }
             #[cfg(feature = "server")]
             crate::messenger_variant::MessengerVariant::Redis(ref mut m) => {
                 m.mark_checked_patterns(&enforced)?
@@ -287,16 +635,8 @@ pub(crate) async fn run_check(
             for result in results {
                 let rewrite_with_reason = match &result.result {
                     MatchResult::Rewrite(r) => {
-                        let reason = Some(MatchReason {
-                            metadata_json: None,
-                            source: RewriteSource::Gritql,
-                            title: result.pattern.title().map(|s| s.to_string()),
-                            name: Some(result.pattern.local_name.to_string()),
-                            level: Some(result.pattern.level()),
-                            explanation: None,
-                        });
                         let mut rewrite = r.clone();
-                        rewrite.reason = reason;
+                        rewrite.reason = Some(result.reason());
                         Some(MatchResult::Rewrite(rewrite))
                     }
                     _ => None,
@@ -307,10 +647,23 @@ pub(crate) async fn run_check(
             }
         }
         let safe_total_file_count = std::cmp::min(total_file_count, i32::MAX as usize) as i32;
+        // Analysis logs are not check results, so count invalid rewrites from the raw results
+        let is_invalid_rewrite = |r: &MatchResult| matches!(r, MatchResult::AnalysisLog(log) if log.level == INVALID_REWRITE_LOG_LEVEL);
+        let invalid_rewrites = results
+            .iter()
+            .map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .filter(|r| is_invalid_rewrite(r))
+                    .count()
+            })
+            .sum::<usize>();
         let all_done = MatchResult::AllDone(AllDone {
             processed: safe_total_file_count,
             found: 0,
             reason: AllDoneReason::AllMatchesFound,
+            invalid_rewrites: std::cmp::min(invalid_rewrites, i32::MAX as usize) as i32,
         });
         emitter.emit(&all_done).unwrap();
 
@@ -337,6 +690,26 @@ pub(crate) async fn run_check(
         return Ok(());
     }
 
+    if format.sarif {
+        let mut emitter = SarifMessenger::new(io::stdout(), None, VisibilityLevels::Primary);
+        emitter.register_patterns(&enforced)?;
+
+        let mut sorted_results: Vec<(&String, &Vec<CheckResult<'_>>)> =
+            check_results.iter().collect();
+        sorted_results.sort_by_key(|(k, _)| *k);
+        for result in sorted_results.into_iter().flat_map(|(_, v)| v.iter()) {
+            let mut message = result.result.clone();
+            match &mut message {
+                MatchResult::Match(m) => m.reason = Some(result.reason()),
+                MatchResult::Rewrite(r) => r.reason = Some(result.reason()),
+                _ => {}
+            }
+            emitter.emit(&message)?;
+        }
+        emitter.flush().await?;
+        return Ok(());
+    }
+
     if format.json {
         let found_files = found_files.into_read_only();
         let all_files = found_files
@@ -354,8 +727,18 @@ pub(crate) async fn run_check(
         info!("\n");
     }
 
+    let unused_ignores = if arg.report_unused_ignores {
+        find_unused_ignores(&problems, &found_files, &context)?
+    } else {
+        vec![]
+    };
+    log_unused_ignores(&unused_ignores, arg.require_ignore_reason);
+
     if check_results.is_empty() {
         info!("No results found, checked {} patterns.", enforced.len());
+        if !unused_ignores.is_empty() {
+            bail!(GoodError::new());
+        }
         return Ok(());
     }
 
@@ -391,16 +774,6 @@ pub(crate) async fn run_check(
         log_file(file, check_results, arg.fix);
     }
 
-    drop(cache);
-    if let Some(manager) = manager {
-        match manager.join() {
-            Ok(_) => {}
-            Err(e) => {
-                bail!("Error joining cache manager: {:?}", e);
-            }
-        }
-    }
-
     let files = check_results.len();
     if arg.fix {
         info!("{} files fixed.", files);
@@ -412,9 +785,50 @@ pub(crate) async fn run_check(
         );
         info!("{}", msg);
         // Make sure we fail if there are rewrites
-        if files > 0 && !arg.github_actions {
+        if (files > 0 || !unused_ignores.is_empty()) && !arg.github_actions {
             bail!(GoodError::new());
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::extract_rewritten_content;
+
+    #[test]
+    fn cached_problems_skip_compilation() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let compiled_cache = CompiledPatternCache::new(dir.path(), false)?;
+        let body = "`console.log($msg)` => `console.info($msg)`";
+        let rich_pattern =
+            GritModuleResolver::new().make_pattern(body, Some("use_info".to_string()))?;
+        let libs = BTreeMap::new();
+        let key = CompiledPatternCache::key(
+            "0.0.0",
+            Some("use_info"),
+            body,
+            CompiledPatternCache::hash_libs(&libs),
+        );
+
+        let compiled = get_cached_problem(&compiled_cache, &key, body, None, || {
+            Ok(rich_pattern.compile(&libs, None, None, None)?.problem)
+        })?;
+        let cached = get_cached_problem(&compiled_cache, &key, body, None, || {
+            panic!("cached patterns should not be compiled again")
+        })?;
+        assert_eq!(cached.hash, compiled.hash);
+
+        let file = RichFile::new("test.js".to_owned(), "console.log('hi');".to_owned());
+        let rewrite = |problem: &Problem| {
+            problem
+                .execute_file(&file, &ExecutionContext::default())
+                .iter()
+                .find_map(|r| extract_rewritten_content(r).cloned())
+        };
+        assert_eq!(rewrite(&compiled), Some("console.info('hi');".to_owned()));
+        assert_eq!(rewrite(&cached), rewrite(&compiled));
+        Ok(())
+    }
+}
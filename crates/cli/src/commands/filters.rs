@@ -4,7 +4,7 @@ use anyhow::Result;
 use clap::Args;
 
 use grit_util::{FileRange, UtilRange};
-use marzano_util::diff::FileDiff;
+use marzano_util::diff::{parse_modified_ranges, run_git_diff_from, FileDiff, GitDiffBase};
 use serde::Serialize;
 
 use crate::{community::parse_eslint_output, diff::extract_target_diffs};
@@ -15,16 +15,38 @@ pub struct SharedFilterArgs {
     #[clap(
         long = "only-in-json",
         help = r#"Only analyze ranges inside a provided eslint-style JSON string. The JSON should be an array of objects formatted as `[{"filePath": "path/to/file", "messages": [{"line": 1, "column": 1, "endLine": 1, "endColumn": 1}]}]`."#,
-        conflicts_with = "only_in_diff"
+        conflicts_with_all = &["only_in_diff", "since", "staged"]
     )]
     pub(crate) only_in_json: Option<String>,
     #[clap(
         long = "only-in-diff",
         help = "Only analyze ranges that are inside the provided unified diff, or the results of git diff HEAD if no diff is provided.",
         hide = true,
-        conflicts_with = "only_in_json"
+        conflicts_with_all = &["only_in_json", "since", "staged"]
     )]
     pub(crate) only_in_diff: Option<Option<String>>,
+    #[clap(
+        long = "since",
+        help = "Only analyze lines changed since the current branch diverged from the given git ref, including uncommitted changes.",
+        conflicts_with = "staged"
+    )]
+    pub(crate) since: Option<String>,
+    #[clap(
+        long = "staged",
+        help = "Only analyze lines changed in the changes staged for commit."
+    )]
+    pub(crate) staged: bool,
+}
+
+/// The diff to restrict matches to, from git or a provided diff
+fn extract_diffs(args: &SharedFilterArgs, root: Option<&PathBuf>) -> Result<Option<Vec<FileDiff>>> {
+    let base = match (&args.since, args.staged) {
+        (Some(since), _) => GitDiffBase::Since(since),
+        (None, true) => GitDiffBase::Staged,
+        (None, false) => return extract_target_diffs(&args.only_in_diff, root),
+    };
+    let diff = run_git_diff_from(root.unwrap_or(&std::env::current_dir()?), base)?;
+    Ok(Some(parse_modified_ranges(&diff)?))
 }
 
 #[tracing::instrument]
@@ -36,7 +58,7 @@ pub(crate) fn extract_filter_ranges(
         let json_ranges = parse_eslint_output(json_content)?;
         Ok(Some(json_ranges))
     } else {
-        let raw_diff = extract_target_diffs(&args.only_in_diff, root)?;
+        let raw_diff = extract_diffs(args, root)?;
         let Some(raw_diff) = raw_diff else {
             return Ok(None);
         };
@@ -65,5 +87,5 @@ pub(crate) fn extract_filter_diff(
     args: &SharedFilterArgs,
     root: Option<&PathBuf>,
 ) -> Result<Option<Vec<FileDiff>>> {
-    extract_target_diffs(&args.only_in_diff, root)
+    extract_diffs(args, root)
 }
@@ -13,7 +13,7 @@ use marzano_core::api::{DoneFile, MatchResult, Rewrite};
 use marzano_gritmodule::{config::ResolvedGritDefinition, parser::PatternFileExt};
 use marzano_language::{markdown_block::MarkdownBlock, target_language::TargetLanguage};
 use marzano_messenger::{
-    emit::{ApplyDetails, Messager},
+    emit::{ApplyDetails, InteractiveState, Messager},
     output_mode::OutputMode,
 };
 use serde::Serialize;
@@ -74,6 +74,7 @@ pub async fn run_format(arg: &FormatGritArgs, flags: &GlobalFormatFlags) -> Resu
         matched: 0,
         rewritten: 0,
         named_pattern: None,
+        invalid_rewrites: 0,
     };
     let dry_run = !arg.write;
 
@@ -85,7 +86,7 @@ pub async fn run_format(arg: &FormatGritArgs, flags: &GlobalFormatFlags) -> Resu
                 &mut details,
                 dry_run,
                 false,
-                &mut false,
+                &mut InteractiveState::default(),
                 None,
                 None,
                 None,
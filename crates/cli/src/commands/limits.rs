@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use marzano_gritmodule::{api::read_grit_config, searcher::find_root_grit_dir_from};
+use marzano_util::runtime::ExecutionContext;
+use serde::Serialize;
+
+#[derive(Args, Clone, Debug, Serialize, Default)]
+/// Shared arguments for limiting the work spent on each file in apply and check commands.
+pub struct SharedLimitArgs {
+    #[clap(
+        long = "max-file-millis",
+        value_name = "MILLIS",
+        help = "Stop matching a file after this many milliseconds, reporting it as skipped. Overrides `limits.max_file_millis` in grit.yaml and GRIT_MAX_FILE_MILLIS; 0 disables the limit."
+    )]
+    pub(crate) max_file_millis: Option<u64>,
+    #[clap(
+        long = "max-file-steps",
+        value_name = "STEPS",
+        help = "Stop matching a file after this many pattern steps, reporting it as skipped. Overrides `limits.max_file_steps` in grit.yaml and GRIT_MAX_FILE_STEPS; 0 disables the limit."
+    )]
+    pub(crate) max_file_steps: Option<u64>,
+}
+
+/// Set the per-file limits on the context, from the flags or the `limits` in the root grit.yaml.
+/// Limits left unset fall back to the environment when the pattern runs.
+pub(crate) async fn set_file_limits(
+    args: &SharedLimitArgs,
+    context: &mut ExecutionContext,
+    dir: PathBuf,
+) -> Result<()> {
+    let config_limits = match find_root_grit_dir_from(dir).await {
+        Some(grit_dir) => match grit_dir.parent() {
+            Some(repo_dir) => read_grit_config(repo_dir)
+                .await?
+                .map(|config| config.limits)
+                .unwrap_or_default(),
+            None => Default::default(),
+        },
+        None => Default::default(),
+    };
+    context.max_file_millis = args.max_file_millis.or(config_limits.max_file_millis);
+    context.max_file_steps = args.max_file_steps.or(config_limits.max_file_steps);
+    Ok(())
+}
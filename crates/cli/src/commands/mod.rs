@@ -13,6 +13,7 @@ pub(crate) mod init;
 pub(crate) mod install;
 pub(crate) mod list;
 pub(crate) mod lsp;
+pub(crate) mod modules;
 
 pub(crate) mod check;
 
@@ -22,6 +23,7 @@ pub(crate) mod patterns;
 pub(crate) mod patterns_list;
 pub(crate) mod patterns_test;
 pub(crate) mod plumbing;
+pub(crate) mod repl;
 pub(crate) mod version;
 
 #[cfg(feature = "workflows_v2")]
@@ -67,6 +69,7 @@ use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Registry};
 #[cfg(feature = "docgen")]
 pub(crate) mod docgen;
 mod filters;
+mod limits;
 
 use crate::{
     analytics::{
@@ -92,9 +95,11 @@ use list::ListArgs;
 use log::LevelFilter;
 use lsp::LspArgs;
 use marzano_messenger::emit::ApplyDetails;
+use modules::{ModuleCommands, Modules};
 use parse::ParseArgs;
 use patterns::{PatternCommands, Patterns};
 use plumbing::PlumbingArgs;
+use repl::ReplArgs;
 use serde::Serialize;
 use std::io::Write;
 use std::process::{ChildStdin, Command, Stdio};
@@ -126,11 +131,13 @@ use self::{
     install::run_install,
     list::run_list_all,
     lsp::run_lsp,
+    modules::{run_modules_update, run_modules_vendor},
     parse::run_parse,
     patterns::{run_patterns_describe, run_patterns_edit},
     patterns_list::run_patterns_list,
     patterns_test::run_patterns_test,
     plumbing::run_plumbing,
+    repl::run_repl,
     version::run_version,
 };
 
@@ -158,6 +165,9 @@ pub enum Commands {
     Install(InstallArgs),
     /// Install grit modules
     Init(InitArgs),
+    /// Module commands, run `grit modules --help` for more information
+    #[clap(name = "modules")]
+    Modules(Modules),
     /// Hidden command for parsing input files, consumed by provolone
     #[clap(name = "parse", hide = true)]
     Parse(ParseArgs),
@@ -168,6 +178,8 @@ pub enum Commands {
     /// Patterns commands, run `grit patterns --help` for more information
     #[clap(name = "patterns")]
     Patterns(Patterns),
+    /// Interactively write and run patterns against a set of files
+    Repl(ReplArgs),
     /// Plumbing subcommands for easy machine integration
     #[clap(subcommand, name = "plumbing", hide = true)]
     Plumbing(PlumbingArgs),
@@ -200,6 +212,10 @@ impl fmt::Display for Commands {
             },
             Commands::Install(_) => write!(f, "install"),
             Commands::Init(_) => write!(f, "init"),
+            Commands::Modules(arg) => match arg.modules_commands {
+                ModuleCommands::Update(_) => write!(f, "modules update"),
+                ModuleCommands::Vendor(_) => write!(f, "modules vendor"),
+            },
             Commands::Parse(_) => write!(f, "parse"),
             Commands::Patterns(arg) => match arg.patterns_commands {
                 PatternCommands::List(_) => write!(f, "patterns list"),
@@ -219,6 +235,7 @@ impl fmt::Display for Commands {
                 WorkflowCommands::Watch(_) => write!(f, "workflows watch"),
                 WorkflowCommands::Upload(_) => write!(f, "workflows upload"),
             },
+            Commands::Repl(_) => write!(f, "repl"),
             Commands::Plumbing(_) => write!(f, "plumbing"),
             Commands::Version(_) => write!(f, "version"),
             Commands::Format(_) => write!(f, "format"),
@@ -334,7 +351,9 @@ fn setup_env_logger(app: &App, multi: &MultiProgress) {
     logger.target(match format {
         OutputFormat::Standard => env_logger::Target::Stdout,
         OutputFormat::Transformed => env_logger::Target::Stderr,
-        OutputFormat::Json | OutputFormat::Jsonl => env_logger::Target::Stderr,
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Sarif => {
+            env_logger::Target::Stderr
+        }
         #[cfg(feature = "remote_redis")]
         OutputFormat::Redis => env_logger::Target::Stderr,
         #[cfg(feature = "remote_pubsub")]
@@ -397,6 +416,7 @@ async fn run_command(_use_tracing: bool) -> Result<()> {
         matched: 0,
         rewritten: 0,
         named_pattern: None,
+        invalid_rewrites: 0,
     };
     let start = Instant::now();
 
@@ -438,6 +458,10 @@ async fn run_command(_use_tracing: bool) -> Result<()> {
             Commands::Lsp(arg) => run_lsp(arg).await,
             Commands::Install(arg) => run_install(arg).await,
             Commands::Init(arg) => run_init(arg).await,
+            Commands::Modules(arg) => match arg.modules_commands {
+                ModuleCommands::Update(arg) => run_modules_update(arg).await,
+                ModuleCommands::Vendor(arg) => run_modules_vendor(arg).await,
+            },
             Commands::Parse(arg) => run_parse(arg, app.format_flags, None).await,
             Commands::Patterns(arg) => match arg.patterns_commands {
                 PatternCommands::List(arg) => run_patterns_list(arg, app.format_flags).await,
@@ -459,6 +483,7 @@ async fn run_command(_use_tracing: bool) -> Result<()> {
                     .await
                     .map(|_| ()),
             },
+            Commands::Repl(arg) => run_repl(arg, &app.format_flags).await,
             Commands::Plumbing(arg) => {
                 run_plumbing(arg, multi, &mut apply_details, app.format_flags).await
             }
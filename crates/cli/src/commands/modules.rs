@@ -0,0 +1,146 @@
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use log::info;
+use marzano_gritmodule::{
+    config::VENDOR_DIR,
+    config::{init_config_from_path, init_global_grit_modules},
+    fetcher::{CleanFetcherKind, KeepFetcherKind, ModuleRepo},
+    lockfile::{GritLock, LOCKFILE_NAME},
+    searcher::{find_global_grit_dir, find_root_grit_dir_from},
+    vendor::vendor_modules,
+};
+use serde::Serialize;
+
+#[derive(Parser, Debug, Serialize)]
+pub struct Modules {
+    #[structopt(subcommand)]
+    pub modules_commands: ModuleCommands,
+}
+
+#[derive(Subcommand, Debug, Serialize)]
+pub enum ModuleCommands {
+    /// Fetch the latest revision of grit modules and record it in grit.lock
+    Update(ModulesUpdateArgs),
+    /// Copy all resolved modules into .grit/vendor, so patterns can be resolved without network access
+    Vendor(ModulesVendorArgs),
+}
+
+#[derive(Args, Debug, Serialize)]
+pub struct ModulesUpdateArgs {
+    /// Modules to update, like github.com/getgrit/stdlib (defaults to every module)
+    #[clap(value_parser)]
+    modules: Vec<String>,
+    /// Update global grit modules
+    #[clap(long = "global", default_value = "false")]
+    global: bool,
+}
+
+#[derive(Args, Debug, Serialize)]
+pub struct ModulesVendorArgs {}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+pub(crate) async fn run_modules_update(arg: ModulesUpdateArgs) -> Result<()> {
+    let grit_dir = if arg.global {
+        find_global_grit_dir().await?
+    } else {
+        let cwd = std::env::current_dir()?;
+        find_root_grit_dir_from(cwd)
+            .await
+            .context("No .grit directory found, run `grit init` to create one")?
+    };
+
+    // Modules without a locked commit are fetched at their latest revision
+    let previous = GritLock::read(&grit_dir)?;
+    let mut unlocked = previous.clone();
+    if arg.modules.is_empty() {
+        unlocked.modules.clear();
+    }
+    for module in arg.modules.iter() {
+        let repo = ModuleRepo::from_repo_str(module)?;
+        if unlocked.modules.remove(&repo.provider_name).is_none() {
+            bail!(
+                "{} is not in {}",
+                module,
+                grit_dir.join(LOCKFILE_NAME).display()
+            );
+        }
+    }
+    unlocked.write(&grit_dir)?;
+
+    if arg.global {
+        init_global_grit_modules::<CleanFetcherKind>(None).await?;
+    } else {
+        let repo_dir = grit_dir
+            .parent()
+            .context("Unable to find parent of .grit directory")?;
+        init_config_from_path::<CleanFetcherKind>(repo_dir.to_path_buf(), false).await?;
+    }
+
+    let updated = GritLock::read(&grit_dir)?;
+    let mut changed = 0;
+    for (name, locked) in updated.modules.iter() {
+        match previous.modules.get(name) {
+            Some(old) if old.commit == locked.commit => {}
+            Some(old) => {
+                changed += 1;
+                info!(
+                    "Updated {} from {} to {}",
+                    name,
+                    short_commit(&old.commit),
+                    short_commit(&locked.commit)
+                );
+            }
+            None => {
+                changed += 1;
+                info!("Locked {} at {}", name, short_commit(&locked.commit));
+            }
+        }
+    }
+    if changed == 0 {
+        info!("All modules are up to date");
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn run_modules_vendor(_arg: ModulesVendorArgs) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let grit_dir = find_root_grit_dir_from(cwd)
+        .await
+        .context("No .grit directory found, run `grit init` to create one")?;
+    let repo_dir = grit_dir
+        .parent()
+        .context("Unable to find parent of .grit directory")?
+        .to_path_buf();
+
+    // Set the current copies aside, so modules are fetched again instead of read from them
+    let vendor_dir = grit_dir.join(VENDOR_DIR);
+    let previous_dir = grit_dir.join(format!("{}.old", VENDOR_DIR));
+    if vendor_dir.exists() {
+        fs_err::rename(&vendor_dir, &previous_dir)?;
+    }
+    if let Err(e) = init_config_from_path::<KeepFetcherKind>(repo_dir, false).await {
+        if previous_dir.exists() {
+            fs_err::rename(&previous_dir, &vendor_dir)?;
+        }
+        return Err(e);
+    }
+
+    let vendored = vendor_modules(&grit_dir)?;
+    if previous_dir.exists() {
+        fs_err::remove_dir_all(&previous_dir)?;
+    }
+    for module in vendored.iter() {
+        info!("Vendored {}", module);
+    }
+    info!(
+        "Vendored {} modules into {}",
+        vendored.len(),
+        vendor_dir.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use dialoguer::{History, Input};
+use log::info;
+use marzano_core::{api::MatchResult, pattern_compiler::CompilationResult};
+use marzano_gritmodule::{
+    config::{REPO_CONFIG_DIR_NAME, REPO_CONFIG_PATTERNS_DIR},
+    patterns_directory::PatternsDirectory,
+    searcher::{find_global_grit_dir, find_grit_dir_from},
+    utils::{infer_pattern, is_pattern_name},
+};
+use marzano_language::target_language::PatternLanguage;
+use marzano_util::{rich_path::RichFile, runtime::ExecutionContext};
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    io::{BufRead, IsTerminal},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    flags::GlobalFormatFlags,
+    resolver::{get_grit_files_from_flags_or_cwd, GritModuleResolver},
+    result_formatting::FormattedResult,
+    updater::Updater,
+};
+
+const HISTORY_FILE: &str = "repl_history";
+const MAX_HISTORY: usize = 1000;
+
+const HELP: &str = "Type a pattern to run it against the loaded files, or one of:
+  :lang [language]   Show or change the default language for patterns
+  :file [paths...]   Show or replace the loaded files
+  :save <name>       Save the last pattern to .grit/patterns/<name>.md, with the first file as a sample
+  :help              Show this message
+  :quit              Exit the REPL";
+
+#[derive(Args, Debug, Serialize)]
+pub struct ReplArgs {
+    /// Files to run patterns against
+    #[clap(value_parser)]
+    pub paths: Vec<PathBuf>,
+    /// The default language for patterns (if unset, it is inferred from the first file)
+    #[clap(long = "language", alias = "lang")]
+    pub language: Option<PatternLanguage>,
+}
+
+/// Previous inputs, newest first, persisted between sessions
+struct ReplHistory {
+    path: Option<PathBuf>,
+    entries: VecDeque<String>,
+}
+
+impl ReplHistory {
+    fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs_err::read_to_string(path).ok())
+            .map(|content| content.lines().rev().map(String::from).collect())
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    fn push(&mut self, entry: String) {
+        if entry.trim().is_empty() || self.entries.front() == Some(&entry) {
+            return;
+        }
+        self.entries.push_front(entry);
+        self.entries.truncate(MAX_HISTORY);
+        // History is a convenience, so failing to save it shouldn't end the session
+        if let Some(path) = &self.path {
+            let content: Vec<&str> = self.entries.iter().rev().map(String::as_str).collect();
+            if let Some(parent) = path.parent() {
+                let _ = fs_err::create_dir_all(parent);
+            }
+            let _ = fs_err::write(path, content.join("\n") + "\n");
+        }
+    }
+}
+
+impl<T: ToString> History<T> for ReplHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, val: &T) {
+        self.push(val.to_string());
+    }
+}
+
+/// The last pattern that compiled, with its results
+struct LastRun {
+    body: String,
+    language: PatternLanguage,
+    results: Vec<MatchResult>,
+}
+
+struct Repl {
+    grit_files: PatternsDirectory,
+    language: PatternLanguage,
+    files: Vec<RichFile>,
+    context: ExecutionContext,
+    last_run: Option<LastRun>,
+}
+
+fn load_files(paths: &[PathBuf]) -> Result<Vec<RichFile>> {
+    paths
+        .iter()
+        .map(|path| {
+            let content = fs_err::read_to_string(path)?;
+            Ok(RichFile::new(path.to_string_lossy().to_string(), content))
+        })
+        .collect()
+}
+
+fn language_from_path(path: &Path) -> Option<PatternLanguage> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(PatternLanguage::from_extension)
+}
+
+fn show_bindings(variables: &[grit_util::VariableMatch], content: Option<&str>) {
+    let Some(content) = content else {
+        return;
+    };
+    for variable in variables {
+        if !variable.name.starts_with('$') || variable.name == "$match" {
+            continue;
+        }
+        for range in &variable.ranges {
+            if let Some(text) = content.get(range.start_byte as usize..range.end_byte as usize) {
+                info!("  {} = {}", variable.name.bold(), text.dimmed());
+            }
+        }
+    }
+}
+
+fn show_result(result: &MatchResult) {
+    match result {
+        MatchResult::Match(m) => {
+            if let Some(formatted) = FormattedResult::new(result.clone(), false) {
+                info!("{}", formatted);
+            }
+            show_bindings(&m.variables, m.content.as_deref());
+        }
+        MatchResult::Rewrite(r) => {
+            if let Some(formatted) = FormattedResult::new(result.clone(), false) {
+                info!("{}", formatted);
+            }
+            show_bindings(&r.original.variables, r.original.content.as_deref());
+        }
+        MatchResult::CreateFile(_) | MatchResult::RemoveFile(_) | MatchResult::AnalysisLog(_) => {
+            if let Some(formatted) = FormattedResult::new(result.clone(), false) {
+                info!("{}", formatted);
+            }
+        }
+        MatchResult::InputFile(_)
+        | MatchResult::DoneFile(_)
+        | MatchResult::AllDone(_)
+        | MatchResult::PatternInfo(_) => {}
+    }
+}
+
+/// Render a pattern as a markdown pattern file, with an optional sample input and output
+fn pattern_markdown(
+    name: &str,
+    body: &str,
+    language: PatternLanguage,
+    sample: Option<(&str, &str, Option<&str>)>,
+) -> String {
+    let mut markdown = format!("# {}\n\n```grit\n", name);
+    if PatternLanguage::get_language(body).is_none() {
+        markdown.push_str(&format!("language {}\n\n", language));
+    }
+    markdown.push_str(body.trim());
+    markdown.push_str("\n```\n");
+    if let Some((fence, input, output)) = sample {
+        markdown.push_str(&format!(
+            "\n## Sample\n\n```{}\n{}\n```\n",
+            fence,
+            input.trim_end()
+        ));
+        if let Some(output) = output {
+            markdown.push_str(&format!("\n```{}\n{}\n```\n", fence, output.trim_end()));
+        }
+    }
+    markdown
+}
+
+impl Repl {
+    fn run_pattern(&mut self, input: &str) -> Result<()> {
+        let (language, _, body) = infer_pattern(input, &self.grit_files);
+        let language = language.unwrap_or(self.language);
+        let libs = self
+            .grit_files
+            .get_language_directory_or_default(Some(language))?;
+        let resolver = GritModuleResolver::new();
+        let pattern = resolver.make_pattern(&body, None)?;
+        let CompilationResult {
+            problem,
+            compilation_warnings,
+        } = pattern.compile(&libs, Some(language), None, None)?;
+        for warning in compilation_warnings.into_iter() {
+            show_result(&MatchResult::AnalysisLog(warning.into()));
+        }
+
+        let mut results = vec![];
+        for file in &self.files {
+            results.extend(problem.execute_file(file, &self.context));
+        }
+        let found = results.iter().filter(|r| r.is_match()).count();
+        for result in &results {
+            show_result(result);
+        }
+        info!(
+            "{}",
+            format!("Found {} matches in {} files", found, self.files.len()).dimmed()
+        );
+
+        self.last_run = Some(LastRun {
+            body,
+            language,
+            results,
+        });
+        Ok(())
+    }
+
+    async fn save(&self, name: &str) -> Result<()> {
+        if !is_pattern_name(name) {
+            bail!("Invalid pattern name: '{}'. Pattern names must match the regex /^[A-Za-z_][A-Za-z0-9_]*$/", name);
+        }
+        let Some(last_run) = &self.last_run else {
+            bail!("There is no pattern to save yet");
+        };
+
+        let cwd = std::env::current_dir()?;
+        let grit_dir = find_grit_dir_from(cwd.clone())
+            .await
+            .unwrap_or_else(|| cwd.join(REPO_CONFIG_DIR_NAME));
+        let path = grit_dir
+            .join(REPO_CONFIG_PATTERNS_DIR)
+            .join(format!("{}.md", name));
+        if path.exists() {
+            bail!("{} already exists", path.display());
+        }
+
+        // Only files the pattern matched make useful samples
+        let sample = self.files.first().and_then(|file| {
+            let result = last_run.results.iter().find(|r| {
+                r.is_match()
+                    && r.extract_original_content().is_some()
+                    && r.file_name() == Some(file.path.as_str())
+            })?;
+            let fence = Path::new(&file.path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default();
+            let output = match result {
+                MatchResult::Rewrite(r) => r.rewritten.content.as_deref(),
+                _ => None,
+            };
+            Some((fence, file.content.as_str(), output))
+        });
+        if sample.is_none() {
+            info!("The pattern did not match the first file, so no sample was saved");
+        }
+
+        let markdown = pattern_markdown(name, &last_run.body, last_run.language, sample);
+        if let Some(parent) = path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::write(&path, markdown)?;
+        info!("Saved {} to {}", name.bold(), path.display());
+        Ok(())
+    }
+
+    /// Handle one line of input, returning false when the session should end
+    async fn eval(&mut self, line: &str) -> Result<bool> {
+        let line = line.trim();
+        let (command, rest) = line
+            .split_once(char::is_whitespace)
+            .map(|(command, rest)| (command, rest.trim()))
+            .unwrap_or((line, ""));
+        match command {
+            "" => {}
+            ":quit" | ":q" | ":exit" => return Ok(false),
+            ":help" | ":h" => info!("{}", HELP),
+            ":lang" if rest.is_empty() => info!("{}", self.language),
+            ":lang" => {
+                self.language = PatternLanguage::from_str(rest, true)
+                    .map_err(|_| anyhow!("Unknown language: {}", rest))?;
+            }
+            ":file" if rest.is_empty() => {
+                for file in &self.files {
+                    info!("{}", file.path);
+                }
+            }
+            ":file" => {
+                let paths: Vec<PathBuf> = rest.split_whitespace().map(PathBuf::from).collect();
+                self.files = load_files(&paths)?;
+            }
+            ":save" if rest.is_empty() => bail!("Usage: :save <name>"),
+            ":save" => self.save(rest).await?,
+            _ if command.starts_with(':') => {
+                bail!(
+                    "Unknown command {}, type :help for a list of commands",
+                    command
+                )
+            }
+            _ => self.run_pattern(line)?,
+        }
+        Ok(true)
+    }
+}
+
+pub(crate) async fn run_repl(arg: ReplArgs, flags: &GlobalFormatFlags) -> Result<()> {
+    let files = load_files(&arg.paths)?;
+    let language = arg
+        .language
+        .or_else(|| arg.paths.first().and_then(|path| language_from_path(path)))
+        .unwrap_or_default();
+    let grit_files = get_grit_files_from_flags_or_cwd(flags)
+        .await
+        .context("Failed to load patterns")?;
+    let context = Updater::from_current_bin().await?.get_context()?;
+
+    let mut repl = Repl {
+        grit_files,
+        language,
+        files,
+        context,
+        last_run: None,
+    };
+
+    // Piped input is read line by line, so sessions can be scripted
+    if !std::io::stdin().is_terminal() {
+        for line in std::io::stdin().lock().lines() {
+            match repl.eval(&line?).await {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => log::error!("{}", e),
+            }
+        }
+        return Ok(());
+    }
+
+    info!("{}", "Type :help for a list of commands".dimmed());
+    let history_path = find_global_grit_dir()
+        .await
+        .ok()
+        .map(|dir| dir.join(HISTORY_FILE));
+    let mut history = ReplHistory::load(history_path);
+    loop {
+        let line = match Input::<String>::new()
+            .with_prompt("grit")
+            .allow_empty(true)
+            .history_with(&mut history)
+            .interact_text()
+        {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        match repl.eval(&line).await {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => log::error!("{}", e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_pattern_with_sample() {
+        let markdown = pattern_markdown(
+            "no_console",
+            "`console.log($msg)` => .",
+            PatternLanguage::Tsx,
+            Some(("js", "console.log(1);\n", Some(""))),
+        );
+        assert_eq!(
+            markdown,
+            "# no_console\n\n```grit\nlanguage js\n\n`console.log($msg)` => .\n```\n\n## Sample\n\n```js\nconsole.log(1);\n```\n\n```js\n\n```\n"
+        );
+    }
+}
@@ -6,6 +6,9 @@ pub struct GlobalFormatFlags {
     /// Enable JSONL output, only supported on some commands
     #[arg(long, global = true, conflicts_with = "json")]
     pub jsonl: bool,
+    /// Enable SARIF 2.1.0 output, only supported on `check` and `apply`
+    #[arg(long, global = true, conflicts_with_all = ["json", "jsonl"])]
+    pub sarif: bool,
     #[cfg(feature = "remote_redis")]
     /// Enable Redis output, only supported on some commands
     #[arg(long, global = true, conflicts_with = "jsonl")]
@@ -29,6 +32,8 @@ pub enum OutputFormat {
     Transformed,
     Json,
     Jsonl,
+    /// Collect every result into a single SARIF log
+    Sarif,
     #[cfg(feature = "remote_redis")]
     Redis,
     #[cfg(feature = "remote_pubsub")]
@@ -57,6 +62,8 @@ impl OutputFormat {
             OutputFormat::Json
         } else if flags.jsonl {
             OutputFormat::Jsonl
+        } else if flags.sarif {
+            OutputFormat::Sarif
         } else {
             default
         }
@@ -70,7 +77,7 @@ impl OutputFormat {
         match self {
             OutputFormat::Standard => (false, false),
             OutputFormat::Transformed => (false, false),
-            OutputFormat::Json | OutputFormat::Jsonl => (true, true),
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Sarif => (true, true),
             #[cfg(feature = "remote_redis")]
             OutputFormat::Redis => (false, true),
             #[cfg(feature = "remote_pubsub")]
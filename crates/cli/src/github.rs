@@ -45,9 +45,9 @@ fn print_one(
 pub fn log_check_annotations(check_results: &Vec<&CheckResult<'_>>) {
     for result in check_results {
         let pattern = result.pattern;
+        let level = result.level.clone();
         let result = &result.result;
 
-        let level = pattern.level();
         let file = match extract_path(result).map(|p| p.as_str()) {
             Some(path) => path,
             None => continue,
@@ -0,0 +1,386 @@
+use anyhow::{bail, Context as _, Result};
+use marzano_core::api::{is_match, MatchResult};
+use marzano_util::hasher::hash;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const JOURNAL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FileStatus {
+    Pending,
+    Rewritten,
+    Unchanged,
+    Failed,
+    /// The file was rewritten, then restored with `--revert`
+    Reverted,
+}
+
+impl FileStatus {
+    /// Whether a resumed apply can skip the file
+    fn is_done(&self) -> bool {
+        matches!(self, FileStatus::Rewritten | FileStatus::Unchanged)
+    }
+}
+
+/// The first line of a journal, identifying the pattern it records
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalHeader {
+    version: u32,
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub path: String,
+    pub status: FileStatus,
+    /// Where the file was moved to, if the rewrite renamed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_path: Option<String>,
+    /// The content before the rewrite, kept so it can be reverted. `None` for created files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_hash: Option<String>,
+    /// The hash of the file after the rewrite, or `None` if it was removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewritten_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl JournalEntry {
+    fn new(path: String, status: FileStatus) -> Self {
+        Self {
+            path,
+            status,
+            new_path: None,
+            original: None,
+            original_hash: None,
+            rewritten_hash: None,
+            error: None,
+        }
+    }
+}
+
+fn hex_hash(content: &str) -> String {
+    hash(content).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A rewrite-like result, as (original path, new path, original content, rewritten content)
+fn file_change(result: &MatchResult) -> Option<(&str, &str, Option<&str>, Option<&str>)> {
+    match result {
+        MatchResult::Rewrite(r) => Some((
+            &r.original.source_file,
+            &r.rewritten.source_file,
+            r.original.content.as_deref(),
+            r.rewritten.content.as_deref(),
+        )),
+        MatchResult::CreateFile(c) => Some((
+            &c.rewritten.source_file,
+            &c.rewritten.source_file,
+            None,
+            c.rewritten.content.as_deref(),
+        )),
+        MatchResult::RemoveFile(r) => Some((
+            &r.original.source_file,
+            &r.original.source_file,
+            r.original.content.as_deref(),
+            None,
+        )),
+        _ => None,
+    }
+}
+
+/// Whether a file is empty or ends with a complete line
+fn ends_with_newline(path: &Path) -> Result<bool> {
+    let mut file = fs_err::File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+    file.seek(SeekFrom::End(-1))?;
+    let mut last = [0u8];
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+/// An append-only record of what `grit apply` did to each file.
+/// Each line is flushed as soon as a file is done, so the journal survives a crash or Ctrl-C,
+/// and later lines for a file replace earlier ones.
+pub struct Journal {
+    writer: Mutex<fs_err::File>,
+    entries: HashMap<String, JournalEntry>,
+}
+
+impl Journal {
+    /// Start a new journal, or continue an existing one when `resume` is set
+    pub fn open(path: &Path, pattern: &str, resume: bool) -> Result<Self> {
+        let entries = if resume {
+            Self::read(path, pattern)?
+        } else if path.exists() {
+            bail!(
+                "Journal {} already exists. Use --resume to continue it, or --revert to undo it.",
+                path.display()
+            );
+        } else {
+            HashMap::new()
+        };
+        let is_new = !path.exists();
+        let mut file = fs_err::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        if is_new {
+            let header = JournalHeader {
+                version: JOURNAL_VERSION,
+                pattern: pattern.to_string(),
+            };
+            writeln!(file, "{}", serde_json::to_string(&header)?)?;
+        } else if !ends_with_newline(path)? {
+            // Terminate a half-written last line, so new entries start on their own line
+            writeln!(file)?;
+        }
+        Ok(Self {
+            writer: Mutex::new(file),
+            entries,
+        })
+    }
+
+    /// The latest entry for each file in a journal
+    fn read(path: &Path, pattern: &str) -> Result<HashMap<String, JournalEntry>> {
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let reader = BufReader::new(fs_err::File::open(path)?);
+        let mut lines = reader.lines();
+        let header: JournalHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)
+                .with_context(|| format!("Invalid journal file {}", path.display()))?,
+            None => return Ok(HashMap::new()),
+        };
+        if header.version != JOURNAL_VERSION {
+            bail!(
+                "Unsupported journal version {} in {}",
+                header.version,
+                path.display()
+            );
+        }
+        if header.pattern != pattern {
+            bail!(
+                "Journal {} was recorded for a different pattern: {}",
+                path.display(),
+                header.pattern
+            );
+        }
+        let mut entries = HashMap::new();
+        for line in lines {
+            let line = line?;
+            // A crash can leave the last line half-written
+            let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else {
+                continue;
+            };
+            entries.insert(entry.path.clone(), entry);
+        }
+        Ok(entries)
+    }
+
+    /// Whether an earlier run already finished with the file
+    pub fn is_done(&self, path: &Path) -> bool {
+        self.entries
+            .get(path.to_string_lossy().as_ref())
+            .is_some_and(|entry| entry.status.is_done())
+    }
+
+    fn write(&self, entry: &JournalEntry) -> Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        writeln!(writer, "{}", line)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Record the files that are about to be processed
+    pub fn record_pending(&self, paths: &[PathBuf]) -> Result<()> {
+        for path in paths {
+            let path = path.to_string_lossy().to_string();
+            if !self.entries.contains_key(&path) {
+                self.write(&JournalEntry::new(path, FileStatus::Pending))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the outcome for the files in a batch of results, after they have been handled.
+    /// Whether a rewrite was applied is read back from disk, since it may have been rejected.
+    pub fn record_results(&self, results: &[MatchResult]) -> Result<()> {
+        let mut changed = Vec::new();
+        for result in results.iter().filter(|r| is_match(r)) {
+            let Some((path, new_path, original, rewritten)) = file_change(result) else {
+                continue;
+            };
+            let current = fs_err::read_to_string(new_path).ok();
+            let applied = path != new_path || current.as_deref() != original;
+            let mut entry = JournalEntry::new(
+                path.to_string(),
+                if applied {
+                    FileStatus::Rewritten
+                } else {
+                    FileStatus::Unchanged
+                },
+            );
+            if applied {
+                entry.new_path = (path != new_path).then(|| new_path.to_string());
+                entry.original = original.map(String::from);
+                entry.original_hash = original.map(hex_hash);
+                entry.rewritten_hash = rewritten.and(current.as_deref()).map(hex_hash);
+            }
+            changed.push(path);
+            self.write(&entry)?;
+        }
+
+        for result in results {
+            match result {
+                MatchResult::AnalysisLog(log) if result.is_error() => {
+                    if changed.contains(&log.file.as_str()) {
+                        continue;
+                    }
+                    let mut entry = JournalEntry::new(log.file.clone(), FileStatus::Failed);
+                    entry.error = Some(log.message.clone());
+                    changed.push(&log.file);
+                    self.write(&entry)?;
+                }
+                MatchResult::DoneFile(done) => {
+                    if changed.contains(&done.relative_file_path.as_str()) {
+                        continue;
+                    }
+                    self.write(&JournalEntry::new(
+                        done.relative_file_path.clone(),
+                        FileStatus::Unchanged,
+                    ))?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore every rewritten file to its original content, returning how many were restored.
+    /// Files that changed again since the rewrite are left alone and reported as errors.
+    pub fn revert(path: &Path, pattern: &str) -> Result<(usize, Vec<String>)> {
+        let journal = Self::open(path, pattern, true)?;
+        let mut reverted = 0;
+        let mut errors = Vec::new();
+        let mut entries: Vec<&JournalEntry> = journal
+            .entries
+            .values()
+            .filter(|entry| entry.status == FileStatus::Rewritten)
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        for entry in entries {
+            let target = entry.new_path.as_deref().unwrap_or(&entry.path);
+            let current = fs_err::read_to_string(target).ok();
+            if current.as_deref().map(hex_hash) != entry.rewritten_hash {
+                errors.push(format!(
+                    "{} has changed since it was rewritten, so it was not reverted",
+                    target
+                ));
+                continue;
+            }
+            if (entry.new_path.is_some() || entry.original.is_none()) && Path::new(target).exists()
+            {
+                fs_err::remove_file(target)?;
+            }
+            if let Some(original) = &entry.original {
+                if let Some(parent) = Path::new(&entry.path).parent() {
+                    fs_err::create_dir_all(parent)?;
+                }
+                fs_err::write(&entry.path, original)?;
+            }
+            journal.write(&JournalEntry::new(entry.path.clone(), FileStatus::Reverted))?;
+            reverted += 1;
+        }
+        Ok((reverted, errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use marzano_core::api::{DoneFile, Rewrite};
+
+    #[test]
+    fn resumes_and_reverts() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let journal_path = dir.path().join("journal.jsonl");
+        let changed = dir.path().join("changed.js");
+        let same = dir.path().join("same.js");
+        let changed_name = changed.to_string_lossy().to_string();
+        fs_err::write(&same, "let b = 2;\n")?;
+
+        let journal = Journal::open(&journal_path, "pattern", false)?;
+        journal.record_pending(&[changed.clone(), same.clone()])?;
+        // The rewrite has been applied by the time results are recorded
+        fs_err::write(&changed, "const a = 1;\n")?;
+        journal.record_results(&[
+            Rewrite::for_file(&changed_name, "let a = 1;\n", "const a = 1;\n").into(),
+            MatchResult::DoneFile(DoneFile::new(changed_name.clone())),
+        ])?;
+        drop(journal);
+
+        assert!(Journal::open(&journal_path, "pattern", false).is_err());
+        assert!(Journal::open(&journal_path, "other", true).is_err());
+        let resumed = Journal::open(&journal_path, "pattern", true)?;
+        assert!(resumed.is_done(&changed));
+        assert!(!resumed.is_done(&same));
+        drop(resumed);
+
+        let (reverted, errors) = Journal::revert(&journal_path, "pattern")?;
+        assert_eq!((reverted, errors.len()), (1, 0));
+        assert_eq!(fs_err::read_to_string(&changed)?, "let a = 1;\n");
+        assert_eq!(fs_err::read_to_string(&same)?, "let b = 2;\n");
+        assert!(!Journal::open(&journal_path, "pattern", true)?.is_done(&changed));
+
+        Ok(())
+    }
+
+    #[test]
+    fn resumes_after_a_half_written_line() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let journal_path = dir.path().join("journal.jsonl");
+        let first = dir.path().join("first.js");
+        let second = dir.path().join("second.js");
+
+        let journal = Journal::open(&journal_path, "pattern", false)?;
+        journal.record_results(&[MatchResult::DoneFile(DoneFile::new(
+            first.to_string_lossy().to_string(),
+        ))])?;
+        drop(journal);
+        // Simulate a crash in the middle of writing an entry
+        let mut file = fs_err::OpenOptions::new()
+            .append(true)
+            .open(&journal_path)?;
+        write!(file, "{{\"path\":\"half")?;
+        drop(file);
+
+        let resumed = Journal::open(&journal_path, "pattern", true)?;
+        resumed.record_results(&[MatchResult::DoneFile(DoneFile::new(
+            second.to_string_lossy().to_string(),
+        ))])?;
+        drop(resumed);
+
+        let resumed = Journal::open(&journal_path, "pattern", true)?;
+        assert!(resumed.is_done(&first));
+        assert!(resumed.is_done(&second));
+        Ok(())
+    }
+}
@@ -1,17 +1,20 @@
 mod analytics;
 mod analyze;
+mod baseline;
 pub mod commands;
 mod community;
 mod diff;
 pub mod error;
 mod flags;
 mod github;
+mod journal;
 mod jsonl;
 mod lister;
 mod messenger_variant;
 mod posthog;
 mod resolver;
 mod result_formatting;
+mod sarif;
 mod scan;
 mod updater;
 mod utils;
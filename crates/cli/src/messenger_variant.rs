@@ -23,6 +23,7 @@ use crate::{
     flags::OutputFormat,
     jsonl::JSONLineMessenger,
     result_formatting::{FormattedMessager, TransformedMessenger},
+    sarif::SarifMessenger,
 };
 
 #[allow(clippy::large_enum_variant)]
@@ -31,6 +32,7 @@ pub enum MessengerVariant<'a> {
     Formatted(FormattedMessager<'a>),
     JsonLine(JSONLineMessenger<'a>),
     Transformed(TransformedMessenger<'a>),
+    Sarif(SarifMessenger<'a>),
     #[cfg(feature = "remote_redis")]
     Redis(RedisMessenger),
     #[cfg(feature = "remote_pubsub")]
@@ -45,6 +47,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.get_min_level(),
             MessengerVariant::Transformed(m) => m.get_min_level(),
             MessengerVariant::JsonLine(m) => m.get_min_level(),
+            MessengerVariant::Sarif(m) => m.get_min_level(),
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.get_min_level(),
             #[cfg(feature = "remote_pubsub")]
@@ -59,6 +62,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.raw_emit(message),
             MessengerVariant::Transformed(m) => m.raw_emit(message),
             MessengerVariant::JsonLine(m) => m.raw_emit(message),
+            MessengerVariant::Sarif(m) => m.raw_emit(message),
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.raw_emit(message),
             #[cfg(feature = "remote_pubsub")]
@@ -73,6 +77,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.emit_log(log),
             MessengerVariant::Transformed(m) => m.emit_log(log),
             MessengerVariant::JsonLine(m) => m.emit_log(log),
+            MessengerVariant::Sarif(m) => m.emit_log(log),
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.emit_log(log),
             #[cfg(feature = "remote_pubsub")]
@@ -87,6 +92,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.emit_estimate(count),
             MessengerVariant::Transformed(m) => m.emit_estimate(count),
             MessengerVariant::JsonLine(m) => m.emit_estimate(count),
+            MessengerVariant::Sarif(m) => m.emit_estimate(count),
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.emit_estimate(count),
             #[cfg(feature = "remote_pubsub")]
@@ -101,6 +107,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.start_workflow(),
             MessengerVariant::Transformed(m) => m.start_workflow(),
             MessengerVariant::JsonLine(m) => m.start_workflow(),
+            MessengerVariant::Sarif(m) => m.start_workflow(),
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.start_workflow(),
             #[cfg(feature = "remote_pubsub")]
@@ -115,6 +122,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.finish_workflow(outcome).await,
             MessengerVariant::Transformed(m) => m.finish_workflow(outcome).await,
             MessengerVariant::JsonLine(m) => m.finish_workflow(outcome).await,
+            MessengerVariant::Sarif(m) => m.finish_workflow(outcome).await,
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.finish_workflow(outcome).await,
             #[cfg(feature = "remote_pubsub")]
@@ -129,6 +137,7 @@ impl<'a> Messager for MessengerVariant<'a> {
             MessengerVariant::Formatted(m) => m.get_workflow_status(),
             MessengerVariant::Transformed(m) => m.get_workflow_status(),
             MessengerVariant::JsonLine(m) => m.get_workflow_status(),
+            MessengerVariant::Sarif(m) => m.get_workflow_status(),
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Redis(m) => m.get_workflow_status(),
             #[cfg(feature = "remote_pubsub")]
@@ -147,7 +156,8 @@ impl<'a> WorkflowMessenger for MessengerVariant<'a> {
         match self {
             MessengerVariant::Formatted(_)
             | MessengerVariant::Transformed(_)
-            | MessengerVariant::JsonLine(_) => {
+            | MessengerVariant::JsonLine(_)
+            | MessengerVariant::Sarif(_) => {
                 // These are local, so no need to save metadata
                 log::debug!(
                     "Skipping save_metadata for local messenger: {} {:?}",
@@ -172,7 +182,8 @@ impl<'a> WorkflowMessenger for MessengerVariant<'a> {
         match self {
             MessengerVariant::Formatted(_)
             | MessengerVariant::Transformed(_)
-            | MessengerVariant::JsonLine(_) => {
+            | MessengerVariant::JsonLine(_)
+            | MessengerVariant::Sarif(_) => {
                 // For local emitters,, we will also apply rewrites
                 self.emit(&message.result)?;
                 self.apply_rewrite(&message.result)?;
@@ -206,6 +217,12 @@ impl<'a> From<TransformedMessenger<'a>> for MessengerVariant<'a> {
     }
 }
 
+impl<'a> From<SarifMessenger<'a>> for MessengerVariant<'a> {
+    fn from(value: SarifMessenger<'a>) -> Self {
+        Self::Sarif(value)
+    }
+}
+
 #[cfg(feature = "remote_redis")]
 impl<'a> From<cli_server::redis::RedisMessenger> for MessengerVariant<'a> {
     fn from(value: cli_server::redis::RedisMessenger) -> Self {
@@ -248,6 +265,7 @@ impl FlushableMessenger for MessengerVariant<'_> {
             MessengerVariant::GooglePubSub(ref mut pubsub) => pubsub.flush().await,
             #[cfg(feature = "remote_redis")]
             MessengerVariant::Combined(ref mut combined) => combined.flush().await,
+            MessengerVariant::Sarif(ref mut sarif) => sarif.flush().await,
             _ => {
                 // do nothing
                 Ok(())
@@ -294,6 +312,14 @@ pub async fn create_emitter<'a>(
             );
             jsonl.into()
         }
+        OutputFormat::Sarif => {
+            let sarif = SarifMessenger::new(
+                writer.unwrap_or_else(|| Box::new(io::stdout())),
+                pattern,
+                min_level,
+            );
+            sarif.into()
+        }
         #[cfg(feature = "remote_redis")]
         OutputFormat::Redis => {
             let messenger = RedisMessenger::create(mode, None, _root_path).await?;
@@ -14,7 +14,7 @@ use marzano_gritmodule::{
         find_and_resolve_grit_dir, find_local_patterns, find_user_patterns,
         get_grit_files_from_known_grit_dir, resolve_patterns,
     },
-    searcher::find_grit_dir_from,
+    searcher::find_root_grit_dir_from,
 };
 
 use crate::{flags::GlobalFormatFlags, updater::Updater};
@@ -97,7 +97,7 @@ pub async fn resolve_from(
     cwd: PathBuf,
     source: &Source,
 ) -> Result<(Vec<ResolvedGritDefinition>, ModuleRepo)> {
-    let existing_config = find_grit_dir_from(cwd).await;
+    let existing_config = find_root_grit_dir_from(cwd).await;
     let stdlib_modules = get_stdlib_modules();
 
     match existing_config {
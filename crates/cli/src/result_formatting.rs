@@ -5,7 +5,7 @@ use core::fmt;
 use log::{debug, error, info, warn};
 use marzano_core::api::{
     AllDone, AnalysisLog, AnalysisLogLevel, CreateFile, DoneFile, FileMatchResult, InputFile,
-    Match, MatchReason, MatchResult, PatternInfo, RemoveFile, Rewrite,
+    Match, MatchReason, MatchResult, PatternInfo, RemoveFile, Rewrite, INVALID_REWRITE_LOG_LEVEL,
 };
 use marzano_core::constants::DEFAULT_FILE_NAME;
 use marzano_messenger::output_mode::OutputMode;
@@ -80,7 +80,15 @@ fn print_all_done(item: &AllDone, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f,
         "Processed {} files and found {} matches",
         item.processed, item.found
-    )
+    )?;
+    if item.invalid_rewrites > 0 {
+        write!(
+            f,
+            ", {} rewrites introduced syntax errors",
+            item.invalid_rewrites
+        )?;
+    }
+    Ok(())
 }
 
 fn print_error_log(log: &AnalysisLog, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -94,6 +102,9 @@ fn print_error_log(log: &AnalysisLog, f: &mut fmt::Formatter<'_>) -> fmt::Result
         writeln!(f, "{}", msg)
     } else if log.level == 310 {
         style(log.message.to_string()).dim().fmt(f)
+    } else if log.level == INVALID_REWRITE_LOG_LEVEL {
+        let msg = format!("{}WARNING - {}", file_prefix, log.message).yellow();
+        writeln!(f, "{}", msg)
     } else if log.level == 441 {
         let title = format!("Log in {}", log.file).bold();
         writeln!(f, "{}: {}", title, log.message)?;
@@ -0,0 +1,604 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::anyhow;
+use grit_util::Range;
+use marzano_core::api::{
+    derive_log_level, AnalysisLog, AnalysisLogLevel, EnforcementLevel, MatchReason, MatchResult,
+};
+use marzano_gritmodule::{config::ResolvedGritDefinition, utils::is_pattern_name};
+use marzano_messenger::{
+    emit::{FlushableMessenger, Messager, VisibilityLevels},
+    workflows::StatusManager,
+};
+use serde::Serialize;
+use similar::{DiffOp, TextDiff};
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rule id used for results which cannot be attributed to a named pattern
+const ANONYMOUS_RULE_ID: &str = "grit_pattern";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun<'a> {
+    tool: SarifTool<'a>,
+    results: &'a [SarifResult],
+    invocations: Vec<SarifInvocation<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool<'a> {
+    driver: SarifDriver<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver<'a> {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<&'a ReportingDescriptor>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifInvocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: &'a [SarifNotification],
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+impl SarifMessage {
+    fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<SarifMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<SarifMessage>,
+    default_configuration: ReportingConfiguration,
+    properties: DescriptorProperties,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReportingConfiguration {
+    level: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DescriptorProperties {
+    tags: Vec<String>,
+}
+
+impl ReportingDescriptor {
+    fn from_definition(pattern: &ResolvedGritDefinition) -> Self {
+        Self {
+            id: pattern.name().to_string(),
+            name: pattern.name().to_string(),
+            short_description: pattern.title().map(SarifMessage::new),
+            full_description: pattern.description().map(SarifMessage::new),
+            default_configuration: ReportingConfiguration {
+                level: sarif_level(&pattern.level()),
+            },
+            properties: DescriptorProperties {
+                tags: pattern.tags().to_owned(),
+            },
+        }
+    }
+
+    fn from_reason(id: &str, reason: Option<&MatchReason>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: id.to_string(),
+            short_description: reason
+                .and_then(|r| r.title.as_deref())
+                .map(SarifMessage::new),
+            full_description: None,
+            default_configuration: ReportingConfiguration {
+                level: sarif_level(
+                    reason
+                        .and_then(|r| r.level.as_ref())
+                        .unwrap_or(&EnforcementLevel::default()),
+                ),
+            },
+            properties: DescriptorProperties { tags: vec![] },
+        }
+    }
+
+    /// The text used for results of this rule when the match itself carries no explanation
+    fn message(&self) -> String {
+        self.full_description
+            .as_ref()
+            .or(self.short_description.as_ref())
+            .map(|m| m.text.clone())
+            .unwrap_or_else(|| format!("Matched pattern {}", self.name))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<SarifFix>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+impl SarifArtifactLocation {
+    fn new(path: &str) -> Self {
+        Self {
+            uri: path.strip_prefix("./").unwrap_or(path).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+    end_column: u32,
+}
+
+impl SarifRegion {
+    /// Grit columns count bytes, but SARIF columns count UTF-16 code units by default.
+    /// Without the file content, the columns are left as they are.
+    fn new(range: &Range, content: Option<&str>) -> Self {
+        let column = |line: u32, column: u32| match content {
+            Some(content) => utf16_column(content, line, column),
+            None => column,
+        };
+        Self {
+            start_line: range.start.line,
+            start_column: column(range.start.line, range.start.column),
+            end_line: range.end.line,
+            end_column: column(range.end.line, range.end.column),
+        }
+    }
+}
+
+/// Convert a 1-based byte column on a 1-based line to a 1-based UTF-16 column
+fn utf16_column(content: &str, line: u32, byte_column: u32) -> u32 {
+    let Some(line_text) = content.split('\n').nth(line.saturating_sub(1) as usize) else {
+        return byte_column;
+    };
+    match line_text.get(..byte_column.saturating_sub(1) as usize) {
+        Some(prefix) => prefix.encode_utf16().count() as u32 + 1,
+        None => byte_column,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifFix {
+    description: SarifMessage,
+    artifact_changes: Vec<SarifArtifactChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactChange {
+    artifact_location: SarifArtifactLocation,
+    replacements: Vec<SarifReplacement>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifReplacement {
+    deleted_region: SarifRegion,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inserted_content: Option<SarifArtifactContent>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SarifArtifactContent {
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifNotification {
+    level: &'static str,
+    message: SarifMessage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<SarifLocation>,
+}
+
+impl From<&AnalysisLog> for SarifNotification {
+    fn from(log: &AnalysisLog) -> Self {
+        let level = match derive_log_level(log) {
+            AnalysisLogLevel::Error => "error",
+            AnalysisLogLevel::Warn => "warning",
+            AnalysisLogLevel::Info | AnalysisLogLevel::Debug => "note",
+        };
+        let locations = if log.file.is_empty() {
+            vec![]
+        } else {
+            vec![SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: SarifArtifactLocation::new(&log.file),
+                    region: log
+                        .range
+                        .as_ref()
+                        .map(|range| SarifRegion::new(range, log.source.as_deref())),
+                },
+            }]
+        };
+        Self {
+            level,
+            message: SarifMessage::new(log.message.clone()),
+            locations,
+        }
+    }
+}
+
+fn sarif_level(level: &EnforcementLevel) -> &'static str {
+    match level {
+        EnforcementLevel::Error => "error",
+        EnforcementLevel::Warn => "warning",
+        EnforcementLevel::Info => "note",
+        EnforcementLevel::None => "none",
+    }
+}
+
+/// Convert a rewrite into SARIF replacements, one for each changed block of lines.
+/// Regions are expressed as whole lines of the original file, so they are independent of column encoding.
+fn diff_to_replacements(original: &str, rewritten: &str) -> Vec<SarifReplacement> {
+    let diff = TextDiff::from_lines(original, rewritten);
+    let new_lines = diff.new_slices();
+    diff.ops()
+        .iter()
+        .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+        .map(|op| {
+            let old_range = op.old_range();
+            let new_range = op.new_range();
+            let inserted = new_lines[new_range].concat();
+            SarifReplacement {
+                deleted_region: SarifRegion {
+                    start_line: old_range.start as u32 + 1,
+                    start_column: 1,
+                    end_line: old_range.end as u32 + 1,
+                    end_column: 1,
+                },
+                inserted_content: if inserted.is_empty() {
+                    None
+                } else {
+                    Some(SarifArtifactContent { text: inserted })
+                },
+            }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct SarifState {
+    rules: BTreeMap<String, ReportingDescriptor>,
+    results: Vec<SarifResult>,
+    notifications: Vec<SarifNotification>,
+    written: bool,
+}
+
+/// Collects every result into a single SARIF 2.1.0 log, which is written out when the messenger is flushed
+#[derive(Clone)]
+pub struct SarifMessenger<'a> {
+    writer: Arc<Mutex<Box<dyn Write + Send + 'a>>>,
+    state: Arc<Mutex<SarifState>>,
+    default_rule_id: String,
+    min_level: VisibilityLevels,
+    status: StatusManager,
+}
+
+impl<'a> SarifMessenger<'a> {
+    pub fn new<W: Write + Send + 'a>(
+        writer: W,
+        pattern: Option<&str>,
+        min_level: VisibilityLevels,
+    ) -> Self {
+        let default_rule_id = pattern
+            .map(|p| p.trim().trim_end_matches("()"))
+            .filter(|p| is_pattern_name(p))
+            .unwrap_or(ANONYMOUS_RULE_ID)
+            .to_string();
+        Self {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+            state: Arc::new(Mutex::new(SarifState::default())),
+            default_rule_id,
+            min_level,
+            status: StatusManager::new(),
+        }
+    }
+
+    /// Register a reporting descriptor for each pattern, so rules are reported even if they have no results
+    pub fn register_patterns(
+        &mut self,
+        patterns: &[&ResolvedGritDefinition],
+    ) -> anyhow::Result<()> {
+        let mut state = self.lock_state()?;
+        for pattern in patterns {
+            state.rules.insert(
+                pattern.name().to_string(),
+                ReportingDescriptor::from_definition(pattern),
+            );
+        }
+        Ok(())
+    }
+
+    fn lock_state(&self) -> anyhow::Result<std::sync::MutexGuard<'_, SarifState>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("SarifMessenger lock poisoned"))
+    }
+
+    fn add_result(
+        &self,
+        path: &str,
+        content: Option<&str>,
+        ranges: &[Range],
+        reason: Option<&MatchReason>,
+        fix: Option<(&str, &str)>,
+    ) -> anyhow::Result<()> {
+        let rule_id = reason
+            .and_then(|r| r.name.as_deref())
+            .unwrap_or(&self.default_rule_id)
+            .to_string();
+        let mut state = self.lock_state()?;
+        let rule = state
+            .rules
+            .entry(rule_id.clone())
+            .or_insert_with(|| ReportingDescriptor::from_reason(&rule_id, reason));
+        let level = match reason.and_then(|r| r.level.as_ref()) {
+            Some(level) => sarif_level(level),
+            None => rule.default_configuration.level,
+        };
+        let message = reason
+            .and_then(|r| r.explanation.clone())
+            .unwrap_or_else(|| rule.message());
+
+        let content = match content {
+            Some(content) => Some(Cow::Borrowed(content)),
+            None if !ranges.is_empty() => fs_err::read_to_string(path).ok().map(Cow::Owned),
+            None => None,
+        };
+        let artifact_location = SarifArtifactLocation::new(path);
+        let locations = if ranges.is_empty() {
+            vec![SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: artifact_location.clone(),
+                    region: None,
+                },
+            }]
+        } else {
+            ranges
+                .iter()
+                .map(|range| SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: artifact_location.clone(),
+                        region: Some(SarifRegion::new(range, content.as_deref())),
+                    },
+                })
+                .collect()
+        };
+        let fixes = match fix {
+            Some((original, rewritten)) => vec![SarifFix {
+                description: SarifMessage::new(format!("Apply the rewrite from {}", rule_id)),
+                artifact_changes: vec![SarifArtifactChange {
+                    artifact_location,
+                    replacements: diff_to_replacements(original, rewritten),
+                }],
+            }],
+            None => vec![],
+        };
+
+        state.results.push(SarifResult {
+            rule_id,
+            level,
+            message: SarifMessage::new(message),
+            locations,
+            fixes,
+        });
+        Ok(())
+    }
+}
+
+impl<'a> Messager for SarifMessenger<'a> {
+    fn get_min_level(&self) -> VisibilityLevels {
+        self.min_level
+    }
+
+    fn get_workflow_status(
+        &mut self,
+    ) -> anyhow::Result<Option<&marzano_messenger::workflows::PackagedWorkflowOutcome>> {
+        self.status.get_workflow_status()
+    }
+
+    async fn finish_workflow(
+        &mut self,
+        outcome: &marzano_messenger::workflows::PackagedWorkflowOutcome,
+    ) -> anyhow::Result<()> {
+        self.status.upsert(outcome);
+        Ok(())
+    }
+
+    fn raw_emit(&mut self, item: &MatchResult) -> anyhow::Result<()> {
+        match item {
+            MatchResult::Match(m) => self.add_result(
+                &m.source_file,
+                m.content.as_deref(),
+                &m.ranges,
+                m.reason.as_ref(),
+                None,
+            ),
+            MatchResult::Rewrite(r) => {
+                let fix = match (&r.original.content, &r.rewritten.content) {
+                    (Some(original), Some(rewritten)) => {
+                        Some((original.as_str(), rewritten.as_str()))
+                    }
+                    _ => None,
+                };
+                self.add_result(
+                    &r.original.source_file,
+                    r.original.content.as_deref(),
+                    &r.original.ranges,
+                    r.reason.as_ref(),
+                    fix,
+                )
+            }
+            MatchResult::AnalysisLog(log) => {
+                self.lock_state()?.notifications.push(log.into());
+                Ok(())
+            }
+            MatchResult::PatternInfo(_)
+            | MatchResult::AllDone(_)
+            | MatchResult::InputFile(_)
+            | MatchResult::CreateFile(_)
+            | MatchResult::RemoveFile(_)
+            | MatchResult::DoneFile(_) => Ok(()),
+        }
+    }
+
+    fn emit_log(&mut self, log: &marzano_messenger::SimpleLogMessage) -> anyhow::Result<()> {
+        log::debug!("Log received over RPC: {:?}", log);
+        Ok(())
+    }
+}
+
+impl FlushableMessenger for SarifMessenger<'_> {
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        let mut state = self.lock_state()?;
+        // The log is a single document, so only the first flush writes it
+        if state.written {
+            return Ok(());
+        }
+        state.written = true;
+
+        let execution_successful = !state
+            .notifications
+            .iter()
+            .any(|n| n.level == "error" && n.locations.is_empty());
+        let log = SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "grit",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: "https://docs.grit.io",
+                        rules: state.rules.values().collect(),
+                    },
+                },
+                results: &state.results,
+                invocations: vec![SarifInvocation {
+                    execution_successful,
+                    tool_execution_notifications: &state.notifications,
+                }],
+            }],
+        };
+
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow!("SarifMessenger lock poisoned"))?;
+        serde_json::to_writer_pretty(&mut *writer, &log)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacements_cover_changed_lines() {
+        let original = "const a = 1;\nconsole.log(a);\nconst b = 2;\n";
+        let rewritten = "const a = 1;\nlogger.info(a);\nconst b = 2;\n";
+        let replacements = diff_to_replacements(original, rewritten);
+        assert_eq!(replacements.len(), 1);
+        let replacement = &replacements[0];
+        assert_eq!(replacement.deleted_region.start_line, 2);
+        assert_eq!(replacement.deleted_region.end_line, 3);
+        assert_eq!(
+            replacement.inserted_content.as_ref().unwrap().text,
+            "logger.info(a);\n"
+        );
+    }
+
+    #[test]
+    fn deleted_lines_have_no_inserted_content() {
+        let original = "keep\nremove\nkeep\n";
+        let rewritten = "keep\nkeep\n";
+        let replacements = diff_to_replacements(original, rewritten);
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].deleted_region.start_line, 2);
+        assert_eq!(replacements[0].deleted_region.end_line, 3);
+        assert!(replacements[0].inserted_content.is_none());
+    }
+
+    #[test]
+    fn columns_count_utf16_code_units() {
+        let content = "const a = 1;\nconst s = \"héllo 😀\"; log(s);\n";
+        // `é` takes two bytes but one UTF-16 unit, and `😀` four bytes but two units
+        let byte_column = content.lines().nth(1).unwrap().find("log").unwrap() as u32 + 1;
+        let utf16_column = byte_column - 3;
+        let range = Range::new(
+            grit_util::Position::new(2, byte_column),
+            grit_util::Position::new(2, byte_column + 3),
+            0,
+            0,
+        );
+        let region = SarifRegion::new(&range, Some(content));
+        assert_eq!(region.start_column, utf16_column);
+        assert_eq!(region.end_column, utf16_column + 3);
+        assert_eq!(SarifRegion::new(&range, None).start_column, byte_column);
+    }
+}
@@ -67,7 +67,7 @@ pub fn log_check_json(check_results: HashMap<String, Vec<CheckResult<'_>>>, file
                 let (start, end) = SemgrepPosition::pair_from_range(range);
                 let extra = SemgrepExtra {
                     message: result.pattern.description(),
-                    severity: Some(result.pattern.level()),
+                    severity: Some(result.level.clone()),
                 };
                 let semgrep_result = SemgrepResult {
                     check_id: check_id.clone(),
@@ -1,10 +1,10 @@
 use anyhow::Result;
 use colored::Colorize;
 use core::fmt;
-use grit_util::{Position, Range};
+use grit_util::{format_trace, Position, Profiler, Range};
 use log::info;
 use marzano_core::{
-    api::{EnforcementLevel, MatchResult},
+    api::{EnforcementLevel, Explanation, MatchReason, MatchResult, RewriteSource},
     fs::extract_ranges,
 };
 use marzano_gritmodule::{config::ResolvedGritDefinition, testing::SampleTestResult};
@@ -101,10 +101,83 @@ pub fn format_table(table: &Table) -> String {
     output
 }
 
+/// Log the ranked profile of a run, as a table or as a single JSON line
+pub fn log_profile(profiler: &Profiler, json: bool) -> Result<()> {
+    let report = profiler.report();
+    if json {
+        info!(
+            "{}",
+            serde_json::to_string(&serde_json::json!({ "profile": report }))?
+        );
+        return Ok(());
+    }
+    let table = Table {
+        format: Format::Table,
+        headers: Some(
+            ["Kind", "Name", "Calls", "Time (ms)"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        ),
+        data: report
+            .iter()
+            .map(|entry| {
+                vec![
+                    entry.kind.as_str().to_string(),
+                    entry.name.clone(),
+                    entry.calls.to_string(),
+                    format!("{:.3}", entry.total_ms),
+                ]
+            })
+            .collect(),
+    };
+    info!("{}", heading("Profile"));
+    info!("{}", format_table(&table));
+    Ok(())
+}
+
+pub fn log_explanation(explanation: &Explanation, json: bool) -> Result<()> {
+    if json {
+        info!("{}", serde_json::to_string(explanation)?);
+        return Ok(());
+    }
+    info!("{}", heading("Explanation"));
+    if explanation.trace.is_empty() {
+        info!("The pattern was not evaluated against any code that starts on this line.");
+    } else {
+        info!("{}", format_trace(&explanation.trace).trim_end());
+    }
+    if explanation.truncated {
+        info!("The trace was cut off because it grew too large.");
+    }
+    let matched = explanation.results.iter().any(|r| r.is_match());
+    info!(
+        "\nThe pattern {} the file.",
+        if matched { "matched" } else { "did not match" }
+    );
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct CheckResult<'a> {
     pub pattern: &'a ResolvedGritDefinition,
     pub result: MatchResult,
+    /// The level of the pattern for the file the result is in
+    pub level: EnforcementLevel,
+}
+
+impl CheckResult<'_> {
+    /// The reason to attach to the result, describing the enforced pattern that produced it
+    pub fn reason(&self) -> MatchReason {
+        MatchReason {
+            metadata_json: None,
+            source: RewriteSource::Gritql,
+            title: self.pattern.title().map(|s| s.to_string()),
+            name: Some(self.pattern.local_name.to_string()),
+            level: Some(self.level.clone()),
+            explanation: None,
+        }
+    }
 }
 
 fn log_check_result(range: &Range, result: &CheckResult, fix: bool) {
@@ -252,7 +325,7 @@ pub fn get_check_summary(
     let mut grouped_results: HashMap<EnforcementLevel, usize> = HashMap::new();
 
     for result in results.iter() {
-        let key = result.pattern.level();
+        let key = result.level.clone();
         *grouped_results.entry(key).or_default() += 1;
     }
 
@@ -1689,6 +1689,45 @@ fn match_only_format() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn sarif_output_dry_run() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_multiple_targeted", false)?;
+
+    let mut apply_cmd = get_test_cmd()?;
+    apply_cmd.current_dir(dir.clone());
+    apply_cmd
+        .arg("apply")
+        .arg("`$a = $b` => `console.log($b)`")
+        .arg("test.js")
+        .arg("--dry-run")
+        .arg("--sarif");
+
+    let output = apply_cmd.output()?;
+    assert!(
+        output.status.success(),
+        "Command didn't finish successfully: {}",
+        String::from_utf8(output.stderr)?
+    );
+
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(log["version"], "2.1.0");
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "grit_pattern");
+    assert!(
+        results[0]["fixes"][0]["artifactChanges"][0]["replacements"][0]["insertedContent"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("console.log(1)")
+    );
+
+    // Dry run must not touch the file
+    let content = fs_err::read_to_string(dir.join("test.js"))?;
+    assert!(content.contains("a = 1;"));
+
+    Ok(())
+}
+
 #[test]
 fn compact_output() -> Result<()> {
     let (_temp_dir, dir) = get_fixture("matching", true)?;
@@ -3321,3 +3360,238 @@ fn large_file_fails() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn profile_reports_definitions_and_files() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    fs_err::write(
+        tempdir.path().join("logs.js"),
+        "console.log('hello');\nconsole.log(name);\n",
+    )?;
+
+    let pattern = r#"pattern log_call() {
+    `console.log($msg)` where { is_message($msg) }
+}
+predicate is_message($msg) { $msg <: string() }
+log_call()"#;
+
+    let mut apply_cmd = get_test_cmd()?;
+    apply_cmd.current_dir(tempdir.path());
+    apply_cmd
+        .arg("apply")
+        .arg("--jsonl")
+        .arg("--force")
+        .arg("--profile")
+        .arg(pattern)
+        .arg("logs.js");
+
+    let output = apply_cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    println!("stdout: {:?}", stdout);
+    println!("stderr: {:?}", stderr);
+    assert!(output.status.success());
+
+    // Results stay on stdout, and the profile is logged on its own line
+    assert!(!stdout.contains("\"profile\""));
+    let profile: serde_json::Value = stderr
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find_map(|line| line.get("profile").cloned())
+        .ok_or(anyhow!("missing profile"))?;
+    let entries = profile.as_array().unwrap();
+    let find = |kind: &str, name: &str| {
+        entries
+            .iter()
+            .find(|e| e["kind"] == kind && e["name"].as_str().unwrap().ends_with(name))
+            .cloned()
+    };
+    // The pattern is tried on every node, but the predicate only runs on the two calls
+    assert!(
+        find("pattern", "log_call").unwrap()["calls"]
+            .as_u64()
+            .unwrap()
+            >= 2
+    );
+    assert_eq!(find("predicate", "is_message").unwrap()["calls"], 2);
+    assert_eq!(find("file", "logs.js").unwrap()["calls"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn step_budget_aborts_file() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    fs_err::write(tempdir.path().join("logs.js"), "console.log('hello');\n")?;
+
+    let mut apply_cmd = get_test_cmd()?;
+    apply_cmd.current_dir(tempdir.path());
+    apply_cmd
+        .arg("apply")
+        .arg("--jsonl")
+        .arg("--force")
+        .arg("`console.log($msg)` => `console.warn($msg)`")
+        .arg("logs.js")
+        .env("GRIT_MAX_FILE_STEPS", "1");
+
+    let output = apply_cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    println!("stdout: {:?}", stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("logs.js after 1 steps"));
+
+    // The file is left alone
+    let content = fs_err::read_to_string(tempdir.path().join("logs.js"))?;
+    assert_eq!(content, "console.log('hello');\n");
+
+    Ok(())
+}
+
+#[test]
+fn step_budget_flag_aborts_file() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    fs_err::write(tempdir.path().join("logs.js"), "console.log('hello');\n")?;
+
+    let mut apply_cmd = get_test_cmd()?;
+    apply_cmd.current_dir(tempdir.path());
+    apply_cmd
+        .arg("apply")
+        .arg("--jsonl")
+        .arg("--force")
+        .arg("--max-file-steps")
+        .arg("1")
+        .arg("`console.log($msg)` => `console.warn($msg)`")
+        .arg("logs.js");
+
+    let output = apply_cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    println!("stdout: {:?}", stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("logs.js after 1 steps"));
+
+    let content = fs_err::read_to_string(tempdir.path().join("logs.js"))?;
+    assert_eq!(content, "console.log('hello');\n");
+
+    Ok(())
+}
+
+#[test]
+fn explain_traces_line() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let content = "console.log('hello');\nconsole.log('world');\n";
+    fs_err::write(tempdir.path().join("logs.js"), content)?;
+
+    let mut apply_cmd = get_test_cmd()?;
+    apply_cmd.current_dir(tempdir.path());
+    apply_cmd
+        .arg("apply")
+        .arg("--force")
+        .arg("--explain")
+        .arg("logs.js:2")
+        .arg("`console.log($msg)` => `console.warn($msg)` where { $msg <: `'hello'` }")
+        .arg("logs.js");
+
+    let output = apply_cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    println!("stdout: {:?}", stdout);
+    assert!(output.status.success());
+
+    // The snippet matches the second call, but the where clause rejects its message
+    assert!(stdout.contains("✓ $msg = `'world'`"));
+    assert!(stdout.contains("✗ `'hello'` on `'world'`"));
+
+    // Explaining never rewrites the file
+    assert_eq!(
+        fs_err::read_to_string(tempdir.path().join("logs.js"))?,
+        content
+    );
+
+    Ok(())
+}
+
+#[test]
+fn journal_resumes_and_reverts() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let original = "console.log('hello');\n";
+    fs_err::write(tempdir.path().join("logs.js"), original)?;
+    let pattern = "`console.log($msg)` => `console.warn($msg)`";
+
+    let run = |args: &[&str]| -> Result<String> {
+        let mut apply_cmd = get_test_cmd()?;
+        apply_cmd.current_dir(tempdir.path());
+        apply_cmd
+            .arg("apply")
+            .arg("--force")
+            .arg("--journal")
+            .arg("apply.journal")
+            .args(args)
+            .arg(pattern)
+            .arg("logs.js");
+        let output = apply_cmd.output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+        println!("stdout: {:?}", stdout);
+        assert!(output.status.success());
+        Ok(stdout)
+    };
+
+    run(&[])?;
+    let rewritten = fs_err::read_to_string(tempdir.path().join("logs.js"))?;
+    assert_eq!(rewritten, "console.warn('hello');\n");
+    let journal = fs_err::read_to_string(tempdir.path().join("apply.journal"))?;
+    assert!(journal.contains("\"status\":\"rewritten\""));
+
+    // Resuming skips the file that was already rewritten
+    let stdout = run(&["--resume"])?;
+    assert!(stdout.contains("Processed 0 files"));
+
+    let stdout = run(&["--revert"])?;
+    assert!(stdout.contains("Reverted 1 files"));
+    assert_eq!(
+        fs_err::read_to_string(tempdir.path().join("logs.js"))?,
+        original
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rejects_rewrites_that_introduce_syntax_errors() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let original = "console.log('hello');\n";
+    fs_err::write(tempdir.path().join("logs.js"), original)?;
+    let pattern = "`console.log($msg)` => `console.log($msg`";
+
+    let run = |args: &[&str]| -> Result<String> {
+        let mut apply_cmd = get_test_cmd()?;
+        apply_cmd.current_dir(tempdir.path());
+        apply_cmd
+            .arg("apply")
+            .arg("--force")
+            .args(args)
+            .arg(pattern)
+            .arg("logs.js");
+        let output = apply_cmd.output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+        println!("stdout: {:?}", stdout);
+        assert!(output.status.success());
+        Ok(stdout)
+    };
+
+    let stdout = run(&["--reject-invalid-rewrites"])?;
+    assert!(stdout.contains("Rewrite introduces a syntax error"));
+    assert!(stdout.contains("1 rewrites introduced syntax errors"));
+    assert_eq!(
+        fs_err::read_to_string(tempdir.path().join("logs.js"))?,
+        original
+    );
+
+    // Without the flag, the rewrite is applied with a warning
+    let stdout = run(&[])?;
+    assert!(stdout.contains("1 rewrites introduced syntax errors"));
+    assert_eq!(
+        fs_err::read_to_string(tempdir.path().join("logs.js"))?,
+        "console.log('hello';\n"
+    );
+
+    Ok(())
+}
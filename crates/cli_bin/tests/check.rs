@@ -76,6 +76,65 @@ fn check_json_output() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn check_json_output_flushes_the_cache() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_multiple_targeted", true)?;
+    let cache_dir = tempfile::tempdir()?;
+    let mut check_cmd = get_test_cmd()?;
+    check_cmd
+        .current_dir(dir)
+        .env("GRIT_CACHE_DIR", cache_dir.path())
+        .arg("check")
+        .arg("--json");
+    let output = check_cmd.output()?;
+    println!("stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success());
+    // The matches cache holds more than its version byte once the cache manager is joined
+    let matches_cache = fs_err::metadata(cache_dir.path().join("matches_cache"))?;
+    assert!(matches_cache.len() > 1);
+    Ok(())
+}
+
+#[test]
+fn check_sarif_output() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_multiple_targeted", true)?;
+    let mut check_cmd = get_test_cmd()?;
+    check_cmd
+        .current_dir(dir)
+        .arg("check")
+        .arg("--no-cache")
+        .arg("--sarif");
+    let output = check_cmd.output()?;
+    println!("stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success());
+    // Everything else goes to stderr, so stdout is exactly the SARIF log
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["id"], "test_js");
+    assert_eq!(rules[0]["properties"]["tags"][0], "style");
+    let results = run["results"].as_array().unwrap();
+    let js_result = results.iter().find(|r| r["ruleId"] == "test_js").unwrap();
+    assert_eq!(js_result["level"], "error");
+    assert_eq!(
+        js_result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "test.js"
+    );
+    assert_eq!(
+        js_result["locations"][0]["physicalLocation"]["region"]["startLine"],
+        2
+    );
+    let replacement = &js_result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["startLine"], 2);
+    assert!(replacement["insertedContent"]["text"]
+        .as_str()
+        .unwrap()
+        .contains("console.log(1)"));
+    Ok(())
+}
+
 #[test]
 fn grit_dir_without_grit_modules() -> Result<()> {
     let (_temp_dir, dir) = get_fixture("check_js", false)?;
@@ -207,3 +266,255 @@ fn check_only_in_diff() -> Result<()> {
 
     Ok(())
 }
+
+fn git(dir: &PathBuf, args: &[&str]) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .args(["-c", "user.name=grit", "-c", "user.email=grit@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    assert!(output.status.success(), "git {:?} failed", args);
+    Ok(())
+}
+
+#[test]
+fn check_since_and_staged() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_multiple_targeted", true)?;
+    git(&dir, &["init"])?;
+    git(&dir, &["add", "."])?;
+    git(&dir, &["commit", "-m", "initial"])?;
+
+    fs_err::write(
+        dir.join("test.js"),
+        "class Button extends Component {\n  a = 1;\n  b = 2;\n}\n",
+    )?;
+
+    // Nothing is staged yet
+    let output = check_cmd_output(dir.clone(), &["--staged"], Some(0))?;
+    assert!(!output.contains("test_js"));
+
+    // Only the new line is checked
+    let output = check_cmd_output(dir.clone(), &["--since", "HEAD"], None)?;
+    assert!(output.contains("3:3"));
+    assert!(!output.contains("2:3"));
+    assert!(!output.contains("test_python"));
+
+    git(&dir, &["add", "test.js"])?;
+    let output = check_cmd_output(dir.clone(), &["--staged"], None)?;
+    assert!(output.contains("3:3"));
+    assert!(!output.contains("2:3"));
+
+    Ok(())
+}
+
+#[test]
+fn check_baseline_suppresses_existing_violations() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_multiple_targeted", true)?;
+    let output = check_cmd_output(dir.clone(), &["--write-baseline", "baseline.json"], Some(0))?;
+    assert!(output.contains("Wrote 2 violations to baseline baseline.json"));
+
+    let output = check_cmd_output(dir.clone(), &["--baseline", "baseline.json"], Some(0))?;
+    assert!(output.contains("Suppressed 2 violations"));
+    assert!(!output.contains("Fix available"));
+
+    // Moving an existing violation doesn't make it new
+    let js = fs_err::read_to_string(dir.join("test.js"))?;
+    fs_err::write(dir.join("test.js"), format!("// A button\n\n{}", js))?;
+    let output = check_cmd_output(dir.clone(), &["--baseline", "baseline.json"], Some(0))?;
+    assert!(output.contains("Suppressed 2 violations"));
+
+    // New violations are reported, and fixed ones can be pruned
+    fs_err::write(
+        dir.join("test.js"),
+        format!(
+            "// A button\n\n{}\nclass Link extends Component {{\n  b = 2;\n}}\n",
+            js
+        ),
+    )?;
+    fs_err::write(dir.join("test.py"), "def cls():\n  pass\n")?;
+    let output = check_cmd_output(dir, &["--baseline", "baseline.json"], Some(1))?;
+    assert!(output.contains("Suppressed 1 violations"));
+    assert!(output.contains("1 baseline entries no longer match"));
+    assert!(output.contains("test.py:2    test_python"));
+    assert_eq!(output.matches("Fix available").count(), 1);
+    Ok(())
+}
+
+#[test]
+fn check_requires_ignore_reasons() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_ignore", false)?;
+    let output = check_cmd_output(dir, &["--require-ignore-reason", "test.js"], None)?;
+    // The bare `grit-ignore` no longer suppresses anything, but the one with a reason still does
+    assert!(output.contains("another_test"));
+    assert!(output.contains("third"));
+    Ok(())
+}
+
+#[test]
+fn check_reports_unused_ignores() -> Result<()> {
+    let (_temp_dir, dir) = get_fixture("check_ignore", false)?;
+    let js = fs_err::read_to_string(dir.join("test.js"))?;
+    fs_err::write(
+        dir.join("test.js"),
+        format!(
+            "{}\n// grit-ignore test: nothing to suppress\nfunction foo() {{}}\n",
+            js
+        ),
+    )?;
+    let output = check_cmd_output(dir, &["--report-unused-ignores", "test.js"], Some(1))?;
+    assert!(output.contains("1 unused grit-ignore comments"));
+    assert!(output
+        .contains("// grit-ignore test: nothing to suppress    no longer suppresses anything"));
+    Ok(())
+}
+
+#[test]
+fn check_overrides_levels_by_path() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let dir = tempdir.path().to_path_buf();
+    fs_err::create_dir_all(dir.join(".grit"))?;
+    fs_err::write(
+        dir.join(".grit/grit.yaml"),
+        r#"version: 0.0.1
+patterns:
+  - name: no_console_log
+    level: warn
+    body: |
+      language js
+      `console.log($msg)` => `console.warn($msg)`
+overrides:
+  - files: ["scripts/**"]
+    levels:
+      no_console_log: none
+"#,
+    )?;
+    fs_err::create_dir_all(dir.join("packages/legacy/.grit"))?;
+    fs_err::write(
+        dir.join("packages/legacy/.grit/grit.yaml"),
+        "patterns:\n  - name: no_console_log\n    level: none\n",
+    )?;
+    for file in [
+        "src/index.js",
+        "scripts/build.js",
+        "packages/legacy/index.js",
+    ] {
+        let path = dir.join(file);
+        fs_err::create_dir_all(path.parent().unwrap())?;
+        fs_err::write(path, "console.log('hello');\n")?;
+    }
+
+    let output = check_cmd_output(dir, &[], Some(1))?;
+    assert!(output.contains("src/index.js"));
+    assert!(!output.contains("scripts/build.js"));
+    assert!(!output.contains("packages/legacy/index.js"));
+    Ok(())
+}
+
+#[test]
+fn check_from_nested_config_uses_root_config() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let dir = tempdir.path().to_path_buf();
+    fs_err::create_dir_all(dir.join(".git"))?;
+    fs_err::create_dir_all(dir.join(".grit"))?;
+    fs_err::write(
+        dir.join(".grit/grit.yaml"),
+        r#"version: 0.0.1
+patterns:
+  - name: no_console_log
+    level: warn
+    body: |
+      language js
+      `console.log($msg)` => `console.warn($msg)`
+overrides:
+  - files: ["packages/web/scripts/**"]
+    levels:
+      no_console_log: none
+"#,
+    )?;
+    let nested = dir.join("packages/web");
+    fs_err::create_dir_all(nested.join(".grit"))?;
+    fs_err::write(
+        nested.join(".grit/grit.yaml"),
+        "patterns:\n  - name: no_console_log\n    level: error\n",
+    )?;
+    for file in ["src/app.js", "scripts/build.js"] {
+        let path = nested.join(file);
+        fs_err::create_dir_all(path.parent().unwrap())?;
+        fs_err::write(path, "console.log('hello');\n")?;
+    }
+
+    // The pattern comes from the root config, with the nested level and root overrides applied
+    let output = check_cmd_output(nested, &[], Some(1))?;
+    assert!(output.contains("app.js"));
+    assert!(output.contains("Found 1 errors, 0 warnings"));
+    assert!(!output.contains("build.js"));
+    Ok(())
+}
+
+#[test]
+fn check_enforces_patterns_with_args() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let dir = tempdir.path().to_path_buf();
+    fs_err::create_dir_all(dir.join(".grit/patterns"))?;
+    fs_err::write(
+        dir.join(".grit/patterns/no_import.grit"),
+        "language js\n\npattern no_import($module) {\n  `import $_ from $source` where { $source <: includes $module }\n}\n",
+    )?;
+    fs_err::write(
+        dir.join(".grit/grit.yaml"),
+        r#"version: 0.0.1
+patterns:
+  - name: no_lodash
+    pattern: no_import
+    level: error
+    description: Do not import lodash
+    args:
+      module: lodash
+  - name: no_moment
+    pattern: no_import
+    level: warn
+    description: Do not import moment
+    args:
+      module: moment
+"#,
+    )?;
+    fs_err::write(
+        dir.join("index.js"),
+        "import _ from 'lodash';\nimport React from 'react';\n",
+    )?;
+
+    let output = check_cmd_output(dir, &["--json"], None)?;
+    assert!(output.contains("no_lodash"));
+    assert!(!output.contains("no_moment"));
+    Ok(())
+}
+
+#[test]
+fn check_reads_file_limits_from_config() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let dir = tempdir.path().to_path_buf();
+    fs_err::create_dir_all(dir.join(".grit"))?;
+    fs_err::write(
+        dir.join(".grit/grit.yaml"),
+        r#"version: 0.0.1
+patterns:
+  - name: no_console_log
+    level: error
+    body: |
+      language js
+      `console.log($msg)` => `console.warn($msg)`
+limits:
+  max_file_steps: 1
+"#,
+    )?;
+    fs_err::write(dir.join("index.js"), "console.log('hello');\n")?;
+
+    // The file is skipped, so nothing is reported
+    let output = check_cmd_output(dir.clone(), &[], Some(0))?;
+    assert!(!output.contains("index.js"));
+
+    // The flag takes precedence over the config
+    let output = check_cmd_output(dir, &["--max-file-steps", "0"], Some(1))?;
+    assert!(output.contains("index.js"));
+    Ok(())
+}
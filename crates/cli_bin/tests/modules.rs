@@ -0,0 +1,52 @@
+use crate::common::get_test_cmd;
+use anyhow::Result;
+
+mod common;
+
+#[test]
+fn vendors_local_modules() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let shared = tempdir.path().join("shared-patterns");
+    fs_err::create_dir_all(repo.join(".grit"))?;
+    fs_err::create_dir_all(shared.join(".grit/patterns"))?;
+    fs_err::write(
+        shared.join(".grit/patterns/no_console_log.grit"),
+        "language js\n\n`console.log($msg)` => `console.warn($msg)`\n",
+    )?;
+    fs_err::write(
+        repo.join(".grit/grit.yaml"),
+        "version: 0.0.1\npatterns:\n  - name: file:../shared-patterns#no_console_log\n",
+    )?;
+    fs_err::write(repo.join("logs.js"), "console.log('hello');\n")?;
+
+    let mut cmd = get_test_cmd()?;
+    cmd.current_dir(&repo).arg("modules").arg("vendor");
+    let output = cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    println!("stdout: {:?}", stdout);
+    println!("stderr: {:?}", String::from_utf8(output.stderr)?);
+    assert!(output.status.success());
+    assert!(stdout.contains("Vendored file/__/shared-patterns"));
+    assert!(repo
+        .join(".grit/vendor/file/__/shared-patterns/.grit/patterns/no_console_log.grit")
+        .exists());
+
+    // The vendored copy is used once the original is gone
+    fs_err::remove_dir_all(&shared)?;
+    let mut cmd = get_test_cmd()?;
+    cmd.current_dir(&repo)
+        .arg("apply")
+        .arg("--force")
+        .arg("no_console_log")
+        .arg("logs.js");
+    let output = cmd.output()?;
+    println!("stderr: {:?}", String::from_utf8(output.stderr)?);
+    assert!(output.status.success());
+    assert_eq!(
+        fs_err::read_to_string(repo.join("logs.js"))?,
+        "console.warn('hello');\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use crate::common::get_test_cmd;
+use anyhow::Result;
+
+mod common;
+
+#[test]
+fn repl_runs_and_saves_patterns() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    fs_err::write(tempdir.path().join("logs.js"), "console.log('hello');\n")?;
+
+    let mut cmd = get_test_cmd()?;
+    cmd.current_dir(tempdir.path())
+        .arg("repl")
+        .arg("logs.js")
+        .write_stdin(
+            "`console.log($msg)` => `console.warn($msg)`\n:bogus\n:save warn_logs\n:quit\n",
+        );
+
+    let output = cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    println!("stdout: {:?}", stdout);
+    println!("stderr: {:?}", stderr);
+    assert!(output.status.success());
+
+    // Bindings and the rewrite are shown, and a bad command doesn't end the session
+    assert!(stdout.contains("$msg = 'hello'"));
+    assert!(stdout.contains("console.warn('hello');"));
+    assert!(stdout.contains("Found 1 matches in 1 files"));
+    assert!(stdout.contains("Saved warn_logs"));
+
+    // The target file is never rewritten
+    let content = fs_err::read_to_string(tempdir.path().join("logs.js"))?;
+    assert_eq!(content, "console.log('hello');\n");
+
+    let saved = fs_err::read_to_string(tempdir.path().join(".grit/patterns/warn_logs.md"))?;
+    assert_eq!(
+        saved,
+        "# warn_logs\n\n```grit\nlanguage js\n\n`console.log($msg)` => `console.warn($msg)`\n```\n\n## Sample\n\n```js\nconsole.log('hello');\n```\n\n```js\nconsole.warn('hello');\n```\n"
+    );
+
+    Ok(())
+}
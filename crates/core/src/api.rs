@@ -2,9 +2,10 @@ use crate::{fs, problem::Problem, tree_sitter_serde::tree_sitter_node_to_json};
 use anyhow::{bail, Result};
 use grit_pattern_matcher::file_owners::FileOwner;
 pub use grit_util::ByteRange;
-use grit_util::{AnalysisLog as GritAnalysisLog, Ast, Position, Range, VariableMatch};
+use grit_util::{AnalysisLog as GritAnalysisLog, Ast, Position, Range, TraceNode, VariableMatch};
 use marzano_language::grit_ts_node::grit_node_types;
 use marzano_language::language::{MarzanoLanguage, Tree};
+use marzano_language::sourcemap::EmbeddedSourceMap;
 use serde::{Deserialize, Serialize};
 use serde_json::to_string_pretty;
 use std::path::PathBuf;
@@ -479,9 +480,41 @@ impl EntireFile {
         if let Some(input_ranges) = file.matches.borrow().input_matches.as_ref() {
             basic.ranges = input_ranges.ranges.clone();
             basic.variables = input_ranges.variables.clone();
+            if let Some(source_map) = file.tree.source_map.as_ref().filter(|m| m.is_raw()) {
+                basic.map_to_outer(source_map);
+            }
         };
         Ok(basic)
     }
+
+    /// Report ranges in the coordinates of the outer file, when every range can be mapped
+    fn map_to_outer(&mut self, source_map: &EmbeddedSourceMap) {
+        let ranges: Option<Vec<Range>> = self
+            .ranges
+            .iter()
+            .map(|range| source_map.outer_range(range))
+            .collect();
+        let variables: Option<Vec<VariableMatch>> = self
+            .variables
+            .iter()
+            .map(|variable| {
+                Some(VariableMatch {
+                    ranges: variable
+                        .ranges
+                        .iter()
+                        .map(|range| source_map.outer_range(range))
+                        .collect::<Option<_>>()?,
+                    ..variable.clone()
+                })
+            })
+            .collect();
+        if let (Some(ranges), Some(variables)) = (ranges, variables) {
+            self.ranges = ranges;
+            self.variables = variables;
+            // The ranges now line up with the outer content
+            self.inner_content = None;
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -709,6 +742,10 @@ pub struct DoneFile {
     pub file_hash: Option<[u8; 32]>,
     #[serde(skip_serializing, skip_deserializing)]
     pub from_cache: bool,
+    /// The results depend on a `grit-ignore` comment with an `until` date,
+    /// so they can change without the file changing
+    #[serde(skip_serializing, skip_deserializing)]
+    pub depends_on_date: bool,
 }
 
 impl DoneFile {
@@ -718,6 +755,7 @@ impl DoneFile {
             has_results: None,
             file_hash: None,
             from_cache: false,
+            depends_on_date: false,
         }
     }
 }
@@ -730,6 +768,16 @@ pub struct Message {
     pub variable_runtime_id: String,
 }
 
+/// How a pattern was evaluated against the nodes that start on one line of a file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Explanation {
+    pub results: Vec<MatchResult>,
+    pub trace: Vec<TraceNode>,
+    /// Whether the trace was cut off because it grew too large
+    pub truncated: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
 pub struct AllDone {
@@ -738,6 +786,13 @@ pub struct AllDone {
     /// How many matches were found
     pub found: i32,
     pub reason: AllDoneReason,
+    /// How many rewrites introduced syntax errors
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub invalid_rewrites: i32,
+}
+
+fn is_zero(n: &i32) -> bool {
+    *n == 0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -749,6 +804,9 @@ pub enum AllDoneReason {
     Aborted,
 }
 
+/// The level of the log reported for a rewrite that introduces syntax errors
+pub const INVALID_REWRITE_LOG_LEVEL: u16 = 320;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalysisLog {
@@ -13,9 +13,12 @@ use grit_pattern_matcher::{
 };
 use grit_util::{error::GritResult, AnalysisLogs, AstNode, Language};
 use marzano_language::language::{FieldId, LeafEquivalenceClass, MarzanoLanguage, SortId};
+use marzano_language::target_language::TargetLanguage;
 use marzano_util::node_with_source::NodeWithSource;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ASTNode {
     pub(crate) sort: SortId,
     pub(crate) args: Vec<(FieldId, bool, Pattern<MarzanoQueryContext>)>,
@@ -130,19 +133,57 @@ impl Matcher<MarzanoQueryContext> for ASTNode {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedAstLeafNode")]
 pub struct AstLeafNode {
     sort: SortId,
+    /// Holds normalizer functions, so it is rebuilt from the language on load.
+    #[serde(skip)]
     equivalence_class: Option<LeafEquivalenceClass>,
     text: String,
 }
 
+#[derive(Deserialize)]
+struct SerializedAstLeafNode {
+    sort: SortId,
+    text: String,
+}
+
+thread_local! {
+    static DESERIALIZING_LANGUAGE: Cell<Option<TargetLanguage>> = Cell::new(None);
+}
+
+/// Run `f` with `language` as the language that deserialized leaf nodes
+/// rebuild their equivalence classes from.
+pub(crate) fn with_deserializing_language<T>(language: TargetLanguage, f: impl FnOnce() -> T) -> T {
+    let previous = DESERIALIZING_LANGUAGE.with(|cell| cell.replace(Some(language)));
+    let result = f();
+    DESERIALIZING_LANGUAGE.with(|cell| cell.set(previous));
+    result
+}
+
+impl TryFrom<SerializedAstLeafNode> for AstLeafNode {
+    type Error = String;
+
+    fn try_from(node: SerializedAstLeafNode) -> Result<Self, Self::Error> {
+        let language = DESERIALIZING_LANGUAGE
+            .with(Cell::get)
+            .ok_or_else(|| "cannot deserialize a leaf node without a language".to_owned())?;
+        let equivalence_class = language.get_equivalence_class(node.sort, &node.text)?;
+        Ok(Self {
+            sort: node.sort,
+            equivalence_class,
+            text: node.text,
+        })
+    }
+}
+
 impl AstLeafNode {
     pub fn new<'a>(sort: SortId, text: &str, language: &impl MarzanoLanguage<'a>) -> Result<Self> {
+        let text = text.trim();
         let equivalence_class = language
             .get_equivalence_class(sort, text)
             .map_err(|e| anyhow!(e))?;
-        let text = text.trim();
         Ok(Self {
             sort,
             equivalence_class,
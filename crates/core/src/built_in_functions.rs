@@ -191,7 +191,7 @@ impl BuiltIns {
         }
     }
 
-    pub(crate) fn get_built_ins(&self) -> &[BuiltInFunction] {
+    pub fn get_built_ins(&self) -> &[BuiltInFunction] {
         &self.built_ins
     }
 
@@ -30,7 +30,7 @@ pub mod pattern_compiler;
 pub mod problem;
 mod smart_insert;
 mod split_snippet;
-mod suppress;
+pub mod suppress;
 mod text_unparser;
 pub mod tree_sitter_serde;
 mod variables;
@@ -49,8 +49,12 @@ mod test;
 #[cfg(test)]
 mod test_callback;
 #[cfg(test)]
+mod test_embedded;
+#[cfg(test)]
 mod test_errors;
 #[cfg(test)]
+mod test_explain;
+#[cfg(test)]
 mod test_files;
 #[cfg(test)]
 mod test_notebooks;
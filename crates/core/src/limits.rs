@@ -1,7 +1,24 @@
 use crate::constants::MAX_FILE_SIZE;
-use grit_util::{AnalysisLog, Position};
-use marzano_util::rich_path::RichFile;
-use std::env;
+use grit_util::{error::GritPatternError, AnalysisLog, Position};
+use marzano_util::{rich_path::RichFile, runtime::ExecutionContext};
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// How many steps to take between checks of the clock
+const CLOCK_CHECK_INTERVAL: u64 = 1024;
+
+fn env_limit(name: &str) -> Option<u64> {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|limit| *limit > 0)
+}
 
 pub(crate) fn is_file_too_big(file: &RichFile) -> Option<AnalysisLog> {
     let max_size = env::var("GRIT_MAX_FILE_SIZE_BYTES")
@@ -30,3 +47,150 @@ pub(crate) fn is_file_too_big(file: &RichFile) -> Option<AnalysisLog> {
         None
     }
 }
+
+/// Limits the wall-clock time and pattern steps spent matching a single file.
+/// Configured on the execution context, falling back to `GRIT_MAX_FILE_MILLIS` and `GRIT_MAX_FILE_STEPS`;
+/// both are unlimited by default.
+#[derive(Debug)]
+pub(crate) struct FileBudget {
+    started: Instant,
+    max_time: Option<Duration>,
+    max_steps: Option<u64>,
+    steps: AtomicU64,
+    /// The pattern and files being matched, for reporting
+    pattern: String,
+    files: String,
+    /// Why the budget ran out, once it has
+    exceeded: OnceLock<String>,
+}
+
+impl FileBudget {
+    pub(crate) fn new(
+        max_time: Option<Duration>,
+        max_steps: Option<u64>,
+        pattern: Option<&str>,
+        files: &[String],
+    ) -> Option<Self> {
+        if max_time.is_none() && max_steps.is_none() {
+            return None;
+        }
+        Some(Self {
+            started: Instant::now(),
+            max_time,
+            max_steps,
+            steps: AtomicU64::new(0),
+            pattern: pattern.unwrap_or("the pattern").to_string(),
+            files: match files {
+                [file] => file.clone(),
+                files => format!("{} files", files.len()),
+            },
+            exceeded: OnceLock::new(),
+        })
+    }
+
+    pub(crate) fn from_context(
+        context: &ExecutionContext,
+        pattern: Option<&str>,
+        files: &[String],
+    ) -> Option<Self> {
+        let max_millis = context
+            .max_file_millis
+            .or_else(|| env_limit("GRIT_MAX_FILE_MILLIS"))
+            .filter(|limit| *limit > 0);
+        let max_steps = context
+            .max_file_steps
+            .or_else(|| env_limit("GRIT_MAX_FILE_STEPS"))
+            .filter(|limit| *limit > 0);
+        Self::new(
+            max_millis.map(Duration::from_millis),
+            max_steps,
+            pattern,
+            files,
+        )
+    }
+
+    /// Count a step, failing once the budget is used up.
+    /// Every later step fails too, so the file is aborted even if an error is caught along the way.
+    pub(crate) fn step(&self) -> Result<(), GritPatternError> {
+        if let Some(reason) = self.exceeded.get() {
+            return Err(GritPatternError::BudgetExceeded(reason.clone()));
+        }
+        let steps = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+        let reason = match (self.max_steps, self.max_time) {
+            (Some(max_steps), _) if steps > max_steps => format!(
+                "Aborted {} after {} steps of {}, the per-file step limit.",
+                self.files, max_steps, self.pattern
+            ),
+            (_, Some(max_time))
+                if steps % CLOCK_CHECK_INTERVAL == 0 && self.started.elapsed() > max_time =>
+            {
+                format!(
+                    "Aborted {} after running {} for {}ms, the per-file time limit.",
+                    self.files,
+                    self.pattern,
+                    max_time.as_millis()
+                )
+            }
+            _ => return Ok(()),
+        };
+        let reason = self.exceeded.get_or_init(|| reason);
+        Err(GritPatternError::BudgetExceeded(reason.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_limits_means_no_budget() {
+        assert!(FileBudget::new(None, None, None, &["a.js".to_string()]).is_none());
+    }
+
+    #[test]
+    fn reads_limits_from_the_context() {
+        let mut context = ExecutionContext::default();
+        context.max_file_steps = Some(3);
+        let budget = FileBudget::from_context(&context, None, &["a.js".to_string()]).unwrap();
+        assert_eq!(budget.max_steps, Some(3));
+        // Zero turns the limit off
+        context.max_file_steps = Some(0);
+        assert!(FileBudget::from_context(&context, None, &["a.js".to_string()]).is_none());
+    }
+
+    #[test]
+    fn step_budget_is_sticky() {
+        let budget = FileBudget::new(None, Some(2), Some("slow"), &["a.js".to_string()]).unwrap();
+        assert!(budget.step().is_ok());
+        assert!(budget.step().is_ok());
+        let err = budget.step().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Aborted a.js after 2 steps of slow, the per-file step limit."
+        );
+        assert_eq!(budget.step().unwrap_err().to_string(), err.to_string());
+    }
+
+    #[test]
+    fn time_budget_is_sticky() {
+        let budget = FileBudget::new(
+            Some(Duration::ZERO),
+            None,
+            None,
+            &["a.js".to_string(), "b.js".to_string()],
+        )
+        .unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        let results: Vec<_> = (0..CLOCK_CHECK_INTERVAL)
+            .map(|_| budget.step().is_ok())
+            .collect();
+        assert!(results[..CLOCK_CHECK_INTERVAL as usize - 1]
+            .iter()
+            .all(|ok| *ok));
+        assert!(!results[CLOCK_CHECK_INTERVAL as usize - 1]);
+        assert_eq!(
+            budget.step().unwrap_err().to_string(),
+            "Aborted 2 files after running the pattern for 0ms, the per-file time limit."
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use crate::inline_snippets::inline_sorted_snippets_with_offset;
+use crate::marzano_context::MarzanoContext;
 use crate::problem::MarzanoQueryContext;
 use crate::smart_insert::calculate_padding;
-use crate::suppress::is_suppress_comment;
+use crate::suppress::{find_region_suppression, is_suppress_comment, SuppressCheck};
 use crate::{equivalence::are_equivalent, inline_snippets::ReplacementInfo};
+use chrono::Utc;
 use grit_pattern_matcher::{
     binding::Binding,
     constant::Constant,
-    context::QueryContext,
+    context::{ExecContext, QueryContext},
     effects::Effect,
     pattern::{get_top_level_effects, FileRegistry, ResolvedPattern},
 };
@@ -19,6 +21,7 @@ use itertools::{EitherOrBoth, Itertools};
 use marzano_language::language::{FieldId, MarzanoLanguage};
 use marzano_language::target_language::TargetLanguage;
 use marzano_util::node_with_source::NodeWithSource;
+use std::cell::Cell;
 use std::ops::Range as StdRange;
 use std::path::Path;
 use std::{borrow::Cow, collections::HashMap};
@@ -163,6 +166,34 @@ pub(crate) fn linearize_binding<'a, Q: QueryContext>(
     Ok((res.into(), offset, mapping))
 }
 
+/// Find a `grit-ignore` comment that suppresses `node`, and mark it as used
+fn find_suppression<'a>(
+    node: &NodeWithSource<'a>,
+    check: &SuppressCheck,
+    context: &MarzanoContext<'a>,
+) -> bool {
+    let language = context.language();
+    let target_range = node.node.range();
+    for n in node.children().chain(node.ancestors()) {
+        for c in n.children() {
+            if !language.is_comment(&c) {
+                continue;
+            }
+            if is_suppress_comment(&c, &target_range, check, language) {
+                context.mark_suppression_used(c.node.start_byte());
+                return true;
+            }
+        }
+    }
+    let regions = context.suppress_regions(node);
+    if let Some(comment_start) = find_region_suppression(&regions, &target_range, check) {
+        context.mark_suppression_used(comment_start);
+        return true;
+    }
+
+    false
+}
+
 impl<'a> Binding<'a, MarzanoQueryContext> for MarzanoBinding<'a> {
     fn from_constant(constant: &'a Constant) -> Self {
         Self::ConstantRef(constant)
@@ -337,24 +368,22 @@ impl<'a> Binding<'a, MarzanoQueryContext> for MarzanoBinding<'a> {
         }
     }
 
-    fn is_suppressed(&self, language: &TargetLanguage, current_name: Option<&str>) -> bool {
+    fn is_suppressed(&self, context: &MarzanoContext<'a>) -> bool {
         let node = match self {
             Self::Node(node) | Self::List(node, _) | Self::Empty(node, _) => node.clone(),
             Self::String(_, _) | Self::FileName(_) | Self::ConstantRef(_) => return false,
         };
-        let target_range = node.node.range();
-        for n in node.children().chain(node.ancestors()) {
-            for c in n.children() {
-                if !language.is_comment(&c) {
-                    continue;
-                }
-                if is_suppress_comment(&c, &target_range, current_name, language) {
-                    return true;
-                }
-            }
+        let check = SuppressCheck {
+            name: context.name(),
+            require_reason: context.runtime.require_suppress_reason,
+            today: Utc::now().date_naive(),
+            depends_on_date: Cell::new(false),
+        };
+        let suppressed = find_suppression(&node, &check, context);
+        if check.depends_on_date.get() {
+            context.mark_depends_on_date();
         }
-
-        false
+        suppressed
     }
 
     fn get_insertion_padding(
@@ -9,8 +9,9 @@ use grit_pattern_matcher::{
 };
 use grit_util::{error::GritResult, AnalysisLogs};
 use marzano_language::language::SortId;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarzanoCodeSnippet {
     pub(crate) patterns: Vec<(SortId, Pattern<MarzanoQueryContext>)>,
     pub(crate) source: String,
@@ -39,6 +40,10 @@ impl CodeSnippet<MarzanoQueryContext> for MarzanoCodeSnippet {
     fn dynamic_snippet(&self) -> Option<&DynamicPattern<MarzanoQueryContext>> {
         self.dynamic_snippet.as_ref()
     }
+
+    fn source(&self) -> Option<&str> {
+        Some(&self.source)
+    }
 }
 
 impl PatternName for MarzanoCodeSnippet {
@@ -1,18 +1,20 @@
 use crate::{
+    api::INVALID_REWRITE_LOG_LEVEL,
     built_in_functions::BuiltIns,
     clean::{get_replacement_ranges, merge_ranges, replace_cleaned_ranges},
     foreign_function_definition::ForeignFunctionDefinition,
-    limits::is_file_too_big,
+    limits::{is_file_too_big, FileBudget},
     marzano_resolved_pattern::{MarzanoFile, MarzanoResolvedPattern},
     pattern_compiler::file_owner_compiler::FileOwnerCompiler,
     problem::MarzanoQueryContext,
+    suppress::{find_suppress_regions, SuppressRegion},
     text_unparser::apply_effects,
 };
 use grit_pattern_matcher::{
     binding::Binding,
     constants::{GLOBAL_VARS_SCOPE_INDEX, NEW_FILES_INDEX},
     context::ExecContext,
-    file_owners::FileOwners,
+    file_owners::{FileOwner, FileOwners},
     pattern::{
         CallBuiltIn, CallbackPattern, File, FilePtr, GritFunctionDefinition, Matcher, Pattern,
         PatternDefinition, PredicateDefinition, ResolvedPattern, State,
@@ -20,17 +22,26 @@ use grit_pattern_matcher::{
 };
 use grit_util::{
     error::{GritPatternError, GritResult},
-    AnalysisLogs, Ast, FileOrigin, InputRanges, MatchRanges,
+    AnalysisLog, AnalysisLogs, Ast, FileOrigin, InputRanges, MatchRanges, Profiler, Range, Tracer,
 };
 use marzano_language::{
     language::{MarzanoLanguage, Tree},
     target_language::TargetLanguage,
 };
 use marzano_util::{
+    node_with_source::NodeWithSource,
     rich_path::{LoadableFile, RichFile},
     runtime::ExecutionContext,
 };
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
 
 pub struct MarzanoContext<'a> {
     pub pattern_definitions: &'a Vec<PatternDefinition<MarzanoQueryContext>>,
@@ -43,6 +54,14 @@ pub struct MarzanoContext<'a> {
     pub language: &'a TargetLanguage,
     pub runtime: &'a ExecutionContext,
     pub name: Option<String>,
+    previous_tree: Option<&'a Tree>,
+    /// Start bytes of the `grit-ignore` comments that suppressed a match
+    used_suppressions: Mutex<BTreeSet<u32>>,
+    /// The `grit-ignore` regions of each file, keyed by the address of its source
+    suppress_regions: Mutex<HashMap<usize, Arc<Vec<SuppressRegion<'a>>>>>,
+    /// Whether a `grit-ignore` comment with an `until` date applied to a match
+    depends_on_date: AtomicBool,
+    budget: Option<FileBudget>,
 }
 
 impl<'a> MarzanoContext<'a> {
@@ -70,9 +89,26 @@ impl<'a> MarzanoContext<'a> {
             language,
             runtime,
             name,
+            previous_tree: None,
+            used_suppressions: Mutex::new(BTreeSet::new()),
+            suppress_regions: Mutex::new(HashMap::new()),
+            depends_on_date: AtomicBool::new(false),
+            budget: None,
         }
     }
 
+    /// Reuse an edited tree when loading the (single) file, instead of parsing it from scratch
+    pub(crate) fn with_previous_tree(mut self, previous_tree: Option<&'a Tree>) -> Self {
+        self.previous_tree = previous_tree;
+        self
+    }
+
+    /// Abort matching once the budget is used up
+    pub(crate) fn with_budget(mut self, budget: Option<FileBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
     #[cfg(all(
         feature = "network_requests_external",
         feature = "external_functions_ffi",
@@ -91,6 +127,50 @@ impl<'a> MarzanoContext<'a> {
     pub(crate) fn foreign_function_definitions(&self) -> &[ForeignFunctionDefinition] {
         self.foreign_function_definitions
     }
+
+    pub(crate) fn mark_suppression_used(&self, comment_start: u32) {
+        if let Ok(mut used) = self.used_suppressions.lock() {
+            used.insert(comment_start);
+        }
+    }
+
+    pub(crate) fn used_suppressions(&self) -> BTreeSet<u32> {
+        self.used_suppressions
+            .lock()
+            .map(|used| used.clone())
+            .unwrap_or_default()
+    }
+
+    /// The `grit-ignore-file` comments and `grit-ignore-start` regions of the file containing `node`.
+    /// They are only collected once per file, since every match checks them.
+    pub(crate) fn suppress_regions(
+        &self,
+        node: &NodeWithSource<'a>,
+    ) -> Arc<Vec<SuppressRegion<'a>>> {
+        let find = || {
+            let regions = node
+                .ancestors()
+                .last()
+                .map(|root| find_suppress_regions(&root, self.language))
+                .unwrap_or_default();
+            Arc::new(regions)
+        };
+        // Trees are owned for the lifetime of the context, so their sources have distinct addresses
+        let key = node.source.as_ptr() as usize;
+        match self.suppress_regions.lock() {
+            Ok(mut regions) => regions.entry(key).or_insert_with(find).clone(),
+            Err(_) => find(),
+        }
+    }
+
+    pub(crate) fn mark_depends_on_date(&self) {
+        self.depends_on_date.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the results depend on the current date, because of a `grit-ignore` comment with an `until` date
+    pub(crate) fn depends_on_date(&self) -> bool {
+        self.depends_on_date.load(Ordering::Relaxed)
+    }
 }
 
 impl<'a> ExecContext<'a, MarzanoQueryContext> for MarzanoContext<'a> {
@@ -161,12 +241,16 @@ impl<'a> ExecContext<'a, MarzanoQueryContext> for MarzanoContext<'a> {
                 }
 
                 let owned = cow.into_owned();
+                let origin = match self.previous_tree {
+                    Some(tree) if self.lazy_files.len() == 1 => FileOrigin::Edited(tree),
+                    _ => FileOrigin::Fresh,
+                };
 
                 let file = FileOwnerCompiler::from_matches(
                     owned.path,
                     owned.content,
                     None,
-                    FileOrigin::Fresh,
+                    origin,
                     None,
                     self.language,
                     logs,
@@ -224,8 +308,7 @@ impl<'a> ExecContext<'a, MarzanoQueryContext> for MarzanoContext<'a> {
         }
 
         // todo, for multifile we need to split up the matches by file.
-        let (variables, ranges, suppressed) =
-            state.bindings_history_to_ranges(self.language(), self.name());
+        let (variables, ranges, suppressed) = state.bindings_history_to_ranges(self);
 
         let input_ranges = InputRanges {
             ranges,
@@ -323,6 +406,14 @@ impl<'a> ExecContext<'a, MarzanoQueryContext> for MarzanoContext<'a> {
                         ))
                     })?;
 
+                    if let Some(error) = introduced_syntax_error(&file.tree, &rewritten_file) {
+                        let rejected = self.runtime.reject_invalid_rewrites;
+                        logs.push(invalid_rewrite_log(&file.name, error, rejected));
+                        if rejected {
+                            continue;
+                        }
+                    }
+
                     self.files().push(rewritten_file);
                     state
                         .files
@@ -386,4 +477,81 @@ impl<'a> ExecContext<'a, MarzanoQueryContext> for MarzanoContext<'a> {
     fn name(&self) -> Option<&str> {
         self.name.as_deref()
     }
+
+    fn profiler(&self) -> Option<&Profiler> {
+        self.runtime.profiler.as_deref()
+    }
+
+    fn tracer(&self) -> Option<&Tracer> {
+        self.runtime.tracer.as_deref()
+    }
+
+    fn check_budget(&self) -> GritResult<()> {
+        match &self.budget {
+            Some(budget) => budget.step(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The first syntax error in a rewritten file that the rewrite introduced, if any.
+/// Errors inside the rewritten ranges are reported even if the original had the same
+/// error, since the rewrite produced that code. Code outside them is unchanged, so
+/// errors there are only reported if the original file did not have the same error.
+fn introduced_syntax_error(original: &Tree, rewritten: &FileOwner<Tree>) -> Option<Range> {
+    let errors = rewritten.tree.syntax_errors();
+    if errors.is_empty() {
+        return None;
+    }
+    let matches = rewritten.matches.borrow();
+    let rewritten_ranges = matches.byte_ranges.as_deref().unwrap_or_default();
+    let (in_rewrite, outside): (Vec<_>, Vec<_>) = errors.into_iter().partition(|error| {
+        rewritten_ranges.iter().any(|range| {
+            range.start <= (error.start_byte as usize) && (error.end_byte as usize) <= range.end
+        })
+    });
+    if let Some(error) = in_rewrite.first() {
+        return Some(*error);
+    }
+
+    fn error_text<'a>(tree: &'a Tree, error: &Range) -> &'a str {
+        tree.source
+            .get(error.start_byte as usize..error.end_byte as usize)
+            .unwrap_or_default()
+    }
+    let mut original_errors: Vec<&str> = original
+        .syntax_errors()
+        .iter()
+        .map(|error| error_text(original, error))
+        .collect();
+    // Each error in the original can only account for one error in the rewritten file
+    outside.into_iter().find(|error| {
+        let text = error_text(&rewritten.tree, error);
+        match original_errors
+            .iter()
+            .position(|original| *original == text)
+        {
+            Some(index) => {
+                original_errors.swap_remove(index);
+                false
+            }
+            None => true,
+        }
+    })
+}
+
+fn invalid_rewrite_log(file: &Path, error: Range, rejected: bool) -> AnalysisLog {
+    let message = format!(
+        "Rewrite introduces a syntax error at {}{}",
+        error.start,
+        if rejected { ", so it was rejected" } else { "" }
+    );
+    AnalysisLog {
+        level: Some(INVALID_REWRITE_LOG_LEVEL),
+        file: Some(file.to_path_buf()),
+        message,
+        position: Some(error.start),
+        range: Some(error),
+        ..Default::default()
+    }
 }
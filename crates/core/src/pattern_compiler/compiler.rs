@@ -31,6 +31,7 @@ use marzano_language::{
     self, grit_parser::MarzanoGritParser, language::Tree, target_language::TargetLanguage,
 };
 use marzano_util::node_with_source::NodeWithSource;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet},
     ffi::OsStr,
@@ -702,7 +703,7 @@ pub fn src_to_problem(src: String, default_lang: TargetLanguage) -> Result<Probl
     Ok(problem)
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct VariableLocations {
     /// List of scopes, each scope with an array of variables
     pub(crate) locations: Vec<Vec<VariableSource>>,
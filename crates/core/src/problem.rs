@@ -1,13 +1,16 @@
 use crate::{
-    api::{is_match, AnalysisLog, DoneFile, MatchResult},
-    ast_node::{ASTNode, AstLeafNode},
+    api::{is_match, AnalysisLog, DoneFile, Explanation, MatchResult},
+    ast_node::{with_deserializing_language, ASTNode, AstLeafNode},
     built_in_functions::BuiltIns,
+    constants::DEFAULT_FILE_NAME,
     foreign_function_definition::ForeignFunctionDefinition,
+    limits::FileBudget,
     marzano_binding::MarzanoBinding,
     marzano_code_snippet::MarzanoCodeSnippet,
     marzano_context::MarzanoContext,
     marzano_resolved_pattern::{MarzanoFile, MarzanoResolvedPattern},
     pattern_compiler::compiler::VariableLocations,
+    suppress::{find_suppress_comments, SuppressComment},
 };
 use anyhow::{bail, Result};
 use grit_pattern_matcher::{
@@ -19,10 +22,17 @@ use grit_pattern_matcher::{
         PredicateDefinition, ResolvedPattern, State,
     },
 };
-use grit_util::VariableMatch;
+use grit_util::{
+    error::GritPatternError, profile, AnalysisLogs, Ast, FileOrigin, ProfileKind, Tracer,
+    VariableMatch,
+};
 
 use log::error;
-use marzano_language::{language::Tree, target_language::TargetLanguage};
+use marzano_language::{
+    grit_parser::MarzanoGritParser,
+    language::{MarzanoLanguage, Tree},
+    target_language::TargetLanguage,
+};
 use marzano_util::{
     cache::{GritCache, NullCache},
     hasher::hash,
@@ -32,24 +42,33 @@ use marzano_util::{
 };
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use crate::api::FileMatchResult;
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
 };
 use std::{fmt::Debug, str::FromStr};
 use tracing::{event, Level};
 #[cfg(feature = "grit_tracing")]
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-#[derive(Debug)]
+/// Only the compiled patterns are serialized: the tree, language and built-ins are
+/// restored from the pattern source by [`Problem::from_cached`].
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Problem {
+    #[serde(skip)]
     pub tree: Option<Tree>,
     pub pattern: Pattern<MarzanoQueryContext>,
+    #[serde(skip)]
     pub language: TargetLanguage,
+    #[serde(skip, default = "BuiltIns::get_built_in_functions")]
     pub built_ins: BuiltIns,
     pub is_multifile: bool,
     pub has_limit: bool,
@@ -83,6 +102,33 @@ impl Problem {
         }
         defs
     }
+
+    /// Serialize the compiled problem, so it can be cached and loaded with [`Problem::from_cached`].
+    /// Problems with callbacks or other runtime-only state cannot be serialized.
+    pub fn to_cached(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Load a problem serialized by [`Problem::to_cached`], without compiling it again.
+    /// `src`, `default_lang` and `custom_built_ins` must match the ones it was compiled with.
+    pub fn from_cached(
+        bytes: &[u8],
+        src: &str,
+        default_lang: TargetLanguage,
+        custom_built_ins: Option<BuiltIns>,
+    ) -> Result<Self> {
+        let mut parser = MarzanoGritParser::new()?;
+        let tree = parser.parse_file(src, Some(Path::new(DEFAULT_FILE_NAME)))?;
+        let language = TargetLanguage::from_tree(&tree).unwrap_or(default_lang);
+        let mut problem: Self =
+            with_deserializing_language(language, || bincode::deserialize(bytes))?;
+        problem.language = language;
+        if let Some(custom_built_ins) = custom_built_ins {
+            problem.built_ins.extend_builtins(custom_built_ins)?;
+        }
+        problem.tree = Some(tree);
+        Ok(problem)
+    }
 }
 
 enum FilePattern {
@@ -202,6 +248,7 @@ impl Problem {
         &self,
         tx: &Sender<Vec<MatchResult>>,
         files: Vec<impl LoadableFile>,
+        previous_tree: Option<&Tree>,
         context: &ExecutionContext,
         cache: &impl GritCache,
     ) {
@@ -228,6 +275,7 @@ impl Problem {
                         has_results: Some(false),
                         file_hash: Some(file_hash),
                         from_cache: true,
+                        depends_on_date: false,
                     },
                 );
             } else {
@@ -252,15 +300,25 @@ impl Problem {
             file_pointers[0].into()
         };
 
-        self.execute_and_send(tx, files, binding, &owned_files, context, done_files);
+        self.execute_and_send(
+            tx,
+            files,
+            binding,
+            &owned_files,
+            previous_tree,
+            context,
+            done_files,
+        );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn execute_and_send(
         &self,
         tx: &Sender<Vec<MatchResult>>,
         files: Vec<impl LoadableFile>,
         binding: FilePattern,
         owned_files: &FileOwners<Tree>,
+        previous_tree: Option<&Tree>,
         context: &ExecutionContext,
         mut done_files: HashMap<String, DoneFile>,
     ) {
@@ -274,37 +332,65 @@ impl Problem {
             .map(|file| Box::new(file) as Box<dyn LoadableFile>)
             .collect();
 
-        let mut outputs =
-            match self.execute(binding, lazy_files, borrowed_names, owned_files, context) {
-                Result::Err(err) => file_names
-                    .iter()
-                    .map(|file| {
-                        MatchResult::AnalysisLog(AnalysisLog::new_error(
-                            err.to_string(),
-                            &file.to_string_lossy(),
-                        ))
-                    })
-                    .collect(),
-                Result::Ok(messages) => {
-                    // For each message, mark the DoneFile as having results
-                    for message in &messages {
-                        if !is_match(message) {
-                            continue;
+        // Only single files are timed, since multifile patterns run on every file at once
+        let profiler = context
+            .profiler
+            .as_deref()
+            .filter(|_| file_names.len() == 1);
+        let profile_name = file_names
+            .first()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut outputs = match profile(profiler, ProfileKind::File, &profile_name, || {
+            self.execute(
+                binding,
+                lazy_files,
+                borrowed_names,
+                owned_files,
+                previous_tree,
+                context,
+            )
+        }) {
+            Result::Err(err) => {
+                let log = |file: &PathBuf| {
+                    let log = AnalysisLog::new_error(err.to_string(), &file.to_string_lossy());
+                    // Files over budget are skipped, like files that are too big
+                    match err.downcast_ref::<GritPatternError>() {
+                        Some(GritPatternError::BudgetExceeded(_)) => {
+                            AnalysisLog { level: 310, ..log }
                         }
-                        if let Some(name) = message.file_name() {
-                            // .unwrap() is safe, because from_str is infallible
-                            let path = PathBuf::from_str(name).unwrap();
-                            if let Some(done_file) =
-                                done_files.get_mut(path.to_string_lossy().as_ref())
-                            {
-                                done_file.has_results = Some(true);
-                            }
+                        _ => log,
+                    }
+                };
+                file_names
+                    .iter()
+                    .map(|file| MatchResult::AnalysisLog(log(file)))
+                    .collect()
+            }
+            Result::Ok((messages, depends_on_date)) => {
+                if depends_on_date {
+                    for done_file in done_files.values_mut() {
+                        done_file.depends_on_date = true;
+                    }
+                }
+                // For each message, mark the DoneFile as having results
+                for message in &messages {
+                    if !is_match(message) {
+                        continue;
+                    }
+                    if let Some(name) = message.file_name() {
+                        // .unwrap() is safe, because from_str is infallible
+                        let path = PathBuf::from_str(name).unwrap();
+                        if let Some(done_file) = done_files.get_mut(path.to_string_lossy().as_ref())
+                        {
+                            done_file.has_results = Some(true);
                         }
                     }
-
-                    messages
                 }
-            };
+
+                messages
+            }
+        };
 
         outputs.extend(done_files.into_values().map(MatchResult::DoneFile));
 
@@ -387,6 +473,49 @@ impl Problem {
         results
     }
 
+    /// Execute the pattern on a file, recording how it was evaluated against the nodes that start on `line`
+    pub fn explain_file(
+        &self,
+        file: &RichFile,
+        line: u32,
+        context: &ExecutionContext,
+    ) -> Explanation {
+        let tracer = Arc::new(Tracer::new(line));
+        let context = context.clone().with_tracer(tracer.clone());
+        let results = self.execute_file(file, &context);
+        Explanation {
+            results,
+            trace: tracer.trace(),
+            truncated: tracer.is_truncated(),
+        }
+    }
+
+    /// Execute on a single file that we have parsed before, such as an open editor buffer.
+    /// `previous_tree` must already be edited to line up with the file content, so the
+    /// parser only needs to reparse the parts that changed.
+    pub fn execute_edited_file(
+        &self,
+        file: &RichFile,
+        previous_tree: &Tree,
+        context: &ExecutionContext,
+    ) -> Vec<MatchResult> {
+        let mut results = vec![];
+        let (tx, rx) = mpsc::channel::<Vec<MatchResult>>();
+        self.build_and_execute_resolved_pattern(
+            &tx,
+            vec![file],
+            Some(previous_tree),
+            context,
+            &NullCache::new(),
+        );
+        drop(tx);
+        for r in rx.iter() {
+            results.extend(r)
+        }
+        results.sort();
+        results
+    }
+
     /// Given a vec of paths, execute the problem on each path and stream the results
     pub fn execute_paths_streaming(
         &self,
@@ -483,7 +612,7 @@ impl Problem {
         let parent_cx = parent_span.context();
 
         if self.is_multifile {
-            self.build_and_execute_resolved_pattern(&tx, files, context, &NullCache::new());
+            self.build_and_execute_resolved_pattern(&tx, files, None, context, &NullCache::new());
         } else {
             rayon::scope(|s| {
                 #[cfg(feature = "grit_tracing")]
@@ -499,13 +628,65 @@ impl Problem {
 
                     files.into_par_iter().for_each_with(tx, |sender, f| {
                         let vec = vec![f];
-                        self.build_and_execute_resolved_pattern(sender, vec, context, cache);
+                        self.build_and_execute_resolved_pattern(sender, vec, None, context, cache);
                     });
                 })
             })
         }
     }
 
+    /// Run the pattern on a single file, and report each `grit-ignore` comment in it
+    /// along with whether it suppressed a match.
+    pub fn audit_suppressions(
+        &self,
+        file: &RichFile,
+        context: &ExecutionContext,
+    ) -> Result<Vec<(SuppressComment, bool)>> {
+        let path = PathBuf::from(&file.path);
+        let Some(tree) = self.language.get_parser().parse_file(
+            &file.content,
+            Some(&path),
+            &mut AnalysisLogs::default(),
+            FileOrigin::Fresh,
+        ) else {
+            return Ok(vec![]);
+        };
+        let comments = find_suppress_comments(&tree.root_node(), &self.language);
+        if comments.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let owned_files = FileOwners::new();
+        let lazy_files: Vec<Box<dyn LoadableFile>> = vec![Box::new(file)];
+        let context = MarzanoContext::new(
+            &self.pattern_definitions,
+            &self.predicate_definitions,
+            &self.function_definitions,
+            &self.foreign_function_definitions,
+            lazy_files,
+            &owned_files,
+            &self.built_ins,
+            &self.language,
+            context,
+            self.name.clone(),
+        );
+        let binding: FilePattern = if self.is_multifile {
+            vec![FilePtr::new(0, 0)].into()
+        } else {
+            FilePtr::new(0, 0).into()
+        };
+        self.execute_in_context(binding, vec![path.as_path()], &context)?;
+
+        let used = context.used_suppressions();
+        Ok(comments
+            .into_iter()
+            .map(|comment| {
+                let is_used = used.contains(&comment.range.start_byte);
+                (comment, is_used)
+            })
+            .collect())
+    }
+
     /// Construct a context, only for testing
     pub fn get_context<'a>(
         &'a self,
@@ -540,11 +721,18 @@ impl Problem {
         files: Vec<Box<dyn LoadableFile + 'a>>,
         file_names: Vec<&Path>,
         owned_files: &FileOwners<Tree>,
+        previous_tree: Option<&Tree>,
         context: &ExecutionContext,
-    ) -> Result<Vec<MatchResult>> {
-        let mut user_logs = vec![].into();
-
+    ) -> Result<(Vec<MatchResult>, bool)> {
         let lazy_files = files;
+        let budget = FileBudget::from_context(
+            context,
+            self.name.as_deref(),
+            &file_names
+                .iter()
+                .map(|name| name.to_string_lossy().to_string())
+                .collect::<Vec<_>>(),
+        );
 
         let context = MarzanoContext::new(
             &self.pattern_definitions,
@@ -557,7 +745,21 @@ impl Problem {
             &self.language,
             context,
             self.name.clone(),
-        );
+        )
+        .with_previous_tree(previous_tree)
+        .with_budget(budget);
+
+        let results = self.execute_in_context(binding, file_names, &context)?;
+        Ok((results, context.depends_on_date()))
+    }
+
+    fn execute_in_context<'a>(
+        &'a self,
+        binding: FilePattern,
+        file_names: Vec<&'a Path>,
+        context: &'a MarzanoContext<'a>,
+    ) -> Result<Vec<MatchResult>> {
+        let mut user_logs = vec![].into();
 
         let bindings = self.variables.initial_bindings();
 
@@ -574,7 +776,7 @@ impl Problem {
         let binding = binding.into();
         if self
             .pattern
-            .execute(&binding, &mut state, &context, &mut user_logs)?
+            .execute(&binding, &mut state, context, &mut user_logs)?
         {
             for file in state.files.files() {
                 if let Some(result) = MatchResult::file_to_match_result(file)? {
@@ -1,54 +1,273 @@
-use grit_util::{AstNode, Language};
+use chrono::NaiveDate;
+use grit_util::{traverse, AstNode, Language, Order};
 use itertools::{EitherOrBoth, Itertools};
 use marzano_util::node_with_source::NodeWithSource;
+use std::cell::Cell;
 use tree_sitter::Range;
 
+const SUPPRESS_DIRECTIVE: &str = "grit-ignore";
+
+/// What a `grit-ignore` comment applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressKind {
+    /// `grit-ignore`, for the node on the same line or the one following the comment
+    Node,
+    /// `grit-ignore-file`, for the whole file
+    File,
+    /// `grit-ignore-start`, for everything up to the matching `grit-ignore-end`
+    Start,
+    /// `grit-ignore-end`, closing a `grit-ignore-start` region
+    End,
+}
+
+/// A parsed suppression comment, such as `grit-ignore no_console until=2027-01-01: legacy code`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressDirective<'a> {
+    pub kind: SuppressKind,
+    /// The patterns to suppress, or every pattern if empty
+    pub names: Vec<&'a str>,
+    pub reason: Option<&'a str>,
+    /// The date the suppression expires on, as written in the comment
+    pub until: Option<&'a str>,
+}
+
+/// The pattern a suppression is checked for, and the rules for honoring it
+pub(crate) struct SuppressCheck<'a> {
+    pub name: Option<&'a str>,
+    pub require_reason: bool,
+    pub today: NaiveDate,
+    /// Set when a suppression with an `until` date applied, so the result can change over time
+    pub depends_on_date: Cell<bool>,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Strip the end of a block comment, like `*/` or `-->`, from the reason
+fn trim_comment_end(text: &str) -> &str {
+    let text = text.trim();
+    let text = ["-->", "*/", "#}", "%}"]
+        .iter()
+        .find_map(|end| text.strip_suffix(end))
+        .unwrap_or(text);
+    text.trim_end_matches('*').trim()
+}
+
+impl<'a> SuppressDirective<'a> {
+    pub fn parse(text: &'a str) -> Option<Self> {
+        let (_, rest) = text.split_once(SUPPRESS_DIRECTIVE)?;
+        let (kind, rest) = [
+            ("-file", SuppressKind::File),
+            ("-start", SuppressKind::Start),
+            ("-end", SuppressKind::End),
+        ]
+        .into_iter()
+        .find_map(|(suffix, kind)| {
+            let rest = rest.strip_prefix(suffix)?;
+            (!rest.starts_with(is_name_char)).then_some((kind, rest))
+        })
+        .unwrap_or((SuppressKind::Node, rest));
+        // A plain `grit-ignore: reason` has no names, but the other directives read like `grit-ignore-file: names`.
+        // Those give a reason for every pattern with an empty list, like `grit-ignore-file: : reason`.
+        let rest = match kind {
+            SuppressKind::Node => rest,
+            _ => {
+                let trimmed = rest.trim_start();
+                trimmed.strip_prefix(':').unwrap_or(trimmed)
+            }
+        };
+        let (spec, reason) = match rest.split_once(':') {
+            Some((spec, reason)) => (spec, Some(reason)),
+            None => (rest, None),
+        };
+        let mut names = vec![];
+        let mut until = None;
+        for token in spec
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            if let Some(date) = token.strip_prefix("until=") {
+                until = Some(date);
+            } else if token.chars().all(is_name_char) {
+                names.push(token);
+            } else {
+                // Anything else, like the end of the comment, finishes the list of names
+                break;
+            }
+        }
+        Some(Self {
+            kind,
+            names,
+            reason: reason.map(trim_comment_end).filter(|r| !r.is_empty()),
+            until,
+        })
+    }
+
+    /// Suppressions expire on their `until` date. Dates that can't be read count as expired,
+    /// so a typo never hides a violation forever.
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.until.is_some_and(|until| {
+            NaiveDate::parse_from_str(until, "%Y-%m-%d").map_or(true, |until| today >= until)
+        })
+    }
+
+    pub(crate) fn suppresses(&self, check: &SuppressCheck) -> bool {
+        if self.kind == SuppressKind::End || (check.require_reason && self.reason.is_none()) {
+            return false;
+        }
+        if !self.names.is_empty() && !check.name.is_some_and(|name| self.names.contains(&name)) {
+            return false;
+        }
+        if self.until.is_some() {
+            check.depends_on_date.set(true);
+        }
+        !self.is_expired(check.today)
+    }
+}
+
+fn comment_text<'a>(comment_node: &NodeWithSource<'a>) -> &'a str {
+    let range = comment_node.byte_range();
+    comment_node
+        .source
+        .get(range.start..range.end)
+        .unwrap_or("")
+}
+
 pub(crate) fn is_suppress_comment<'a>(
     comment_node: &'a NodeWithSource,
     target_range: &Range,
-    current_name: Option<&str>,
+    check: &SuppressCheck,
     lang: &impl Language<Node<'a> = NodeWithSource<'a>>,
 ) -> bool {
     let child_range = comment_node.node.range();
-    let text = match comment_node.text() {
-        Ok(text) => text,
-        Err(_) => return false,
-    };
+    let text = comment_text(comment_node);
     let inline_suppress = child_range.end_point().row() >= target_range.start_point().row()
         && child_range.end_point().row() <= target_range.end_point().row();
     if !inline_suppress {
         let pre_suppress = comment_applies_to_range(comment_node, target_range, lang)
-            && comment_occupies_entire_line(&text, comment_node);
+            && comment_occupies_entire_line(text, comment_node);
         if !pre_suppress {
             return false;
         }
     }
-    if !text.contains("grit-ignore") {
-        return false;
+    SuppressDirective::parse(text.trim()).is_some_and(|directive| {
+        directive.kind == SuppressKind::Node && directive.suppresses(check)
+    })
+}
+
+/// A `grit-ignore-file` comment, or a `grit-ignore-start` region, in a file
+#[derive(Debug, Clone)]
+pub(crate) struct SuppressRegion<'a> {
+    /// The start byte of the suppressing comment
+    comment_start: u32,
+    /// The bytes covered by a region, or `None` for the whole file
+    bytes: Option<(u32, u32)>,
+    directive: SuppressDirective<'a>,
+}
+
+impl SuppressRegion<'_> {
+    fn contains(&self, target_range: &Range) -> bool {
+        self.bytes.map_or(true, |(start, end)| {
+            start <= target_range.start_byte() && target_range.end_byte() <= end
+        })
     }
-    let comment_text = text.trim();
-    let ignore_spec = match comment_text.split_once("grit-ignore") {
-        Some((_, s)) => match s.split(':').next() {
-            Some(s) => s.trim(),
-            None => return true,
-        },
-        None => return true,
-    };
-    if ignore_spec.is_empty()
-        || ignore_spec
-            .chars()
-            .next()
-            .is_some_and(|c| !c.is_alphanumeric() && c != '_')
-    {
-        return true;
+}
+
+/// Collect the `grit-ignore-file` comments and `grit-ignore-start` regions in a file,
+/// in the order they should be checked
+pub(crate) fn find_suppress_regions<'a>(
+    root: &NodeWithSource<'a>,
+    lang: &impl Language<Node<'a> = NodeWithSource<'a>>,
+) -> Vec<SuppressRegion<'a>> {
+    if !root.source.contains("grit-ignore-") {
+        return vec![];
     }
-    let Some(current_name) = current_name else {
-        return false;
-    };
-    ignore_spec
-        .split(',')
-        .map(str::trim)
-        .contains(&current_name)
+    let mut regions = vec![];
+    let mut open: Vec<(Range, SuppressDirective)> = vec![];
+    for comment in traverse(root.walk(), Order::Pre).filter(|n| lang.is_comment(n)) {
+        let Some(directive) = SuppressDirective::parse(comment_text(&comment)) else {
+            continue;
+        };
+        let range = comment.node.range();
+        match directive.kind {
+            SuppressKind::File => regions.push(SuppressRegion {
+                comment_start: range.start_byte(),
+                bytes: None,
+                directive,
+            }),
+            SuppressKind::Start => open.push((range, directive)),
+            SuppressKind::End => {
+                // An end closes the latest region with the same names, or the latest region if it has none
+                let Some(index) = open.iter().rposition(|(_, start)| {
+                    directive.names.is_empty() || start.names == directive.names
+                }) else {
+                    continue;
+                };
+                let (start_range, start) = open.remove(index);
+                regions.push(SuppressRegion {
+                    comment_start: start_range.start_byte(),
+                    bytes: Some((start_range.end_byte(), range.start_byte())),
+                    directive: start,
+                });
+            }
+            SuppressKind::Node => {}
+        }
+    }
+    // Regions that are never closed run to the end of the file
+    regions.extend(open.into_iter().map(|(range, start)| SuppressRegion {
+        comment_start: range.start_byte(),
+        bytes: Some((range.end_byte(), u32::MAX)),
+        directive: start,
+    }));
+    regions
+}
+
+/// Find the region in `regions` that suppresses the target.
+/// Returns the start byte of the suppressing comment.
+pub(crate) fn find_region_suppression(
+    regions: &[SuppressRegion],
+    target_range: &Range,
+    check: &SuppressCheck,
+) -> Option<u32> {
+    regions
+        .iter()
+        .find(|region| region.contains(target_range) && region.directive.suppresses(check))
+        .map(|region| region.comment_start)
+}
+
+/// A suppression comment in a file, for finding suppressions that are no longer needed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressComment {
+    pub range: grit_util::Range,
+    pub text: String,
+}
+
+impl SuppressComment {
+    pub fn directive(&self) -> Option<SuppressDirective> {
+        SuppressDirective::parse(&self.text)
+    }
+}
+
+/// Collect every suppression comment in a file, except the ends of regions
+pub(crate) fn find_suppress_comments<'a>(
+    root: &NodeWithSource<'a>,
+    lang: &impl Language<Node<'a> = NodeWithSource<'a>>,
+) -> Vec<SuppressComment> {
+    if !root.source.contains(SUPPRESS_DIRECTIVE) {
+        return vec![];
+    }
+    traverse(root.walk(), Order::Pre)
+        .filter(|n| lang.is_comment(n))
+        .filter_map(|comment| {
+            let text = comment_text(&comment);
+            let directive = SuppressDirective::parse(text)?;
+            (directive.kind != SuppressKind::End).then(|| SuppressComment {
+                range: comment.range(),
+                text: text.to_string(),
+            })
+        })
+        .collect()
 }
 
 fn comment_applies_to_range<'a>(
@@ -88,3 +307,76 @@ fn comment_occupies_entire_line(text: &str, node: &NodeWithSource) -> bool {
             }
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: Option<&str>, require_reason: bool) -> SuppressCheck {
+        SuppressCheck {
+            name,
+            require_reason,
+            today: NaiveDate::from_ymd_opt(2026, 6, 1).unwrap(),
+            depends_on_date: Cell::new(false),
+        }
+    }
+
+    #[test]
+    fn parses_directives() {
+        let directive = SuppressDirective::parse("// grit-ignore: We don't want this").unwrap();
+        assert_eq!(directive.kind, SuppressKind::Node);
+        assert!(directive.names.is_empty());
+        assert_eq!(directive.reason, Some("We don't want this"));
+
+        let directive = SuppressDirective::parse(
+            "/* grit-ignore no_console, no_alert until=2027-01-01: legacy */",
+        )
+        .unwrap();
+        assert_eq!(directive.names, vec!["no_console", "no_alert"]);
+        assert_eq!(directive.until, Some("2027-01-01"));
+        assert_eq!(directive.reason, Some("legacy"));
+
+        let directive = SuppressDirective::parse("# grit-ignore-file: no_console").unwrap();
+        assert_eq!(directive.kind, SuppressKind::File);
+        assert_eq!(directive.names, vec!["no_console"]);
+        assert_eq!(directive.reason, None);
+
+        let directive = SuppressDirective::parse("// grit-ignore-file: : generated").unwrap();
+        assert!(directive.names.is_empty());
+        assert_eq!(directive.reason, Some("generated"));
+
+        let directive = SuppressDirective::parse("<!-- grit-ignore-start -->").unwrap();
+        assert_eq!(directive.kind, SuppressKind::Start);
+        assert!(directive.names.is_empty());
+
+        assert!(SuppressDirective::parse("// nothing to see here").is_none());
+    }
+
+    #[test]
+    fn honors_names_reasons_and_expiry() {
+        let named = SuppressDirective::parse("// grit-ignore no_console").unwrap();
+        assert!(named.suppresses(&check(Some("no_console"), false)));
+        assert!(!named.suppresses(&check(Some("no_alert"), false)));
+        assert!(!named.suppresses(&check(None, false)));
+        assert!(!named.suppresses(&check(Some("no_console"), true)));
+
+        let with_reason = SuppressDirective::parse("// grit-ignore: legacy code").unwrap();
+        assert!(with_reason.suppresses(&check(None, true)));
+
+        let expiring = SuppressDirective::parse("// grit-ignore until=2026-06-02").unwrap();
+        let expiring_check = check(None, false);
+        assert!(expiring.suppresses(&expiring_check));
+        assert!(expiring_check.depends_on_date.get());
+        let other_pattern = check(Some("no_alert"), false);
+        assert!(
+            !SuppressDirective::parse("// grit-ignore no_console until=2026-06-02")
+                .unwrap()
+                .suppresses(&other_pattern)
+        );
+        assert!(!other_pattern.depends_on_date.get());
+        let expired = SuppressDirective::parse("// grit-ignore until=2026-06-01").unwrap();
+        assert!(!expired.suppresses(&check(None, false)));
+        let invalid = SuppressDirective::parse("// grit-ignore until=next-year").unwrap();
+        assert!(!invalid.suppresses(&check(None, false)));
+    }
+}
@@ -508,6 +508,97 @@ fn does_not_warn_against_regular_snippet_rewrite() {
     assert!(pattern.compilation_warnings.is_empty())
 }
 
+#[test]
+fn cached_problem_matches_like_the_compiled_one() {
+    let pattern = "
+    pattern console_method_to_info($method) {
+        `console.$method($message)` => `console.info($message)`
+    }
+    console_method_to_info(method = `log`)
+    "
+    .to_string();
+    let libs = BTreeMap::new();
+    let default_language: TargetLanguage = PatternLanguage::Tsx.try_into().unwrap();
+    let compiled = src_to_problem_libs(
+        pattern.clone(),
+        &libs,
+        default_language,
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .problem;
+    let bytes = compiled.to_cached().unwrap();
+    let cached = Problem::from_cached(&bytes, &pattern, default_language, None).unwrap();
+    assert_eq!(cached.hash, compiled.hash);
+
+    let rewritten = |problem: &Problem| {
+        let file = RichFile::new(
+            "test.ts".to_owned(),
+            "console.log('hi');\nconsole.warn('bye');".to_owned(),
+        );
+        problem
+            .execute_file(&file, &ExecutionContext::default())
+            .into_iter()
+            .find_map(|r| match r {
+                MatchResult::Rewrite(r) => r.rewritten.content,
+                _ => None,
+            })
+    };
+    let expected = rewritten(&compiled);
+    assert_eq!(
+        expected.as_deref(),
+        Some("console.info('hi');\nconsole.warn('bye');")
+    );
+    assert_eq!(rewritten(&cached), expected);
+}
+
+#[test]
+fn cached_problem_keeps_string_equivalence_classes() {
+    let pattern = "
+    language yaml
+    `'ubuntu-latest'` => `'ubuntu-22.04'`
+    "
+    .to_string();
+    let libs = BTreeMap::new();
+    let default_language: TargetLanguage = PatternLanguage::Tsx.try_into().unwrap();
+    let compiled = src_to_problem_libs(
+        pattern.clone(),
+        &libs,
+        default_language,
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .problem;
+    let bytes = compiled.to_cached().unwrap();
+    let cached = Problem::from_cached(&bytes, &pattern, default_language, None).unwrap();
+
+    let rewritten = |problem: &Problem| {
+        let file = RichFile::new(
+            "build.yml".to_owned(),
+            "a: ubuntu-latest\nb: \"ubuntu-latest\"\nc: 'ubuntu-latest'\n".to_owned(),
+        );
+        problem
+            .execute_file(&file, &ExecutionContext::default())
+            .into_iter()
+            .find_map(|r| match r {
+                MatchResult::Rewrite(r) => r.rewritten.content,
+                _ => None,
+            })
+    };
+    let expected = rewritten(&compiled);
+    assert_eq!(
+        expected.as_deref(),
+        Some("a: 'ubuntu-22.04'\nb: 'ubuntu-22.04'\nc: 'ubuntu-22.04'\n")
+    );
+    assert_eq!(rewritten(&cached), expected);
+}
+
 #[test]
 fn warns_against_snippet_regex_without_metavars() {
     let pattern = "
@@ -7975,6 +8066,85 @@ fn has_error(mr: &MatchResult, message: &str) -> bool {
     }
 }
 
+#[test]
+fn flags_rewrites_that_introduce_syntax_errors() {
+    let pattern = r#"
+        |language js
+        |
+        |`console.log($msg)` => `console.log($msg`
+        |"#
+    .trim_margin()
+    .unwrap();
+
+    let source = r#"
+        |console.log("hello");
+        |"#
+    .trim_margin()
+    .unwrap();
+
+    let tsx: TargetLanguage = PatternLanguage::Tsx.try_into().unwrap();
+    let problem = src_to_problem(pattern, tsx).unwrap();
+    let file = RichFile::new("test-file.tsx".to_owned(), source);
+    let is_invalid_rewrite_log = |m: &MatchResult| matches!(m, MatchResult::AnalysisLog(l) if l.level == api::INVALID_REWRITE_LOG_LEVEL && l.range.is_some());
+
+    let context = ExecutionContext::default();
+    let results = problem.execute_file(&file, &context);
+    assert!(results.iter().any(is_invalid_rewrite_log));
+    assert!(results.iter().any(|m| matches!(m, MatchResult::Rewrite(_))));
+
+    let mut context = ExecutionContext::default();
+    context.reject_invalid_rewrites = true;
+    let results = problem.execute_file(&file, &context);
+    assert!(results.iter().any(is_invalid_rewrite_log));
+    assert!(!results.iter().any(|m| matches!(m, MatchResult::Rewrite(_))));
+    assert!(results.iter().any(|m| matches!(m, MatchResult::Match(_))));
+}
+
+#[test]
+fn compares_syntax_errors_with_the_original() {
+    let source = r#"
+        |const a = (1;
+        |console.log("hello");
+        |"#
+    .trim_margin()
+    .unwrap();
+    let file = RichFile::new("test-file.tsx".to_owned(), source);
+    let invalid_rewrite_log = |results: &[MatchResult]| {
+        results.iter().find_map(|m| match m {
+            MatchResult::AnalysisLog(l) if l.level == api::INVALID_REWRITE_LOG_LEVEL => {
+                Some(l.clone())
+            }
+            _ => None,
+        })
+    };
+
+    // The error on the first line was already there
+    let valid = r#"
+        |language js
+        |
+        |`console.log($msg)` => `console.warn($msg)`
+        |"#
+    .trim_margin()
+    .unwrap();
+    let problem = src_to_problem(valid, PatternLanguage::Tsx.try_into().unwrap()).unwrap();
+    let results = problem.execute_file(&file, &ExecutionContext::default());
+    assert!(results.iter().any(|m| matches!(m, MatchResult::Rewrite(_))));
+    assert!(invalid_rewrite_log(&results).is_none());
+
+    // A new error is reported where the rewrite introduced it
+    let invalid = r#"
+        |language js
+        |
+        |`console.log($msg)` => `console.log($msg`
+        |"#
+    .trim_margin()
+    .unwrap();
+    let problem = src_to_problem(invalid, PatternLanguage::Tsx.try_into().unwrap()).unwrap();
+    let results = problem.execute_file(&file, &ExecutionContext::default());
+    let log = invalid_rewrite_log(&results).unwrap();
+    assert_eq!(log.range.unwrap().start.line, 2);
+}
+
 #[test]
 fn test_regex() {
     run_test_expected({
@@ -11506,6 +11676,126 @@ fn solidity_respects_suppression() {
     .unwrap();
 }
 
+#[test]
+fn respects_file_suppression() {
+    run_test_no_match({
+        TestArg {
+            pattern: r#"
+                |language js
+                |
+                |`console.log($msg)` => .
+                |"#
+            .trim_margin()
+            .unwrap(),
+            source: r#"
+                |// grit-ignore-file
+                |console.log("one");
+                |function foo() {
+                |  console.log("two");
+                |}
+                |"#
+            .trim_margin()
+            .unwrap(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn respects_region_suppression() {
+    run_test_expected({
+        TestArgExpected {
+            pattern: r#"
+                |language js
+                |
+                |`console.log($msg)` => .
+                |"#
+            .trim_margin()
+            .unwrap(),
+            source: r#"
+                |// grit-ignore-start
+                |console.log("one");
+                |console.log("two");
+                |// grit-ignore-end
+                |console.log("three");
+                |"#
+            .trim_margin()
+            .unwrap(),
+            expected: r#"
+                |// grit-ignore-start
+                |console.log("one");
+                |console.log("two");
+                |// grit-ignore-end
+                |
+                |"#
+            .trim_margin()
+            .unwrap(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn expired_suppressions_are_ignored() {
+    run_test_expected({
+        TestArgExpected {
+            pattern: r#"
+                |language js
+                |
+                |`console.log($msg)` => .
+                |"#
+            .trim_margin()
+            .unwrap(),
+            source: r#"
+                |// grit-ignore until=2020-01-01: migrating soon
+                |console.log("one");
+                |// grit-ignore until=9999-01-01: migrating later
+                |console.log("two");
+                |"#
+            .trim_margin()
+            .unwrap(),
+            expected: r#"
+                |// grit-ignore until=2020-01-01: migrating soon
+                |
+                |// grit-ignore until=9999-01-01: migrating later
+                |console.log("two");
+                |"#
+            .trim_margin()
+            .unwrap(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn reports_results_that_depend_on_the_date() {
+    let pattern = "language js\n\n`console.log($msg)`".to_string();
+    let problem = src_to_problem(pattern, PatternLanguage::Tsx.try_into().unwrap()).unwrap();
+    let depends_on_date = |source: &str| {
+        let file = RichFile::new("test.js".to_owned(), source.to_owned());
+        problem
+            .execute_file(&file, &ExecutionContext::default())
+            .into_iter()
+            .find_map(|r| match r {
+                MatchResult::DoneFile(done) => Some(done.depends_on_date),
+                _ => None,
+            })
+            .unwrap()
+    };
+    assert!(depends_on_date(
+        "// grit-ignore until=9999-01-01: migrating later\nconsole.log('one');"
+    ));
+    assert!(depends_on_date(
+        "// grit-ignore-start until=2020-01-01: migrating soon\nconsole.log('one');\n// grit-ignore-end"
+    ));
+    assert!(!depends_on_date(
+        "// grit-ignore: forever\nconsole.log('one');"
+    ));
+    assert!(!depends_on_date(
+        "// grit-ignore until=9999-01-01: unrelated\nalert('one');"
+    ));
+}
+
 #[test]
 fn does_not_delete_comma_after_type_annotation() {
     run_test_expected({
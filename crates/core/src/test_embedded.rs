@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use marzano_language::target_language::TargetLanguage;
+
+    use crate::api::FileMatchResult;
+    use crate::{
+        api::MatchResult,
+        pattern_compiler::src_to_problem_libs,
+        test_utils::{run_on_test_files, SyntheticFile},
+    };
+
+    use std::collections::BTreeMap;
+
+    fn run_file(pattern_src: &str, lang: &str, name: &str, content: &str) -> Vec<MatchResult> {
+        let libs = BTreeMap::new();
+        let pattern = src_to_problem_libs(
+            pattern_src.to_string(),
+            &libs,
+            TargetLanguage::from_string(lang, None).unwrap(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .problem;
+
+        let test_files = vec![SyntheticFile::new(
+            name.to_owned(),
+            content.to_owned(),
+            true,
+        )];
+        let results = run_on_test_files(&pattern, &test_files);
+        assert!(!results.iter().any(|r| r.is_error()));
+        results
+    }
+
+    fn rewrite_file(pattern_src: &str, lang: &str, name: &str, content: &str) -> Option<String> {
+        let results = run_file(pattern_src, lang, name, content);
+        results.iter().find_map(|r| match r {
+            MatchResult::Rewrite(rewrite) => Some(rewrite.content().unwrap().to_string()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn rewrites_html_scripts() {
+        let content = r#"<html>
+<head>
+  <script>
+    console.log("head");
+  </script>
+</head>
+<body>
+  <p>console.log("not code")</p>
+  <script type="module">
+    console.log("body");
+  </script>
+</body>
+</html>
+"#;
+        let rewritten = rewrite_file(
+            "language js\n\n`console.log($x)` => `logger.info($x)`",
+            "js",
+            "index.html",
+            content,
+        )
+        .unwrap();
+        assert_eq!(
+            rewritten,
+            r#"<html>
+<head>
+  <script>
+    logger.info("head");
+  </script>
+</head>
+<body>
+  <p>console.log("not code")</p>
+  <script type="module">
+    logger.info("body");
+  </script>
+</body>
+</html>
+"#
+        );
+    }
+
+    #[test]
+    fn rewrites_svelte_styles() {
+        let content = r#"<script>
+  let name = "world";
+</script>
+
+<h1>Hello {name}!</h1>
+
+<style>
+  h1 { color: red; }
+</style>
+"#;
+        let rewritten = rewrite_file(
+            "language css\n\n`color: red` => `color: blue`",
+            "css",
+            "App.svelte",
+            content,
+        )
+        .unwrap();
+        assert_eq!(rewritten, content.replace("color: red", "color: blue"));
+    }
+
+    #[test]
+    fn rewrites_astro_frontmatter() {
+        let content = r#"---
+const title = getTitle();
+---
+<h1>{title}</h1>
+<script>
+  const other = getTitle();
+</script>
+"#;
+        let rewritten = rewrite_file(
+            "language js\n\n`getTitle()` => `loadTitle()`",
+            "js",
+            "page.astro",
+            content,
+        )
+        .unwrap();
+        assert_eq!(rewritten, content.replace("getTitle", "loadTitle"));
+    }
+
+    #[test]
+    fn rewrites_markdown_code_blocks() {
+        let content = r#"# Logging
+
+```python
+print("hello")
+```
+
+```js
+print("not python");
+```
+
+Call `print("inline")` to log.
+"#;
+        let rewritten = rewrite_file(
+            "language python\n\n`print($x)` => `log($x)`",
+            "python",
+            "README.md",
+            content,
+        )
+        .unwrap();
+        assert_eq!(
+            rewritten,
+            content.replace(r#"print("hello")"#, r#"log("hello")"#)
+        );
+    }
+
+    #[test]
+    fn reports_markdown_positions() {
+        let content = r#"# Usage
+
+Some text first.
+
+```ts
+const a = 1;
+console.log(a);
+```
+"#;
+        let results = run_file(
+            "language js\n\n`console.log($x)`",
+            "js",
+            "docs/usage.md",
+            content,
+        );
+        let m = results
+            .iter()
+            .find_map(|r| match r {
+                MatchResult::Match(m) => Some(m),
+                _ => None,
+            })
+            .unwrap();
+        let range = &m.ranges[0];
+        assert_eq!(range.start.line, 7);
+        assert_eq!(range.start.column, 1);
+        assert_eq!(
+            &content[range.start_byte as usize..range.end_byte as usize],
+            "console.log(a)"
+        );
+        let x = m.variables.iter().find(|v| v.name == "$x").unwrap();
+        assert_eq!(x.ranges[0].start.line, 7);
+        assert_eq!(x.ranges[0].start.column, 13);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{api::MatchResult, pattern_compiler::src_to_problem_libs};
+use grit_util::format_trace;
+use marzano_language::target_language::TargetLanguage;
+use marzano_util::{rich_path::RichFile, runtime::ExecutionContext};
+use std::collections::BTreeMap;
+
+fn explain(pattern_src: &str, content: &str, line: u32) -> (Vec<MatchResult>, String) {
+    let libs = BTreeMap::new();
+    let problem = src_to_problem_libs(
+        pattern_src.to_string(),
+        &libs,
+        TargetLanguage::default(),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .problem;
+    let explanation = problem.explain_file(
+        &RichFile::new("test.js".to_string(), content.to_string()),
+        line,
+        &ExecutionContext::default(),
+    );
+    assert!(!explanation.truncated);
+    (explanation.results, format_trace(&explanation.trace))
+}
+
+#[test]
+fn explains_failed_where() {
+    let (results, trace) = explain(
+        r#"`console.log($msg)` where { $msg <: `bar` }"#,
+        "const a = 1;\nconsole.log(foo);\n",
+        2,
+    );
+    assert!(!results.iter().any(|r| matches!(r, MatchResult::Match(_))));
+    assert!(trace.contains("on `console.log(foo)` (2:1)"));
+    assert!(trace.contains("✓ `console.log($msg)`"));
+    assert!(trace.contains("✓ $msg = `foo`"));
+    assert!(trace.contains("✗ where"));
+    assert!(trace.contains("✗ `bar`"));
+    // Nothing on the first line is traced
+    assert!(!trace.contains("const a"));
+}
+
+#[test]
+fn explains_named_patterns() {
+    let (results, trace) = explain(
+        r#"pattern log_call() { `console.log($_)` }
+log_call()"#,
+        "console.log(foo);\n",
+        1,
+    );
+    assert!(results.iter().any(|r| matches!(r, MatchResult::Match(_))));
+    assert!(trace.contains("✓ log_call()"));
+}
@@ -35,11 +35,10 @@ pub(crate) fn apply_effects<'a, Q: QueryContext>(
     logs: &mut AnalysisLogs,
 ) -> GritResult<EffectOutcome> {
     let language = context.language();
-    let current_name = context.name();
 
     let effects: Vec<_> = effects
         .into_iter()
-        .filter(|effect| !effect.binding.is_suppressed(language, current_name))
+        .filter(|effect| !effect.binding.is_suppressed(context))
         .collect();
     if effects.is_empty() {
         return Ok((code.source().to_string(), None, None));
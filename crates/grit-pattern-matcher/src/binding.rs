@@ -51,7 +51,8 @@ pub trait Binding<'a, Q: QueryContext>: Clone + std::fmt::Debug + PartialEq + Si
     /// Bindings are considered equivalent if they refer to the same thing.
     fn is_equivalent_to(&self, other: &Self, language: &Q::Language<'a>) -> bool;
 
-    fn is_suppressed(&self, language: &Q::Language<'a>, current_name: Option<&str>) -> bool;
+    /// Checks whether a `grit-ignore` comment suppresses the binding for the pattern being run.
+    fn is_suppressed(&self, context: &Q::ExecContext<'a>) -> bool;
 
     /// Returns the padding to use for inserting the given text.
     fn get_insertion_padding(
@@ -7,7 +7,7 @@ use crate::{
         State,
     },
 };
-use grit_util::{error::GritResult, AnalysisLogs, Ast, AstNode, Language};
+use grit_util::{error::GritResult, AnalysisLogs, Ast, AstNode, Language, Profiler, Tracer};
 
 /// Contains various kinds of context about the query being executed.
 pub trait QueryContext: Clone + std::fmt::Debug + Sized + 'static {
@@ -75,6 +75,21 @@ pub trait ExecContext<'a, Q: QueryContext> {
     ) -> GritResult<bool>;
 
     fn name(&self) -> Option<&str>;
+
+    /// The profiler to record definition calls with, if profiling is enabled
+    fn profiler(&self) -> Option<&Profiler> {
+        None
+    }
+
+    /// The tracer to record pattern steps with, when explaining a match
+    fn tracer(&self) -> Option<&Tracer> {
+        None
+    }
+
+    /// Called for every pattern step, to abort files that exceed their execution budget
+    fn check_budget(&self) -> GritResult<()> {
+        Ok(())
+    }
 }
 
 /// Static information used for a pattern
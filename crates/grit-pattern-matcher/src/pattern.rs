@@ -18,6 +18,7 @@ mod divide;
 mod dynamic_snippet;
 mod equal;
 mod every;
+mod explain;
 mod file_pattern;
 mod files;
 mod float_constant;
@@ -48,6 +49,7 @@ mod regex;
 mod resolved_pattern;
 mod rewrite;
 mod sequential;
+mod serialization;
 mod some;
 mod state;
 mod step;
@@ -111,6 +113,7 @@ pub use resolved_pattern::ResolvedPattern;
 pub use resolved_pattern::{File, JoinFn, LazyBuiltIn, ResolvedFile, ResolvedSnippet};
 pub use rewrite::Rewrite;
 pub use sequential::Sequential;
+pub use serialization::SerializablePattern;
 pub use some::Some;
 pub use state::{get_top_level_effects, EffectRange, FilePtr, FileRegistry, State};
 pub use step::Step;
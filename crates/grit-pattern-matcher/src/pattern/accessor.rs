@@ -17,18 +17,29 @@ use grit_util::{
 use std::borrow::Cow;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum AccessorMap<Q: QueryContext> {
     Container(Container<Q>),
     Map(GritMap<Q>),
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Accessor<Q: QueryContext> {
     pub map: AccessorMap<Q>,
     pub key: AccessorKey,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccessorKey {
     String(String),
     Variable(Variable),
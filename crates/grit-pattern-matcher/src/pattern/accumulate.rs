@@ -15,6 +15,11 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Accumulate<Q: QueryContext> {
     pub(crate) left: Pattern<Q>,
     pub(crate) right: Pattern<Q>,
@@ -8,6 +8,11 @@ use crate::context::QueryContext;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct And<Q: QueryContext> {
     pub patterns: Vec<Pattern<Q>>,
 }
@@ -42,6 +47,11 @@ impl<Q: QueryContext> Matcher<Q> for And<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrAnd<Q: QueryContext> {
     pub predicates: Vec<Predicate<Q>>,
 }
@@ -9,6 +9,11 @@ use core::fmt::Debug;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Any<Q: QueryContext> {
     pub patterns: Vec<Pattern<Q>>,
 }
@@ -55,6 +60,11 @@ impl<Q: QueryContext> Matcher<Q> for Any<Q> {
     }
 }
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrAny<Q: QueryContext> {
     pub predicates: Vec<Predicate<Q>>,
 }
@@ -9,6 +9,11 @@ use crate::context::{ExecContext, QueryContext};
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Assignment<Q: QueryContext> {
     pub container: Container<Q>,
     pub pattern: Pattern<Q>,
@@ -1,12 +1,13 @@
 use super::{
     iter_pattern::PatternOrPredicate,
     patterns::{Matcher, PatternName},
+    serialization::SerializablePattern,
 };
 use crate::context::{QueryContext, StaticDefinitions};
 
 /// Type of pattern that matches against an individual (non-leaf) AST node.
 pub trait AstNodePattern<Q: QueryContext>:
-    Clone + std::fmt::Debug + Matcher<Q> + PatternName + Sized
+    Clone + std::fmt::Debug + Matcher<Q> + PatternName + SerializablePattern + Sized
 {
     /// Does this AST include trivia?
     /// Trivia is useful for being able to re-print an AST, but not all parsers support collecting it.
@@ -22,7 +23,7 @@ pub trait AstNodePattern<Q: QueryContext>:
 
 /// Type of pattern that matches against an individual AST leaf node.
 pub trait AstLeafNodePattern<Q: QueryContext>:
-    Clone + std::fmt::Debug + Matcher<Q> + PatternName + Sized
+    Clone + std::fmt::Debug + Matcher<Q> + PatternName + SerializablePattern + Sized
 {
     /// Provides a *possible* text value for the leaf node.
     /// This is not mandatory, but enables some advanced functionality.
@@ -14,6 +14,11 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Before<Q: QueryContext> {
     pub before: Pattern<Q>,
 }
@@ -7,6 +7,7 @@ use crate::context::{ExecContext, QueryContext};
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BooleanConstant {
     pub value: bool,
 }
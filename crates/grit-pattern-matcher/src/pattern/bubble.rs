@@ -6,6 +6,11 @@ use crate::context::QueryContext;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Bubble<Q: QueryContext> {
     pub pattern_def: PatternDefinition<Q>,
     pub args: Vec<Option<Pattern<Q>>>,
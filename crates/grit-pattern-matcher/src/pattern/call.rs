@@ -12,6 +12,11 @@ use grit_util::{
 };
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Call<Q: QueryContext> {
     pub index: usize,
     pub args: Vec<Option<Pattern<Q>>>,
@@ -46,6 +51,11 @@ impl<Q: QueryContext> Matcher<Q> for Call<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrCall<Q: QueryContext> {
     pub(crate) index: usize,
     pub args: Vec<Option<Pattern<Q>>>,
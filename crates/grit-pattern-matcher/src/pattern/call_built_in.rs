@@ -10,6 +10,11 @@ use grit_util::{error::GritResult, AnalysisLogs};
 // and calls the input function with the vec args unpacked.
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct CallBuiltIn<Q: QueryContext> {
     pub index: usize,
     pub name: String,
@@ -13,6 +13,11 @@ use crate::context::QueryContext;
 /// - Accessor: a map accessor (ex. `$foo.bar`)
 /// - ListIndex: a list index (ex. `$foo[0]`)
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum Container<Q: QueryContext> {
     Variable(Variable),
     Accessor(Box<Accessor<Q>>),
@@ -13,6 +13,11 @@ use grit_util::{error::GritResult, AnalysisLogs};
 use grit_util::{AstCursor, AstNode};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Contains<Q: QueryContext> {
     pub contains: Pattern<Q>,
     pub until: Option<Pattern<Q>>,
@@ -10,6 +10,11 @@ use crate::{
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Divide<Q: QueryContext> {
     pub lhs: Pattern<Q>,
     pub rhs: Pattern<Q>,
@@ -12,22 +12,34 @@ use crate::context::{ExecContext, QueryContext};
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DynamicSnippetPart {
     String(String),
     Variable(Variable),
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DynamicSnippet {
     pub parts: Vec<DynamicSnippetPart>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct DynamicList<Q: QueryContext> {
     pub elements: Vec<DynamicPattern<Q>>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum DynamicPattern<Q: QueryContext> {
     Variable(Variable),
     Accessor(Box<Accessor<Q>>),
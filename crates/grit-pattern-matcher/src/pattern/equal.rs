@@ -8,6 +8,11 @@ use crate::context::{ExecContext, QueryContext};
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Equal<Q: QueryContext> {
     pub var: Variable,
     pub pattern: Pattern<Q>,
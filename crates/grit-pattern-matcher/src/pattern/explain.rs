@@ -0,0 +1,112 @@
+use super::{
+    functions::FuncEvaluation,
+    patterns::{CodeSnippet, Pattern, PatternName},
+    predicates::Predicate,
+    resolved_pattern::ResolvedPattern,
+    variable::Variable,
+    State,
+};
+use crate::context::{ExecContext, QueryContext};
+use grit_util::{error::GritResult, AnalysisLogs, Tracer};
+
+fn variable_label<Q: QueryContext>(variable: &Variable, state: &mut State<'_, Q>) -> String {
+    let (Ok(scope), Ok(index)) = (variable.get_scope(state), variable.get_index(state)) else {
+        return "variable".to_string();
+    };
+    state
+        .bindings
+        .get(scope as usize)
+        .and_then(|scope| scope.last())
+        .and_then(|scope| scope.get(index as usize))
+        .map(|content| content.name.clone())
+        .unwrap_or_else(|| "variable".to_string())
+}
+
+fn pattern_label<'a, Q: QueryContext>(
+    pattern: &'a Pattern<Q>,
+    state: &mut State<'a, Q>,
+    context: &'a Q::ExecContext<'a>,
+) -> String {
+    let label = match pattern {
+        Pattern::Call(call) => context
+            .pattern_definitions()
+            .get(call.index)
+            .map(|definition| format!("{}()", definition.name)),
+        Pattern::Variable(variable) => Some(variable_label(variable, state)),
+        Pattern::CodeSnippet(snippet) => snippet.source().map(|source| format!("`{}`", source)),
+        Pattern::StringConstant(string) => Some(format!("{:?}", string.text)),
+        _ => None,
+    };
+    label.unwrap_or_else(|| pattern.name().to_lowercase())
+}
+
+fn predicate_label<'a, Q: QueryContext>(
+    predicate: &'a Predicate<Q>,
+    context: &'a Q::ExecContext<'a>,
+) -> String {
+    let label = match predicate {
+        Predicate::Call(call) => context
+            .predicate_definitions()
+            .get(call.index)
+            .map(|definition| format!("{}()", definition.name)),
+        _ => None,
+    };
+    label.unwrap_or_else(|| {
+        let name = predicate.name();
+        name.strip_prefix("PREDICATE_")
+            .unwrap_or(name)
+            .to_lowercase()
+    })
+}
+
+/// Execute a pattern, recording it if it runs against the traced line or within a recorded step
+pub(crate) fn trace_pattern<'a, Q: QueryContext>(
+    pattern: &'a Pattern<Q>,
+    tracer: &Tracer,
+    binding: &Q::ResolvedPattern<'a>,
+    state: &mut State<'a, Q>,
+    context: &'a Q::ExecContext<'a>,
+    logs: &mut AnalysisLogs,
+) -> GritResult<bool> {
+    let language = context.language();
+    let range = binding.position(language);
+    // Contains visits every node below the one it starts from, so traces start from the nodes it reaches
+    let starts_trace = !matches!(pattern, Pattern::Contains(_))
+        && range
+            .as_ref()
+            .is_some_and(|range| range.start.line == tracer.line());
+    if !starts_trace && !tracer.is_tracing() {
+        return pattern.execute_step(binding, state, context, logs);
+    }
+
+    let label = pattern_label(pattern, state, context);
+    let text = if binding.get_file().is_some() || binding.get_files().is_some() {
+        None
+    } else {
+        binding
+            .text(&state.files, language)
+            .ok()
+            .map(|text| text.into_owned())
+    };
+    tracer.enter(label, text, range);
+    let result = pattern.execute_step(binding, state, context, logs);
+    tracer.exit(matches!(result, Ok(true)));
+    result
+}
+
+/// Execute a predicate, recording it if it runs within a recorded step
+pub(crate) fn trace_predicate<'a, Q: QueryContext>(
+    predicate: &'a Predicate<Q>,
+    tracer: &Tracer,
+    state: &mut State<'a, Q>,
+    context: &'a Q::ExecContext<'a>,
+    logs: &mut AnalysisLogs,
+) -> GritResult<FuncEvaluation<'a, Q>> {
+    if !tracer.is_tracing() {
+        return predicate.execute_step(state, context, logs);
+    }
+    tracer.enter(predicate_label(predicate, context), None, None);
+    let result = predicate.execute_step(state, context, logs);
+    tracer.exit(matches!(&result, Ok(evaluation) if evaluation.predicator));
+    result
+}
@@ -11,6 +11,11 @@ use crate::{context::QueryContext, pattern::resolved_pattern::File};
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct FilePattern<Q: QueryContext> {
     pub name: Pattern<Q>,
     pub body: Pattern<Q>,
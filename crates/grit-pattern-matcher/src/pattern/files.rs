@@ -7,6 +7,11 @@ use crate::context::QueryContext;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Files<Q: QueryContext> {
     pub pattern: Pattern<Q>,
 }
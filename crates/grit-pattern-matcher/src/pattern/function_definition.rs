@@ -5,8 +5,8 @@ use super::{
     state::State,
     variable::Variable,
 };
-use crate::context::QueryContext;
-use grit_util::{error::GritResult, AnalysisLogs};
+use crate::context::{ExecContext, QueryContext};
+use grit_util::{error::GritResult, profile, AnalysisLogs, ProfileKind};
 
 pub trait FunctionDefinition<Q: QueryContext> {
     fn call<'a>(
@@ -19,6 +19,11 @@ pub trait FunctionDefinition<Q: QueryContext> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct GritFunctionDefinition<Q: QueryContext> {
     pub name: String,
     pub scope: usize,
@@ -54,7 +59,12 @@ impl<Q: QueryContext> FunctionDefinition<Q> for GritFunctionDefinition<Q> {
         logs: &mut AnalysisLogs,
     ) -> GritResult<FuncEvaluation<Q>> {
         let tracker = state.enter_scope(self.scope, args);
-        let res = self.function.execute_func(state, context, logs);
+        let res = profile(
+            context.profiler(),
+            ProfileKind::Function,
+            &self.name,
+            || self.function.execute_func(state, context, logs),
+        );
         state.exit_scope(tracker);
         res
     }
@@ -26,6 +26,11 @@ pub trait Evaluator<Q: QueryContext>: Debug {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct CallFunction<Q: QueryContext> {
     pub index: usize,
     pub args: Vec<Option<Pattern<Q>>>,
@@ -74,6 +79,11 @@ impl<Q: QueryContext> PatternName for CallFunction<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct CallForeignFunction<Q: QueryContext> {
     pub index: usize,
     pub args: Vec<Option<Pattern<Q>>>,
@@ -12,6 +12,11 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct If<Q: QueryContext> {
     pub if_: Predicate<Q>,
     pub then: Pattern<Q>,
@@ -52,6 +57,11 @@ impl<Q: QueryContext> Matcher<Q> for If<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrIf<Q: QueryContext> {
     pub if_: Predicate<Q>,
     pub then: Predicate<Q>,
@@ -11,6 +11,11 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Includes<Q: QueryContext> {
     pub includes: Pattern<Q>,
 }
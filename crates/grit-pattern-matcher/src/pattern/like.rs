@@ -9,6 +9,11 @@ use grit_util::AnalysisLogs;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Like<Q: QueryContext> {
     pub like: Pattern<Q>,
     pub threshold: Pattern<Q>,
@@ -10,9 +10,15 @@ use std::sync::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Limit<Q: QueryContext> {
     pub pattern: Pattern<Q>,
     pub limit: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub invocation_count: Arc<AtomicUsize>,
 }
 
@@ -13,6 +13,11 @@ use grit_util::{
 use std::borrow::Cow;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct List<Q: QueryContext> {
     pub patterns: Vec<Pattern<Q>>,
 }
@@ -16,18 +16,33 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum ListOrContainer<Q: QueryContext> {
     Container(Container<Q>),
     List(List<Q>),
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum ContainerOrIndex<Q: QueryContext> {
     Container(Container<Q>),
     Index(isize),
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct ListIndex<Q: QueryContext> {
     pub list: ListOrContainer<Q>,
     pub index: ContainerOrIndex<Q>,
@@ -8,6 +8,11 @@ use grit_util::{error::GritResult, AnalysisLogs};
 use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct GritMap<Q: QueryContext> {
     pub elements: BTreeMap<String, Pattern<Q>>,
 }
@@ -13,6 +13,11 @@ use crate::{
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Match<Q: QueryContext> {
     pub val: Container<Q>,
     pub pattern: Option<Pattern<Q>>,
@@ -8,6 +8,11 @@ use crate::context::QueryContext;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Maybe<Q: QueryContext> {
     pub pattern: Pattern<Q>,
 }
@@ -41,6 +46,11 @@ impl<Q: QueryContext> PatternName for Maybe<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrMaybe<Q: QueryContext> {
     pub(crate) predicate: Predicate<Q>,
 }
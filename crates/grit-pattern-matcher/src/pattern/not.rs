@@ -12,6 +12,11 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Not<Q: QueryContext> {
     pub pattern: Pattern<Q>,
 }
@@ -43,6 +48,11 @@ impl<Q: QueryContext> Matcher<Q> for Not<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrNot<Q: QueryContext> {
     pub(crate) predicate: Predicate<Q>,
 }
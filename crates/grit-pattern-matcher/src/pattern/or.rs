@@ -11,6 +11,11 @@ use core::fmt::Debug;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Or<Q: QueryContext> {
     pub patterns: Vec<Pattern<Q>>,
 }
@@ -66,6 +71,11 @@ impl<Q: QueryContext> Matcher<Q> for Or<Q> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrOr<Q: QueryContext> {
     pub predicates: Vec<Predicate<Q>>,
 }
@@ -3,20 +3,26 @@ use super::{
     variable::Variable,
     State,
 };
-use crate::context::QueryContext;
+use crate::context::{ExecContext, QueryContext};
 use grit_util::{
     error::{GritPatternError, GritResult},
-    AnalysisLogs,
+    profile, AnalysisLogs, ProfileKind,
 };
 use rand::Rng as _;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PatternDefinitionInternal {
     Static { scope: usize },
     Dynamic,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PatternDefinition<Q: QueryContext> {
     pub name: String,
     pattern: Pattern<Q>,
@@ -85,7 +91,14 @@ impl<Q: QueryContext> PatternDefinition<Q> {
         let scope = self.get_scope(state);
         let tracker = state.enter_scope(scope, args);
 
-        let res = self.pattern.execute(binding, state, context, logs);
+        // Ephemeral definitions have random names, so they aren't worth profiling
+        let profiler = match self.internal {
+            PatternDefinitionInternal::Static { .. } => context.profiler(),
+            PatternDefinitionInternal::Dynamic => None,
+        };
+        let res = profile(profiler, ProfileKind::Pattern, &self.name, || {
+            self.pattern.execute(binding, state, context, logs)
+        });
         state.exit_scope(tracker);
 
         let fn_state = state.bindings[scope].pop().unwrap();
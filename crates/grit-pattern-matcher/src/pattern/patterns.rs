@@ -15,6 +15,7 @@ use super::{
     divide::Divide,
     dynamic_snippet::DynamicPattern,
     every::Every,
+    explain::trace_pattern,
     file_pattern::FilePattern,
     files::Files,
     float_constant::FloatConstant,
@@ -38,6 +39,7 @@ use super::{
     resolved_pattern::ResolvedPattern,
     rewrite::Rewrite,
     sequential::Sequential,
+    serialization::SerializablePattern,
     some::Some,
     string_constant::StringConstant,
     subtract::Subtract,
@@ -80,6 +82,11 @@ pub trait PatternName {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum Pattern<Q: QueryContext> {
     AstNode(Box<Q::NodePattern>),
     List(Box<List<Q>>),
@@ -95,6 +102,7 @@ pub enum Pattern<Q: QueryContext> {
     CallBuiltIn(Box<CallBuiltIn<Q>>),
     CallFunction(Box<CallFunction<Q>>),
     CallForeignFunction(Box<CallForeignFunction<Q>>),
+    #[cfg_attr(feature = "serde", serde(skip))]
     CallbackPattern(Box<CallbackPattern>),
     Assignment(Box<Assignment<Q>>),
     Accumulate(Box<Accumulate<Q>>),
@@ -230,6 +238,23 @@ impl<Q: QueryContext> Matcher<Q> for Pattern<Q> {
         state: &mut State<'a, Q>,
         context: &'a Q::ExecContext<'a>,
         logs: &mut AnalysisLogs,
+    ) -> GritResult<bool> {
+        context.check_budget()?;
+
+        match context.tracer() {
+            Some(tracer) => trace_pattern(self, tracer, binding, state, context, logs),
+            None => self.execute_step(binding, state, context, logs),
+        }
+    }
+}
+
+impl<Q: QueryContext> Pattern<Q> {
+    pub(crate) fn execute_step<'a>(
+        &'a self,
+        binding: &Q::ResolvedPattern<'a>,
+        state: &mut State<'a, Q>,
+        context: &'a Q::ExecContext<'a>,
+        logs: &mut AnalysisLogs,
     ) -> GritResult<bool> {
         if let Some(file) = binding.get_file() {
             state.bindings[GLOBAL_VARS_SCOPE_INDEX as usize]
@@ -314,9 +339,16 @@ impl<Q: QueryContext> Matcher<Q> for Pattern<Q> {
     }
 }
 
-pub trait CodeSnippet<Q: QueryContext + 'static>: Clone + Debug + Matcher<Q> + PatternName {
+pub trait CodeSnippet<Q: QueryContext + 'static>:
+    Clone + Debug + Matcher<Q> + PatternName + SerializablePattern
+{
     /// Return the different patterns which could *all* possibly match the code snippet.
     fn patterns(&self) -> impl Iterator<Item = &Pattern<Q>>;
 
     fn dynamic_snippet(&self) -> Option<&DynamicPattern<Q>>;
+
+    /// The snippet as written in the pattern, if available
+    fn source(&self) -> Option<&str> {
+        None
+    }
 }
@@ -1,10 +1,15 @@
 use super::{
     functions::Evaluator, patterns::Pattern, predicates::Predicate, variable::Variable, State,
 };
-use crate::context::QueryContext;
-use grit_util::{error::GritResult, AnalysisLogs};
+use crate::context::{ExecContext, QueryContext};
+use grit_util::{error::GritResult, profile, AnalysisLogs, ProfileKind};
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PredicateDefinition<Q: QueryContext> {
     pub name: String,
     pub scope: usize,
@@ -39,7 +44,12 @@ impl<Q: QueryContext> PredicateDefinition<Q> {
         logs: &mut AnalysisLogs,
     ) -> GritResult<bool> {
         let tracker = state.enter_scope(self.scope, args);
-        let res = self.predicate.execute_func(state, context, logs)?;
+        let res = profile(
+            context.profiler(),
+            ProfileKind::Predicate,
+            &self.name,
+            || self.predicate.execute_func(state, context, logs),
+        )?;
         state.exit_scope(tracker);
         Ok(res.predicator)
     }
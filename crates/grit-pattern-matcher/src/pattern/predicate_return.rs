@@ -8,6 +8,11 @@ use crate::context::QueryContext;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct PrReturn<Q: QueryContext> {
     pub pattern: Pattern<Q>,
 }
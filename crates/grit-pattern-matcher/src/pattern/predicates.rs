@@ -5,6 +5,7 @@ use super::{
     assignment::Assignment,
     call::PrCall,
     equal::Equal,
+    explain::trace_predicate,
     functions::{Evaluator, FuncEvaluation},
     maybe::PrMaybe,
     not::PrNot,
@@ -16,11 +17,16 @@ use super::{
     rewrite::Rewrite,
     CallBuiltIn, State,
 };
-use crate::context::QueryContext;
+use crate::context::{ExecContext, QueryContext};
 use core::fmt::Debug;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum Predicate<Q: QueryContext> {
     Call(Box<PrCall<Q>>),
     CallBuiltIn(Box<CallBuiltIn<Q>>),
@@ -69,6 +75,20 @@ impl<Q: QueryContext> Evaluator<Q> for Predicate<Q> {
         state: &mut State<'a, Q>,
         context: &'a Q::ExecContext<'a>,
         logs: &mut AnalysisLogs,
+    ) -> GritResult<FuncEvaluation<Q>> {
+        match context.tracer() {
+            Some(tracer) => trace_predicate(self, tracer, state, context, logs),
+            None => self.execute_step(state, context, logs),
+        }
+    }
+}
+
+impl<Q: QueryContext> Predicate<Q> {
+    pub(crate) fn execute_step<'a>(
+        &'a self,
+        state: &mut State<'a, Q>,
+        context: &'a Q::ExecContext<'a>,
+        logs: &mut AnalysisLogs,
     ) -> GritResult<FuncEvaluation<Q>> {
         match self {
             Predicate::Call(call) => call.execute_func(state, context, logs),
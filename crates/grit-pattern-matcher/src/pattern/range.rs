@@ -10,6 +10,7 @@ use grit_util::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     line: u32,
     column: Option<u32>,
@@ -32,6 +33,7 @@ impl Point {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Range {
     pub start: Option<Point>,
     pub end: Option<Point>,
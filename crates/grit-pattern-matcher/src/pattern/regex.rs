@@ -16,12 +16,22 @@ use grit_util::{
 use regex::Regex;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct RegexPattern<Q: QueryContext> {
     pub regex: RegexLike<Q>,
     pub variables: Vec<Variable>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum RegexLike<Q: QueryContext> {
     Regex(String),
     Pattern(Box<Pattern<Q>>),
@@ -15,6 +15,11 @@ use grit_util::{
 use std::borrow::Cow;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Rewrite<Q: QueryContext> {
     pub left: Pattern<Q>,
     pub right: DynamicPattern<Q>,
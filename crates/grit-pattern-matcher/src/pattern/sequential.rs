@@ -8,6 +8,11 @@ use grit_util::{error::GritResult, AnalysisLogs};
 use std::ops;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Sequential<Q: QueryContext>(pub Vec<Step<Q>>);
 
 impl<Q: QueryContext> Matcher<Q> for Sequential<Q> {
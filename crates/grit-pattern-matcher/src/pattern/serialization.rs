@@ -0,0 +1,16 @@
+/// Bound for the language-specific pattern types stored in a [`Pattern`](super::Pattern).
+///
+/// With the `serde` feature enabled, compiled patterns can be serialized, so
+/// the language-specific patterns have to be serializable too. Without the
+/// feature this bound is satisfied by every type.
+#[cfg(feature = "serde")]
+pub trait SerializablePattern: serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> SerializablePattern for T {}
+
+#[cfg(not(feature = "serde"))]
+pub trait SerializablePattern {}
+
+#[cfg(not(feature = "serde"))]
+impl<T> SerializablePattern for T {}
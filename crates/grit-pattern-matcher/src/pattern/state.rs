@@ -6,7 +6,7 @@ use super::{
 use crate::{
     binding::Binding,
     constants::MATCH_VAR,
-    context::QueryContext,
+    context::{ExecContext, QueryContext},
     effects::Effect,
     file_owners::FileOwner,
     intervals::{earliest_deadline_sort, get_top_level_intervals_in_range, Interval},
@@ -423,9 +423,9 @@ impl<'a, Q: QueryContext> State<'a, Q> {
 
     pub fn bindings_history_to_ranges(
         &self,
-        language: &Q::Language<'a>,
-        current_name: Option<&str>,
+        context: &Q::ExecContext<'a>,
     ) -> (Vec<VariableMatch>, Vec<Range>, bool) {
+        let language = context.language();
         let mut matches = vec![];
         let mut top_level_matches = vec![];
         let mut suppressed = false;
@@ -439,7 +439,7 @@ impl<'a, Q: QueryContext> State<'a, Q> {
                     if let Some(bindings) = value.get_bindings() {
                         for binding in bindings {
                             bindings_count += 1;
-                            if binding.is_suppressed(language, current_name) {
+                            if binding.is_suppressed(context) {
                                 suppressed_count += 1;
                                 continue;
                             }
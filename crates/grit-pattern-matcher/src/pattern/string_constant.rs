@@ -8,6 +8,7 @@ use core::fmt::Debug;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StringConstant {
     pub text: String,
 }
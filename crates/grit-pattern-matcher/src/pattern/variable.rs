@@ -25,6 +25,7 @@ use std::{
 };
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct VariableScope {
     pub(crate) scope: u16,
     pub(crate) index: u16,
@@ -48,6 +49,7 @@ struct DynamicVariableInternal {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum VariableInternal {
     /// Static variable, which is bound at compile time (ex. global variables).
     /// These are slightly more efficient, and follow the traditional approach in Grit.
@@ -56,10 +58,12 @@ enum VariableInternal {
     /// Dynamic variables are lazy, so we just need to register them by name.
     /// They will then automatically be bound to the first scope that attempts to use them.
     /// This should be avoided where possible, since it means names will likely overwrite each other across scopes.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Dynamic(DynamicVariableInternal),
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variable {
     internal: VariableInternal,
 }
@@ -67,6 +71,7 @@ pub struct Variable {
 /// VariableSource is used to track the origin of a variable
 /// It can come from
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VariableSource {
     /// Compiled from a pattern
     Compiled {
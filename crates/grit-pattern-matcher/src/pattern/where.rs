@@ -9,6 +9,11 @@ use core::fmt::Debug;
 use grit_util::{error::GritResult, AnalysisLogs};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Where<Q: QueryContext> {
     pub pattern: Pattern<Q>,
     pub side_condition: Predicate<Q>,
@@ -8,6 +8,11 @@ use core::fmt::Debug;
 use grit_util::{error::GritResult, AnalysisLogs, AstNode};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Within<Q: QueryContext> {
     pub pattern: Pattern<Q>,
     until: Option<Pattern<Q>>,
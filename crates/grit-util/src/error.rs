@@ -33,6 +33,10 @@ pub enum GritPatternError {
 
     #[error("{0}")]
     Generic(String),
+
+    /// Matching a file took longer, or more steps, than its budget allows
+    #[error("{0}")]
+    BudgetExceeded(String),
 }
 
 impl GritPatternError {
@@ -8,7 +8,9 @@ pub mod error;
 mod language;
 mod parser;
 mod position;
+mod profiler;
 mod ranges;
+mod tracer;
 
 pub use analysis_logs::{AnalysisLog, AnalysisLogBuilder, AnalysisLogs};
 pub use ast_node::AstNode;
@@ -18,7 +20,9 @@ pub use effect_kind::EffectKind;
 pub use language::{GritMetaValue, Language, Replacement};
 pub use parser::{Ast, FileOrigin, Parser, SnippetTree};
 pub use position::Position;
+pub use profiler::{profile, ProfileEntry, ProfileKind, Profiler};
 pub use ranges::{
     ByteRange, EffectRange, FileRange, InputRanges, MatchRanges, Range, RangeWithoutByte,
     UtilRange, VariableBinding, VariableMatch,
 };
+pub use tracer::{format_trace, TraceNode, Tracer};
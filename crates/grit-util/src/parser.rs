@@ -1,5 +1,5 @@
 use crate::{AnalysisLogs, AstNode};
-use std::{borrow::Cow, marker::PhantomData, path::Path};
+use std::{borrow::Cow, path::Path};
 
 /// Information on where a file came from, for the parser to be smarter
 #[derive(Clone, Debug)]
@@ -13,15 +13,24 @@ where
     Mutated,
     /// A file that was constructed by Grit
     New,
-    /// We might need these
-    _Phantom(PhantomData<&'tree Tree>),
+    /// A file from outside Grit that we have parsed before, such as an open
+    /// editor buffer. The previous tree must already be edited to line up with
+    /// the new source, so the parser can reuse its unchanged nodes.
+    Edited(&'tree Tree),
 }
 
-#[allow(clippy::needless_lifetimes)]
 impl<'tree, Tree: Ast> FileOrigin<'tree, Tree> {
     /// Is this a file we are parsing for the first time, from outside Grit?
     pub fn is_fresh(&self) -> bool {
-        matches!(self, FileOrigin::Fresh)
+        matches!(self, FileOrigin::Fresh | FileOrigin::Edited(_))
+    }
+
+    /// The previous tree for the file, if the parser can reuse it
+    pub fn previous_tree(&self) -> Option<&'tree Tree> {
+        match self {
+            FileOrigin::Edited(tree) => Some(tree),
+            FileOrigin::Fresh | FileOrigin::Mutated | FileOrigin::New => None,
+        }
     }
 }
 
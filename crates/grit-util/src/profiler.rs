@@ -0,0 +1,140 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// What a profile entry was recorded for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileKind {
+    Pattern,
+    Predicate,
+    Function,
+    File,
+}
+
+impl ProfileKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileKind::Pattern => "pattern",
+            ProfileKind::Predicate => "predicate",
+            ProfileKind::Function => "function",
+            ProfileKind::File => "file",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileEntry {
+    pub kind: ProfileKind,
+    pub name: String,
+    pub calls: u64,
+    /// Time spent in the definition, including any definitions it calls
+    pub total_ms: f64,
+}
+
+/// Records time and invocation counts while a query runs.
+/// A profiler is shared between threads, so one profiler covers every file in a run.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    entries: Mutex<HashMap<(ProfileKind, String), (u64, Duration)>>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, kind: ProfileKind, name: &str, elapsed: Duration) {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (calls, total) = entries.entry((kind, name.to_string())).or_default();
+        *calls += 1;
+        *total += elapsed;
+    }
+
+    /// Run `f`, recording how long it took
+    pub fn time<T>(&self, kind: ProfileKind, name: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(kind, name, start.elapsed());
+        result
+    }
+
+    /// Every entry, slowest first
+    pub fn report(&self) -> Vec<ProfileEntry> {
+        let entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut report: Vec<(Duration, ProfileEntry)> = entries
+            .iter()
+            .map(|((kind, name), (calls, total))| {
+                (
+                    *total,
+                    ProfileEntry {
+                        kind: *kind,
+                        name: name.clone(),
+                        calls: *calls,
+                        total_ms: total.as_micros() as f64 / 1000.0,
+                    },
+                )
+            })
+            .collect();
+        report.sort_by(|(a_total, a), (b_total, b)| {
+            b_total
+                .cmp(a_total)
+                .then_with(|| (a.kind, &a.name).cmp(&(b.kind, &b.name)))
+        });
+        report.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// Run `f`, timing it if there is a profiler
+pub fn profile<T>(
+    profiler: Option<&Profiler>,
+    kind: ProfileKind,
+    name: &str,
+    f: impl FnOnce() -> T,
+) -> T {
+    match profiler {
+        Some(profiler) => profiler.time(kind, name, f),
+        None => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_entries_by_total_time() {
+        let profiler = Profiler::new();
+        profiler.record(ProfileKind::Pattern, "fast", Duration::from_millis(1));
+        profiler.record(ProfileKind::Predicate, "slow", Duration::from_millis(5));
+        profiler.record(ProfileKind::Pattern, "fast", Duration::from_millis(2));
+        profiler.record(ProfileKind::File, "a.js", Duration::from_millis(4));
+
+        let report = profiler.report();
+        let names: Vec<_> = report.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["slow", "a.js", "fast"]);
+        assert_eq!(report[2].calls, 2);
+        assert_eq!(report[2].total_ms, 3.0);
+    }
+
+    #[test]
+    fn profile_without_profiler_still_runs() {
+        assert_eq!(profile(None, ProfileKind::Function, "f", || 42), 42);
+        let profiler = Profiler::new();
+        assert_eq!(
+            profile(Some(&profiler), ProfileKind::Function, "f", || 42),
+            42
+        );
+        assert_eq!(profiler.report()[0].calls, 1);
+    }
+}
@@ -7,10 +7,10 @@ use std::{ops::Add, path::PathBuf};
 pub struct Range {
     pub start: Position,
     pub end: Position,
-    // TODO: automatically derive these from the start and end positions during deserialization
-    #[serde(skip_deserializing)]
+    // TODO: automatically derive these from the start and end positions when they are missing
+    #[serde(default)]
     pub start_byte: u32,
-    #[serde(skip_deserializing)]
+    #[serde(default)]
     pub end_byte: u32,
 }
 
@@ -0,0 +1,203 @@
+use crate::Range;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Steps recorded before the trace is cut off, to keep explaining a pathological pattern bounded
+const MAX_TRACE_STEPS: usize = 10_000;
+
+/// Longest node text to show in a formatted trace
+const MAX_TEXT_LENGTH: usize = 60;
+
+/// One step in the evaluation of a pattern
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceNode {
+    /// The kind of pattern or predicate, or the name of the definition that was called
+    pub label: String,
+    /// The source the step was matched against, if any
+    pub text: Option<String>,
+    pub range: Option<Range>,
+    pub matched: bool,
+    pub children: Vec<TraceNode>,
+}
+
+#[derive(Debug, Default)]
+struct TraceState {
+    stack: Vec<TraceNode>,
+    roots: Vec<TraceNode>,
+    steps: usize,
+    truncated: bool,
+    /// Steps entered after the trace was cut off, which are not on the stack
+    skipped: usize,
+}
+
+/// Records how a pattern was evaluated against the nodes that start on a given line
+#[derive(Debug)]
+pub struct Tracer {
+    line: u32,
+    state: Mutex<TraceState>,
+}
+
+impl Tracer {
+    pub fn new(line: u32) -> Self {
+        Self {
+            line,
+            state: Mutex::new(TraceState::default()),
+        }
+    }
+
+    /// The (1-based) line being explained
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Whether a step is being recorded, so any nested steps should be recorded too
+    pub fn is_tracing(&self) -> bool {
+        let state = self.lock();
+        !state.stack.is_empty() || state.skipped > 0
+    }
+
+    pub fn enter(&self, label: String, text: Option<String>, range: Option<Range>) {
+        let mut state = self.lock();
+        state.steps += 1;
+        if state.steps > MAX_TRACE_STEPS {
+            state.truncated = true;
+            state.skipped += 1;
+            return;
+        }
+        state.stack.push(TraceNode {
+            label,
+            text,
+            range,
+            matched: false,
+            children: vec![],
+        });
+    }
+
+    pub fn exit(&self, matched: bool) {
+        let mut state = self.lock();
+        if state.skipped > 0 {
+            state.skipped -= 1;
+            return;
+        }
+        let Some(mut node) = state.stack.pop() else {
+            return;
+        };
+        node.matched = matched;
+        match state.stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => state.roots.push(node),
+        }
+    }
+
+    /// The recorded steps, one root for every node that started on the line
+    pub fn trace(&self) -> Vec<TraceNode> {
+        self.lock().roots.clone()
+    }
+
+    /// Whether the trace was cut off because it grew too large
+    pub fn is_truncated(&self) -> bool {
+        self.lock().truncated
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TraceState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn shorten(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or_default().trim();
+    let mut short: String = first_line.chars().take(MAX_TEXT_LENGTH).collect();
+    if short.len() < text.trim().len() {
+        short.push('…');
+    }
+    short
+}
+
+fn format_node(node: &TraceNode, parent_text: Option<&str>, depth: usize, out: &mut String) {
+    let mark = if node.matched { "✓" } else { "✗" };
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(mark);
+    out.push(' ');
+    out.push_str(&node.label);
+    // Only show the text where it changes, since most steps run against the same node as their parent
+    let is_variable = node.label.starts_with('$');
+    if let Some(text) = node
+        .text
+        .as_deref()
+        .filter(|t| is_variable || Some(*t) != parent_text)
+    {
+        let separator = if is_variable { " = " } else { " on " };
+        out.push_str(separator);
+        out.push('`');
+        out.push_str(&shorten(text));
+        out.push('`');
+        if let Some(range) = &node.range {
+            out.push_str(&format!(" ({}:{})", range.start.line, range.start.column));
+        }
+    }
+    out.push('\n');
+    let text = node.text.as_deref().or(parent_text);
+    for child in &node.children {
+        format_node(child, text, depth + 1, out);
+    }
+}
+
+/// Format a trace as an indented tree, marking each step as matched (✓) or failed (✗)
+pub fn format_trace(trace: &[TraceNode]) -> String {
+    let mut out = String::new();
+    for node in trace {
+        format_node(node, None, 0, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    #[test]
+    fn records_nested_steps() {
+        let tracer = Tracer::new(1);
+        let range = Range::new(Position::new(1, 1), Position::new(1, 12), 0, 11);
+        tracer.enter(
+            "and".to_string(),
+            Some("console.log(foo)".to_string()),
+            Some(range),
+        );
+        assert!(tracer.is_tracing());
+        tracer.enter("$msg".to_string(), Some("foo".to_string()), None);
+        tracer.exit(true);
+        tracer.enter("\"bar\"".to_string(), Some("foo".to_string()), None);
+        tracer.exit(false);
+        tracer.exit(false);
+        assert!(!tracer.is_tracing());
+
+        assert_eq!(
+            format_trace(&tracer.trace()),
+            "✗ and on `console.log(foo)` (1:1)\n  ✓ $msg = `foo`\n  ✗ \"bar\" on `foo`\n"
+        );
+    }
+
+    #[test]
+    fn keeps_recorded_steps_when_truncated() {
+        let tracer = Tracer::new(1);
+        tracer.enter("and".to_string(), None, None);
+        for _ in 0..MAX_TRACE_STEPS {
+            tracer.enter("or".to_string(), None, None);
+            tracer.exit(true);
+        }
+        assert!(tracer.is_truncated());
+        assert!(tracer.is_tracing());
+        tracer.exit(true);
+        assert!(!tracer.is_tracing());
+
+        let trace = tracer.trace();
+        assert_eq!(trace.len(), 1);
+        assert!(trace[0].matched);
+        assert_eq!(trace[0].children.len(), MAX_TRACE_STEPS - 1);
+    }
+}
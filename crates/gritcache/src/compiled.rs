@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use marzano_util::hasher::hash;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const COMPILED_CACHE_NAME: &str = "compiled_patterns";
+const COMPILED_CACHE_VERSION: u8 = 2;
+
+/// An on-disk cache of serialized compiled patterns, with one file per pattern
+pub struct CompiledPatternCache {
+    dir: PathBuf,
+}
+
+impl CompiledPatternCache {
+    /// Open the compiled pattern cache inside `cache_dir`, clearing it if `refresh` is set
+    pub fn new(cache_dir: &Path, refresh: bool) -> Result<Self> {
+        let dir = cache_dir.join(COMPILED_CACHE_NAME);
+        if refresh && dir.exists() {
+            fs_err::remove_dir_all(&dir).context("Failed to clear compiled pattern cache")?;
+        }
+        fs_err::create_dir_all(&dir)
+            .context("Failed to create compiled pattern cache")
+            .context("Please run `grit init` or set GRIT_CACHE_DIR to cache compiled patterns")?;
+        Ok(Self { dir })
+    }
+
+    /// Hash a set of pattern libraries, so it only has to be done once per language
+    pub fn hash_libs(libs: &BTreeMap<String, String>) -> [u8; 32] {
+        let mut combined = String::new();
+        for (name, body) in libs {
+            combined.push_str(&format!(
+                "{}:{}:{}:{}\n",
+                name.len(),
+                name,
+                body.len(),
+                body
+            ));
+        }
+        hash(&combined)
+    }
+
+    /// The cache key for a pattern. Any change to the pattern, the libraries it was resolved
+    /// against (including module revisions), or the binary version produces a new key.
+    pub fn key(
+        binary_version: &str,
+        name: Option<&str>,
+        body: &str,
+        libs_hash: [u8; 32],
+    ) -> String {
+        let key = hash(&format!(
+            "{}\0{}\0{}\0{}",
+            binary_version,
+            name.unwrap_or_default(),
+            body,
+            libs_hash
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        ));
+        key.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.bin", key))
+    }
+
+    /// Get a serialized compiled pattern, if it is cached
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut bytes = fs_err::read(self.path(key)).ok()?;
+        if bytes.first() != Some(&COMPILED_CACHE_VERSION) {
+            return None;
+        }
+        bytes.remove(0);
+        Some(bytes)
+    }
+
+    /// Store a serialized compiled pattern
+    pub fn put(&self, key: &str, compiled: &[u8]) -> Result<()> {
+        let mut entry = Vec::with_capacity(compiled.len() + 1);
+        entry.push(COMPILED_CACHE_VERSION);
+        entry.extend_from_slice(compiled);
+        // Write to a temporary file first, so concurrent readers never see a partial entry
+        let path = self.path(key);
+        let tmp = path.with_extension(format!("bin.{}.tmp", std::process::id()));
+        fs_err::write(&tmp, entry)?;
+        fs_err::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn libs(body: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([("helper.grit".to_string(), body.to_string())])
+    }
+
+    #[test]
+    fn keys_change_with_inputs() {
+        let libs_hash = CompiledPatternCache::hash_libs(&libs("pattern helper() { `foo` }"));
+        let key = CompiledPatternCache::key("0.1.0", Some("p"), "p()", libs_hash);
+        assert_eq!(
+            key,
+            CompiledPatternCache::key("0.1.0", Some("p"), "p()", libs_hash)
+        );
+        assert_ne!(
+            key,
+            CompiledPatternCache::key("0.1.1", Some("p"), "p()", libs_hash)
+        );
+        assert_ne!(
+            key,
+            CompiledPatternCache::key("0.1.0", Some("p"), "p() ", libs_hash)
+        );
+        let other_libs = CompiledPatternCache::hash_libs(&libs("pattern helper() { `bar` }"));
+        assert_ne!(
+            key,
+            CompiledPatternCache::key("0.1.0", Some("p"), "p()", other_libs)
+        );
+    }
+
+    #[test]
+    fn stores_and_refreshes_entries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = CompiledPatternCache::new(dir.path(), false)?;
+        assert!(cache.get("missing").is_none());
+
+        cache.put("key", b"compiled")?;
+        let cache = CompiledPatternCache::new(dir.path(), false)?;
+        assert_eq!(cache.get("key"), Some(b"compiled".to_vec()));
+
+        let cache = CompiledPatternCache::new(dir.path(), true)?;
+        assert!(cache.get("key").is_none());
+        Ok(())
+    }
+}
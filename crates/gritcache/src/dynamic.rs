@@ -30,4 +30,23 @@ impl GritCache for DynamicCache {
             DynamicCache::Null(cache) => cache.put_no_matches(file_hash, pattern_hash),
         }
     }
+
+    fn get_matches(&self, file_hash: [u8; 32], pattern_hash: [u8; 32]) -> Option<&[u8]> {
+        match self {
+            DynamicCache::Threaded(cache) => cache.get_matches(file_hash, pattern_hash),
+            DynamicCache::Null(cache) => cache.get_matches(file_hash, pattern_hash),
+        }
+    }
+
+    fn put_matches(
+        &self,
+        file_hash: [u8; 32],
+        pattern_hash: [u8; 32],
+        results: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        match self {
+            DynamicCache::Threaded(cache) => cache.put_matches(file_hash, pattern_hash, results),
+            DynamicCache::Null(cache) => cache.put_matches(file_hash, pattern_hash, results),
+        }
+    }
 }
@@ -1,3 +1,4 @@
+pub mod compiled;
 pub mod dynamic;
 pub mod new_cache;
 pub mod paths;
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
 use marzano_util::cache::GritCache;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Sender};
 use std::thread;
 use std::thread::JoinHandle;
@@ -10,6 +10,16 @@ use std::thread::JoinHandle;
 /// A HashKey is a 64 byte array, which is the result of combining a file hash and a pattern hash.
 type HashKey = [u8; 64];
 
+/// Updates sent to the thread that owns the cache files
+enum CacheEntry {
+    /// The file-pattern pair has no matches
+    NoMatches(HashKey),
+    /// The serialized results for a file-pattern pair with matches
+    Matches(HashKey, Vec<u8>),
+    /// A cached result was used, so it should survive eviction
+    Used(HashKey),
+}
+
 /// ThreadCache leverages multi-threading to make caching safe and concurrent.
 /// - Reading can be done from any thread (safely and directly), without locks
 /// - Writing is done from a dedicated thread, and is sent to the cache via a channel
@@ -19,37 +29,77 @@ pub struct ThreadedCache {
     /// The value is a boolean, true if there are *no* matches, false if there might be matches.
     no_matches: HashMap<HashKey, bool>,
 
-    /// The channel to send new misses to the cache
-    sender: Sender<HashKey>,
+    /// Serialized results for file-pattern pairs that have matches, from previous runs
+    matches: HashMap<HashKey, Vec<u8>>,
+
+    /// The channel to send new entries to the cache
+    sender: Sender<CacheEntry>,
 }
 
 const MISMATCHES_CACHE_NAME: &str = "mismatches_cache";
 const MISMATCHES_CACHE_VERSION: u8 = 2;
 
+/// The matches cache is a version byte, followed by records of a key, a little-endian u32 length, and the results
+const MATCHES_CACHE_NAME: &str = "matches_cache";
+const MATCHES_CACHE_VERSION: u8 = 1;
+
+/// Results larger than this are not cached, since they would bloat the cache for little gain
+const MAX_MATCHES_ENTRY_SIZE: usize = 1024 * 1024;
+
+/// Once the matches cache grows past this size, entries that were not used in the last run are evicted
+const MAX_MATCHES_CACHE_SIZE: u64 = 64 * 1024 * 1024;
+
 impl ThreadedCache {
     /// Create a new ThreadedCache
     pub(crate) async fn new(dir: PathBuf, refresh: bool) -> Result<(Self, JoinHandle<()>)> {
+        Self::new_with_limit(dir, refresh, MAX_MATCHES_CACHE_SIZE).await
+    }
+
+    async fn new_with_limit(
+        dir: PathBuf,
+        refresh: bool,
+        max_matches_size: u64,
+    ) -> Result<(Self, JoinHandle<()>)> {
         let mismatches_path = dir.join(MISMATCHES_CACHE_NAME);
+        let matches_path = dir.join(MATCHES_CACHE_NAME);
 
-        let no_matches = if refresh {
-            Self::reset(&mismatches_path)?;
-            HashMap::new()
+        let (no_matches, matches) = if refresh {
+            Self::reset(&mismatches_path, MISMATCHES_CACHE_VERSION)?;
+            Self::reset(&matches_path, MATCHES_CACHE_VERSION)?;
+            (HashMap::new(), HashMap::new())
         } else {
-            Self::initialize(&mismatches_path)?
+            (
+                Self::initialize(&mismatches_path)?,
+                Self::initialize_matches(&matches_path)?,
+            )
         };
 
-        let (sender, receiver) = mpsc::channel::<HashKey>();
+        let (sender, receiver) = mpsc::channel::<CacheEntry>();
         let mut writer = Self::new_writer(&mismatches_path)?;
+        let mut matches_log = MatchesLog::new(matches_path, max_matches_size)?;
         let manager = thread::spawn(move || {
-            while let Ok(key) = receiver.recv() {
-                writer.write_all(&key).unwrap();
+            while let Ok(entry) = receiver.recv() {
+                match entry {
+                    CacheEntry::NoMatches(key) => writer.write_all(&key).unwrap(),
+                    CacheEntry::Matches(key, results) => matches_log.append(key, &results).unwrap(),
+                    CacheEntry::Used(key) => matches_log.mark_used(key),
+                }
             }
+            writer.flush().unwrap();
+            matches_log.finish().unwrap();
         });
 
-        Ok((Self { no_matches, sender }, manager))
+        Ok((
+            Self {
+                no_matches,
+                matches,
+                sender,
+            },
+            manager,
+        ))
     }
 
-    fn reset(path: &PathBuf) -> Result<()> {
+    fn reset(path: &PathBuf, version: u8) -> Result<()> {
         let mut writer = BufWriter::new(
             fs_err::OpenOptions::new()
                 .create(true)
@@ -58,7 +108,7 @@ impl ThreadedCache {
                 .open(path)
                 .context("Failed to truncate cache file".to_string())?,
         );
-        writer.write_all(&[MISMATCHES_CACHE_VERSION])?;
+        writer.write_all(&[version])?;
         Ok(())
     }
 
@@ -82,7 +132,7 @@ impl ThreadedCache {
         }
         let (version_byte, key_vector) = file_vector.split_at(1);
         if (version_byte[0]) != MISMATCHES_CACHE_VERSION {
-            Self::reset(path)?;
+            Self::reset(path, MISMATCHES_CACHE_VERSION)?;
             return Ok(HashMap::new());
         }
 
@@ -99,6 +149,29 @@ impl ThreadedCache {
         Ok(map)
     }
 
+    fn initialize_matches(path: &PathBuf) -> Result<HashMap<HashKey, Vec<u8>>> {
+        let file_vector = match fs_err::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    return Ok(HashMap::new());
+                } else {
+                    return Err(e).context(format!(
+                        "Failed to read cache file {}",
+                        path.to_string_lossy()
+                    ));
+                }
+            }
+        };
+        match read_matches(&file_vector) {
+            Some(matches) => Ok(matches),
+            None => {
+                Self::reset(path, MATCHES_CACHE_VERSION)?;
+                Ok(HashMap::new())
+            }
+        }
+    }
+
     fn new_writer(path: &PathBuf) -> Result<BufWriter<fs_err::File>> {
         let writer = BufWriter::new(
             fs_err::OpenOptions::new()
@@ -136,12 +209,153 @@ impl GritCache for ThreadedCache {
         let key = Self::key(file_hash, pattern_hash);
 
         // Send the key to the cache
-        self.sender.send(key)?;
+        self.sender.send(CacheEntry::NoMatches(key))?;
+
+        Ok(())
+    }
+
+    fn get_matches(&self, file_hash: [u8; 32], pattern_hash: [u8; 32]) -> Option<&[u8]> {
+        let key = Self::key(file_hash, pattern_hash);
+        let results = self.matches.get(&key)?;
+        // Nothing to do if the manager has gone away, the entry just becomes eligible for eviction
+        let _ = self.sender.send(CacheEntry::Used(key));
+        Some(results)
+    }
 
+    fn put_matches(
+        &self,
+        file_hash: [u8; 32],
+        pattern_hash: [u8; 32],
+        results: Vec<u8>,
+    ) -> Result<()> {
+        if results.len() > MAX_MATCHES_ENTRY_SIZE {
+            return Ok(());
+        }
+        let key = Self::key(file_hash, pattern_hash);
+        if self
+            .matches
+            .get(&key)
+            .is_some_and(|cached| *cached == results)
+        {
+            return Ok(());
+        }
+        self.sender.send(CacheEntry::Matches(key, results))?;
         Ok(())
     }
 }
 
+/// Parse the matches cache file, returning None if it has an unknown version.
+/// A truncated record at the end, such as from an interrupted write, is ignored.
+fn read_matches(bytes: &[u8]) -> Option<HashMap<HashKey, Vec<u8>>> {
+    let mut matches = HashMap::new();
+    let Some((version_byte, mut records)) = bytes.split_first() else {
+        return Some(matches);
+    };
+    if *version_byte != MATCHES_CACHE_VERSION {
+        return None;
+    }
+    while records.len() >= 68 {
+        let (key, rest) = records.split_at(64);
+        let (len, rest) = rest.split_at(4);
+        let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+        if rest.len() < len {
+            break;
+        }
+        let (results, rest) = rest.split_at(len);
+        // Later records replace earlier ones for the same key
+        matches.insert(key.try_into().ok()?, results.to_vec());
+        records = rest;
+    }
+    Some(matches)
+}
+
+fn write_match(writer: &mut impl Write, key: &HashKey, results: &[u8]) -> Result<()> {
+    writer.write_all(key)?;
+    writer.write_all(&(results.len() as u32).to_le_bytes())?;
+    writer.write_all(results)?;
+    Ok(())
+}
+
+/// The append-only log of matches, owned by the manager thread
+struct MatchesLog {
+    path: PathBuf,
+    writer: BufWriter<fs_err::File>,
+    /// The size of the log, including buffered writes
+    size: u64,
+    /// Entries that were used or written in this run, and survive eviction
+    live: HashSet<HashKey>,
+    max_size: u64,
+}
+
+impl MatchesLog {
+    fn new(path: PathBuf, max_size: u64) -> Result<Self> {
+        let mut writer = ThreadedCache::new_writer(&path)?;
+        let mut size = fs_err::metadata(&path)?.len();
+        if size == 0 {
+            writer.write_all(&[MATCHES_CACHE_VERSION])?;
+            size = 1;
+        }
+        Ok(Self {
+            path,
+            writer,
+            size,
+            live: HashSet::new(),
+            max_size,
+        })
+    }
+
+    fn append(&mut self, key: HashKey, results: &[u8]) -> Result<()> {
+        write_match(&mut self.writer, &key, results)?;
+        self.size += (key.len() + 4 + results.len()) as u64;
+        self.live.insert(key);
+        Ok(())
+    }
+
+    fn mark_used(&mut self, key: HashKey) {
+        self.live.insert(key);
+    }
+
+    /// Flush the log, and evict stale entries if it has grown too large
+    fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        if self.size <= self.max_size {
+            return Ok(());
+        }
+        let matches = read_matches(&fs_err::read(&self.path)?).unwrap_or_default();
+        compact_matches(&self.path, matches, &self.live, self.max_size)
+    }
+}
+
+/// Rewrite the matches cache with only the live entries, as long as they fit within `max_size`
+fn compact_matches(
+    path: &Path,
+    matches: HashMap<HashKey, Vec<u8>>,
+    live: &HashSet<HashKey>,
+    max_size: u64,
+) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut writer = BufWriter::new(fs_err::File::create(&tmp)?);
+    writer.write_all(&[MATCHES_CACHE_VERSION])?;
+    let mut size = 1;
+    let mut kept: Vec<_> = matches
+        .iter()
+        .filter(|(key, _)| live.contains(*key))
+        .collect();
+    kept.sort_by_key(|(key, _)| *key);
+    for (key, results) in kept {
+        let record_size = (key.len() + 4 + results.len()) as u64;
+        if size + record_size > max_size {
+            continue;
+        }
+        write_match(&mut writer, key, results)?;
+        size += record_size;
+    }
+    writer.flush()?;
+    drop(writer);
+    fs_err::rename(&tmp, path)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use marzano_util::hasher::hash;
@@ -265,4 +479,73 @@ mod tests {
         fs_err::remove_file(mismatches_cache_path.clone())?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_matches_cache() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().to_path_buf();
+        let file1_hash = hash("&file1");
+        let file2_hash = hash("&file2");
+        let pattern_hash = hash("&pattern");
+
+        let (cache, manager) = ThreadedCache::new(path.clone(), false).await?;
+        assert!(cache.get_matches(file1_hash, pattern_hash).is_none());
+        cache.put_matches(file1_hash, pattern_hash, b"[1]".to_vec())?;
+        cache.put_matches(
+            file2_hash,
+            pattern_hash,
+            vec![0; MAX_MATCHES_ENTRY_SIZE + 1],
+        )?;
+        drop(cache);
+        manager.join().unwrap();
+
+        // Results are replayed on the next run, but oversized ones are never stored
+        let (cache, manager) = ThreadedCache::new(path.clone(), false).await?;
+        assert_eq!(
+            cache.get_matches(file1_hash, pattern_hash),
+            Some(b"[1]".as_slice())
+        );
+        assert!(cache.get_matches(file2_hash, pattern_hash).is_none());
+        drop(cache);
+        manager.join().unwrap();
+
+        // An unknown format version discards the cache
+        fs_err::write(path.join(MATCHES_CACHE_NAME), [MATCHES_CACHE_VERSION + 1])?;
+        let (cache, manager) = ThreadedCache::new(path.clone(), false).await?;
+        assert!(cache.get_matches(file1_hash, pattern_hash).is_none());
+        drop(cache);
+        manager.join().unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_matches_cache_evicts_stale_entries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().to_path_buf();
+        let pattern_hash = hash("&pattern");
+        let old_file = hash("&old");
+        let new_file = hash("&new");
+        let results = vec![1; 100];
+
+        let (cache, manager) = ThreadedCache::new_with_limit(path.clone(), false, 250).await?;
+        cache.put_matches(old_file, pattern_hash, results.clone())?;
+        drop(cache);
+        manager.join().unwrap();
+
+        // The second entry pushes the cache over its limit, and the first one was not used
+        let (cache, manager) = ThreadedCache::new_with_limit(path.clone(), false, 250).await?;
+        cache.put_matches(new_file, pattern_hash, results.clone())?;
+        drop(cache);
+        manager.join().unwrap();
+
+        let (cache, manager) = ThreadedCache::new_with_limit(path.clone(), false, 250).await?;
+        assert!(cache.get_matches(old_file, pattern_hash).is_none());
+        assert_eq!(
+            cache.get_matches(new_file, pattern_hash),
+            Some(results.as_slice())
+        );
+        drop(cache);
+        manager.join().unwrap();
+        Ok(())
+    }
 }
@@ -4,7 +4,7 @@ use anyhow::Result;
 use marzano_gritmodule::searcher::{find_global_grit_modules_dir, find_grit_modules_dir};
 use marzano_util::cache::NullCache;
 
-use crate::{dynamic::DynamicCache, new_cache::ThreadedCache};
+use crate::{compiled::CompiledPatternCache, dynamic::DynamicCache, new_cache::ThreadedCache};
 
 pub async fn cache_dir(current_path: PathBuf) -> Result<PathBuf> {
     // If GRIT_CACHE_DIR env var is set, use that
@@ -36,3 +36,16 @@ pub async fn cache_for_cwd(
         Ok((DynamicCache::Threaded(cache), Some(manager)))
     }
 }
+
+/// Open the compiled pattern cache for the current working directory
+/// Returns None if caching is disabled
+pub async fn compiled_cache_for_cwd(
+    refresh: bool,
+    null_cache: bool,
+) -> Result<Option<CompiledPatternCache>> {
+    if null_cache {
+        return Ok(None);
+    }
+    let cache_dir = cache_dir(std::env::current_dir()?).await?;
+    Ok(Some(CompiledPatternCache::new(&cache_dir, refresh)?))
+}
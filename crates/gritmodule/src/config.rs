@@ -6,6 +6,7 @@ use marzano_language::{grit_parser::MarzanoGritParser, target_language::PatternL
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    collections::BTreeMap,
     path::{Path, PathBuf},
     vec::Vec,
 };
@@ -14,11 +15,11 @@ use tokio::{fs, io::AsyncWriteExt};
 use tracing::instrument;
 
 use crate::searcher::{
-    find_git_dir_from, find_global_grit_dir, find_global_grit_modules_dir, find_grit_dir_from,
+    find_git_dir_from, find_global_grit_dir, find_global_grit_modules_dir, find_root_grit_dir_from,
 };
 use crate::{fetcher::GritModuleFetcher, markdown::GritDefinitionOverrides};
 use crate::{
-    fetcher::{FetcherType, ModuleRepo},
+    fetcher::{FetcherType, ModuleRepo, LOCAL_MODULE_PREFIX},
     parser::PatternFileExt,
     utils::is_pattern_name,
 };
@@ -32,6 +33,13 @@ pub struct GritGitHubConfig {
     pub reviewers: Vec<String>,
 }
 
+/// Limits on the work spent matching a single file, used unless overridden on the command line
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct GritLimitsConfig {
+    pub max_file_millis: Option<u64>,
+    pub max_file_steps: Option<u64>,
+}
+
 /// Represents a reference to an external pattern file
 #[derive(Debug, Deserialize)]
 pub struct GritPatternFile {
@@ -40,12 +48,22 @@ pub struct GritPatternFile {
     pub overrides: GritDefinitionOverrides,
 }
 
+/// Changes the enforcement level of patterns for files matching any of the globs.
+/// Globs are relative to the directory containing the `.grit` directory.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GritLevelOverride {
+    pub files: Vec<String>,
+    pub levels: BTreeMap<String, EnforcementLevel>,
+}
+
 /// Pure in-memory representation of the grit config
 #[derive(Debug)]
 pub struct GritConfig {
     pub patterns: Vec<GritDefinitionConfig>,
     pub pattern_files: Option<Vec<GritPatternFile>>,
     pub github: Option<GritGitHubConfig>,
+    pub overrides: Vec<GritLevelOverride>,
+    pub limits: GritLimitsConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,8 +76,13 @@ pub enum GritPatternConfig {
 /// Compacted / serialized version of the GritConfig
 #[derive(Debug, Deserialize)]
 pub struct SerializedGritConfig {
+    #[serde(default)]
     pub patterns: Vec<GritPatternConfig>,
     pub github: Option<GritGitHubConfig>,
+    #[serde(default)]
+    pub overrides: Vec<GritLevelOverride>,
+    #[serde(default)]
+    pub limits: GritLimitsConfig,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
@@ -103,6 +126,12 @@ pub struct GritDefinitionConfig {
     pub path: String,
     pub range: Option<Range>,
     pub raw: Option<RawGritDefinition>,
+    /// The pattern this entry instantiates, when it is reported under a different name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Arguments passed to the pattern when it is enforced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<BTreeMap<String, GritPatternArg>>,
 }
 
 impl GritDefinitionConfig {
@@ -116,6 +145,43 @@ impl GritDefinitionConfig {
             path,
             range: None,
             raw: None,
+            pattern: serialized.pattern,
+            args: serialized.args,
+        }
+    }
+
+    /// Whether this entry calls another pattern with arguments, instead of defining or enabling one
+    pub fn is_instance(&self) -> bool {
+        self.body.is_none() && (self.pattern.is_some() || self.args.is_some())
+    }
+}
+
+/// An argument for a parameterised pattern, converted to a GritQL literal
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum GritPatternArg {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    /// A code snippet, like `` `lodash` ``
+    Snippet {
+        snippet: String,
+    },
+}
+
+impl fmt::Display for GritPatternArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GritPatternArg::Bool(b) => write!(f, "{}", b),
+            GritPatternArg::Int(i) => write!(f, "{}", i),
+            GritPatternArg::Float(n) => write!(f, "{}", n),
+            GritPatternArg::String(s) => {
+                write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            GritPatternArg::Snippet { snippet } => {
+                write!(f, "`{}`", snippet.replace('\\', "\\\\").replace('`', "\\`"))
+            }
         }
     }
 }
@@ -132,6 +198,9 @@ pub struct GritSerializedDefinitionConfig {
     #[serde(skip)]
     pub kind: Option<DefinitionKind>,
     pub samples: Option<Vec<GritPatternSample>>,
+    /// The pattern to call with `args`, so it can be enforced several times under different names
+    pub pattern: Option<String>,
+    pub args: Option<BTreeMap<String, GritPatternArg>>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -234,12 +303,8 @@ impl ResolvedGritDefinition {
                 if module == local_repo {
                     local_path.join(&self.config.path)
                 } else {
-                    let gritmodule_path = format!(
-                        "{}/{}/{}",
-                        REPO_CONFIG_DIR_NAME, GRIT_MODULE_DIR, module.provider_name
-                    );
-                    let gritmodule_path = PathBuf::from(gritmodule_path);
-                    local_path.join(gritmodule_path).join(&self.config.path)
+                    module_dir(&local_path.join(REPO_CONFIG_DIR_NAME), module)
+                        .join(&self.config.path)
                 }
             }
             DefinitionSource::Config(config) => config.path.clone(),
@@ -252,6 +317,20 @@ impl ResolvedGritDefinition {
         &self.local_name
     }
 
+    // The GritQL call that runs the pattern, including any arguments from grit.yaml
+    pub fn call(&self) -> String {
+        let name = self.config.pattern.as_deref().unwrap_or(&self.local_name);
+        let args = self
+            .config
+            .args
+            .iter()
+            .flatten()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}({})", name, args)
+    }
+
     // Retrieve the level from the config, or fall back to a default
     pub fn level(&self) -> EnforcementLevel {
         self.config
@@ -302,10 +381,15 @@ pub fn pattern_config_to_model(
     pattern: GritDefinitionConfig,
     source: Option<&ModuleRepo>,
 ) -> Result<ModuleGritPattern> {
-    let mut split_name = pattern.name.split('#');
+    // Instances are reported under their own name, but resolved from the pattern they call
+    let reference = pattern.pattern.as_deref().unwrap_or(&pattern.name);
+    let mut split_name = reference.split('#');
     let repo = split_name.next();
     let defined_local_name = split_name.next();
-    let local_name = defined_local_name.unwrap_or(&pattern.name).to_string();
+    let local_name = match pattern.pattern {
+        Some(_) => pattern.name.clone(),
+        None => defined_local_name.unwrap_or(&pattern.name).to_string(),
+    };
 
     if !is_pattern_name(&local_name) && local_name != NAMESPACE_IMPORT_INDICATOR {
         bail!("Invalid pattern name: {}. Grit patterns must match the regex /[\\^#A-Za-z_][A-Za-z0-9_]*/. For more info, consult the docs at https://docs.grit.io/guides/patterns#pattern-definitions.", local_name);
@@ -313,6 +397,9 @@ pub fn pattern_config_to_model(
 
     let module: Option<ModuleRepo> = match repo {
         None => None,
+        Some(repo) if defined_local_name.is_some() && repo.starts_with(LOCAL_MODULE_PREFIX) => {
+            Some(ModuleRepo::from_repo_str(repo)?)
+        }
         Some(_) => {
             let mut split_repo = repo.unwrap().split('/');
             let host = split_repo.next();
@@ -358,8 +445,19 @@ pub const REPO_CONFIG_DIR_NAME: &str = ".grit";
 pub const CONFIG_FILE_NAMES: [&str; 2] = ["grit.yml", "grit.yaml"];
 pub const REPO_CONFIG_PATTERNS_DIR: &str = "patterns";
 pub const GRIT_MODULE_DIR: &str = ".gritmodules";
+pub const VENDOR_DIR: &str = "vendor";
 pub const NAMESPACE_IMPORT_INDICATOR: &str = "*";
 
+/// Where a module's patterns are read from: its vendored copy if there is one, otherwise its fetched copy
+pub fn module_dir(grit_dir: &Path, module: &ModuleRepo) -> PathBuf {
+    let vendored = grit_dir.join(VENDOR_DIR).join(&module.provider_name);
+    if vendored.exists() {
+        vendored
+    } else {
+        grit_dir.join(GRIT_MODULE_DIR).join(&module.provider_name)
+    }
+}
+
 pub fn is_namespace_import(pattern: &ModuleGritPattern) -> bool {
     pattern.local_name == NAMESPACE_IMPORT_INDICATOR
 }
@@ -401,7 +499,7 @@ pub async fn init_config_from_path<T: FetcherType>(
     cwd: PathBuf,
     create_local: bool,
 ) -> Result<ConfigSource> {
-    let existing_config = find_grit_dir_from(cwd.clone()).await;
+    let existing_config = find_root_grit_dir_from(cwd.clone()).await;
     let config_path = match existing_config {
         Some(config) => config,
         None => {
@@ -1,14 +1,24 @@
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-use anyhow::{anyhow, bail, Result};
-use git2::{build::RepoBuilder, FetchOptions, Repository};
+use anyhow::{anyhow, bail, Context, Result};
+use git2::{
+    build::{CheckoutBuilder, RepoBuilder},
+    FetchOptions, Repository,
+};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use fs_err;
 use lazy_static::lazy_static;
+use log::debug;
 
-use crate::{config::GRIT_MODULE_DIR, searcher::find_git_dir_from, utils::remove_dir_all_safe};
+use crate::{
+    config::{GRIT_MODULE_DIR, VENDOR_DIR},
+    lockfile::ModuleLock,
+    searcher::find_git_dir_from,
+    utils::remove_dir_all_safe,
+    vendor::fetch_local_module,
+};
 
 lazy_static! {
     static ref GIT_REMOTE_REGEX: Regex =
@@ -171,6 +181,11 @@ impl LocalRepo {
     }
 }
 
+/// Prefix for modules read from a local directory or `.tar.gz` archive, like `file:../shared-patterns`
+pub const LOCAL_MODULE_PREFIX: &str = "file:";
+
+const LOCAL_MODULE_HOST: &str = "file";
+
 /// Represents a repository containing .grit patterns, used in our packaging system
 #[derive(Eq, Serialize, Deserialize, Default, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -179,6 +194,9 @@ pub struct ModuleRepo {
     pub full_name: String,
     pub remote: String,
     pub provider_name: String,
+    /// The tag, branch or commit to check out, from `host/repo@revision`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
 }
 
 impl PartialEq for ModuleRepo {
@@ -189,6 +207,15 @@ impl PartialEq for ModuleRepo {
 
 impl ModuleRepo {
     pub fn from_host_repo(host: &str, repo: &str) -> Result<Self> {
+        let (repo, revision) = match repo.rsplit_once('@') {
+            Some((repo, revision)) => {
+                if revision.is_empty() {
+                    bail!("Invalid format. Missing revision after @ in {}", repo);
+                }
+                (repo, Some(revision.to_string()))
+            }
+            None => (repo, None),
+        };
         let remote = format!("https://{}/{}.git", host, repo);
         let provider_name = format!("{}/{}", host, repo);
         Ok(Self {
@@ -196,10 +223,42 @@ impl ModuleRepo {
             full_name: repo.to_string(),
             remote,
             provider_name,
+            revision,
         })
     }
 
+    /// A module read from a local path, relative to the repo that references it
+    pub fn from_local_path(path: &str) -> Self {
+        // The name is used as a directory below .gritmodules, so it must not climb out of it
+        let full_name = Path::new(path)
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                Component::ParentDir => Some("__".to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        Self {
+            host: LOCAL_MODULE_HOST.to_string(),
+            provider_name: format!("{}/{}", LOCAL_MODULE_HOST, full_name),
+            full_name,
+            remote: path.to_string(),
+            revision: None,
+        }
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.host == LOCAL_MODULE_HOST
+    }
+
     pub fn from_repo_str(repo: &str) -> Result<Self> {
+        if let Some(path) = repo.strip_prefix(LOCAL_MODULE_PREFIX) {
+            if path.is_empty() {
+                bail!("Invalid format. Missing path after {}", LOCAL_MODULE_PREFIX);
+            }
+            return Ok(Self::from_local_path(path));
+        }
         let slash_pos = repo
             .find('/')
             .ok_or_else(|| anyhow!("Invalid format. Missing slash in repo string"))?;
@@ -222,6 +281,7 @@ impl ModuleRepo {
             full_name: repo,
             provider_name,
             remote: remote.to_string(),
+            revision: None,
         })
     }
 
@@ -248,6 +308,32 @@ enum CloneExistingStrategy {
     Clean,
 }
 
+/// Whether a cloned module is already checked out at the requested revision
+fn is_at_revision(target_dir: &Path, revision: Option<&str>) -> bool {
+    let Some(revision) = revision else {
+        return true;
+    };
+    let Ok(repo) = Repository::open(target_dir) else {
+        return false;
+    };
+    let wanted = repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit());
+    let head = repo.head().and_then(|head| head.peel_to_commit());
+    matches!((wanted, head), (Ok(wanted), Ok(head)) if wanted.id() == head.id())
+}
+
+fn checkout_revision(repo: &Repository, revision: &str) -> Result<()> {
+    // Branches only exist as remote branches in a fresh clone
+    let object = repo
+        .revparse_single(revision)
+        .or_else(|_| repo.revparse_single(&format!("origin/{}", revision)))?;
+    let commit = object.peel_to_commit()?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+    repo.set_head_detached(commit.id())?;
+    Ok(())
+}
+
 fn clone_repo<'a>(
     repo: &ModuleRepo,
     token: &Option<String>,
@@ -256,7 +342,12 @@ fn clone_repo<'a>(
 ) -> Result<&'a PathBuf> {
     if target_dir.exists() {
         match strategy {
-            CloneExistingStrategy::Preserve => return Ok(target_dir),
+            CloneExistingStrategy::Preserve => {
+                if is_at_revision(target_dir, repo.revision.as_deref()) {
+                    return Ok(target_dir);
+                }
+                remove_dir_all_safe(target_dir)?;
+            }
             CloneExistingStrategy::Clean => {
                 remove_dir_all_safe(target_dir)?;
             }
@@ -273,18 +364,76 @@ fn clone_repo<'a>(
     options.depth(1);
     cloner.fetch_options(options);
 
-    match cloner.clone(&remote, target_dir) {
-        Ok(_) => {}
+    let cloned = match cloner.clone(&remote, target_dir) {
+        Ok(cloned) => Some(cloned),
         Err(e) => {
             if !target_dir.exists() {
                 bail!("Failed to clone repo {}: {}", repo.full_name, e.to_string())
             }
+            None
         }
     };
 
+    if let (Some(cloned), Some(revision)) = (cloned, &repo.revision) {
+        if let Err(e) = checkout_shallow_revision(&cloned, revision) {
+            // Some servers refuse to fetch a commit that is not the tip of a branch
+            debug!(
+                "Failed to fetch {} at revision {} shallowly, cloning its full history: {}",
+                repo.full_name, revision, e
+            );
+            drop(cloned);
+            remove_dir_all_safe(target_dir)?;
+            let cloned = RepoBuilder::new()
+                .clone(&remote, target_dir)
+                .with_context(|| format!("Failed to clone repo {}", repo.full_name))?;
+            checkout_revision(&cloned, revision).with_context(|| {
+                format!(
+                    "Failed to check out {} at revision {}",
+                    repo.full_name, revision
+                )
+            })?;
+        }
+    }
+
     Ok(target_dir)
 }
 
+/// Check out a revision in a shallow clone, fetching just that commit if it is not a branch tip
+fn checkout_shallow_revision(repo: &Repository, revision: &str) -> Result<()> {
+    if checkout_revision(repo, revision).is_ok() {
+        return Ok(());
+    }
+    let mut options = FetchOptions::new();
+    options.depth(1);
+    repo.find_remote("origin")?
+        .fetch(&[revision], Some(&mut options), None)?;
+    checkout_revision(repo, "FETCH_HEAD")
+}
+
+/// The .grit directory that a modules directory belongs to, if it is a `.gritmodules` directory
+pub(crate) fn grit_dir_for_clone_dir(clone_dir: &Path) -> Option<PathBuf> {
+    if clone_dir.file_name()? != GRIT_MODULE_DIR {
+        return None;
+    }
+    clone_dir.parent().map(Path::to_path_buf)
+}
+
+/// Fetch a module without going to a git remote: from its vendored copy, or from a local path
+fn fetch_offline_module(clone_dir: &Path, repo: &ModuleRepo) -> Result<Option<String>> {
+    if let Some(grit_dir) = grit_dir_for_clone_dir(clone_dir) {
+        let vendored = grit_dir.join(VENDOR_DIR).join(&repo.provider_name);
+        if vendored.exists() {
+            return Ok(Some(vendored.to_string_lossy().to_string()));
+        }
+    }
+    if !repo.is_local() {
+        return Ok(None);
+    }
+    let target_dir = clone_dir.join(&repo.provider_name);
+    fetch_local_module(Path::new(&repo.remote), &target_dir)?;
+    Ok(Some(target_dir.to_string_lossy().to_string()))
+}
+
 pub trait GritModuleFetcher: Send + Sync {
     fn clone_dir(&self) -> &PathBuf;
     fn fetch_grit_module(&self, repo: &ModuleRepo) -> Result<String>;
@@ -327,11 +476,17 @@ impl FetcherType for CleanFetcherKind {
 pub struct CleanFetcher {
     clone_dir: PathBuf,
     token: Option<String>,
+    lock: ModuleLock,
 }
 
 impl CleanFetcher {
     pub fn new(clone_dir: PathBuf, token: Option<String>) -> Self {
-        Self { clone_dir, token }
+        let lock = ModuleLock::for_clone_dir(&clone_dir);
+        Self {
+            clone_dir,
+            token,
+            lock,
+        }
     }
 
     fn clone_repo<'a>(&self, repo: &ModuleRepo, target_dir: &'a PathBuf) -> Result<&'a PathBuf> {
@@ -349,8 +504,12 @@ impl GritModuleFetcher for CleanFetcher {
     }
 
     fn fetch_grit_module(&self, repo: &ModuleRepo) -> Result<String> {
+        if let Some(module_dir) = fetch_offline_module(&self.clone_dir, repo)? {
+            return Ok(module_dir);
+        }
         let target_dir = self.get_grit_module_dir(repo);
-        self.clone_repo(repo, &target_dir)?;
+        self.clone_repo(&self.lock.pin(repo), &target_dir)?;
+        self.lock.record(repo, &target_dir)?;
         Ok(target_dir.to_str().unwrap().to_string())
     }
 
@@ -369,11 +528,17 @@ impl GritModuleFetcher for CleanFetcher {
 pub struct KeepFetcher {
     clone_dir: PathBuf,
     token: Option<String>,
+    lock: ModuleLock,
 }
 
 impl KeepFetcher {
     pub fn new(clone_dir: PathBuf, token: Option<String>) -> Self {
-        Self { clone_dir, token }
+        let lock = ModuleLock::for_clone_dir(&clone_dir);
+        Self {
+            clone_dir,
+            token,
+            lock,
+        }
     }
 
     fn clone_repo<'a>(&self, repo: &ModuleRepo, target_dir: &'a PathBuf) -> Result<&'a PathBuf> {
@@ -396,8 +561,12 @@ impl GritModuleFetcher for KeepFetcher {
     }
 
     fn fetch_grit_module(&self, repo: &ModuleRepo) -> Result<String> {
+        if let Some(module_dir) = fetch_offline_module(&self.clone_dir, repo)? {
+            return Ok(module_dir);
+        }
         let target_dir = self.get_grit_module_dir(repo);
-        self.clone_repo(repo, &target_dir)?;
+        self.clone_repo(&self.lock.pin(repo), &target_dir)?;
+        self.lock.record(repo, &target_dir)?;
         Ok(target_dir.to_str().unwrap().to_string())
     }
 
@@ -438,6 +607,7 @@ mod tests {
             full_name: "getgrit/stdlib".to_string(),
             remote: "https://github.com/getgrit/stdlib.git".to_string(),
             provider_name: "github.com/getgrit/stdlib".to_string(),
+            revision: None,
         };
         let gritmodule_dir = fetcher.fetch_grit_module(&repo).unwrap();
         assert_eq!(
@@ -458,6 +628,7 @@ mod tests {
             full_name: "getgrit/stdlib".to_string(),
             remote: "http://github.com/getgrit/stdlib.git".to_string(),
             provider_name: "github.com/getgrit/stdlib".to_string(),
+            revision: None,
         };
         let gritmodule_dir = fetcher.fetch_grit_module(&repo).unwrap();
         let module_dir = dir.path().join("github.com/getgrit/stdlib");
@@ -483,6 +654,7 @@ mod tests {
             full_name: "getgrit/rewriter".to_string(),
             remote: remote.to_string(),
             provider_name: "github.com/getgrit/rewriter".to_string(),
+            revision: None,
         };
 
         assert_eq!(repo, expected_repo);
@@ -498,6 +670,7 @@ mod tests {
             full_name: "getgrit/testrepo".to_string(),
             remote: remote.to_string(),
             provider_name: "github.com/getgrit/testrepo".to_string(),
+            revision: None,
         };
 
         assert_eq!(repo, expected_repo);
@@ -514,6 +687,7 @@ mod tests {
             full_name: "some-org/some-repo".to_string(),
             remote: remote.to_string(),
             provider_name: "github.com/some-org/some-repo".to_string(),
+            revision: None,
         };
 
         assert_eq!(repo, expected_repo);
@@ -529,6 +703,7 @@ mod tests {
             full_name: "gritlab/private_thing".to_string(),
             remote: remote.to_string(),
             provider_name: "10.10.0.10/gritlab/private_thing".to_string(),
+            revision: None,
         };
 
         assert_eq!(repo, expected_repo);
@@ -545,11 +720,100 @@ mod tests {
             remote: remote.to_string(),
             provider_name: "internal.gitlab.url.com/group-name/w/subgroup-name/project-name"
                 .to_string(),
+            revision: None,
         };
 
         assert_eq!(repo, expected_repo);
     }
 
+    #[test]
+    fn fetches_locked_revision() {
+        // A local repo with two commits stands in for the remote
+        let origin = tempdir().unwrap();
+        let origin_repo = Repository::init(origin.path()).unwrap();
+        let signature = git2::Signature::now("grit", "grit@example.com").unwrap();
+        let mut commits = vec![];
+        for content in ["first", "second"] {
+            fs_err::write(origin.path().join("pattern.md"), content).unwrap();
+            let mut index = origin_repo.index().unwrap();
+            index.add_path(Path::new("pattern.md")).unwrap();
+            let tree = origin_repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = commits
+                .last()
+                .map(|id| origin_repo.find_commit(*id).unwrap());
+            let parents: Vec<_> = parent.iter().collect();
+            let id = origin_repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    content,
+                    &tree,
+                    &parents,
+                )
+                .unwrap();
+            commits.push(id);
+        }
+        let tag_at = |id| {
+            let commit = origin_repo.find_commit(id).unwrap();
+            origin_repo
+                .tag_lightweight("v1", commit.as_object(), true)
+                .unwrap();
+        };
+        tag_at(commits[0]);
+
+        let dir = tempdir().unwrap();
+        let grit_dir = dir.path().join(".grit");
+        let repo = ModuleRepo {
+            host: "example.com".to_string(),
+            full_name: "getgrit/local".to_string(),
+            remote: origin.path().to_string_lossy().to_string(),
+            provider_name: "example.com/getgrit/local".to_string(),
+            revision: Some("v1".to_string()),
+        };
+
+        let fetcher = CleanFetcher::new(grit_dir.join(GRIT_MODULE_DIR), None);
+        let module_dir = PathBuf::from(fetcher.fetch_grit_module(&repo).unwrap());
+        assert_eq!(
+            fs_err::read_to_string(module_dir.join("pattern.md")).unwrap(),
+            "first"
+        );
+        let lock = crate::lockfile::GritLock::read(&grit_dir).unwrap();
+        assert_eq!(
+            lock.modules[&repo.provider_name].commit,
+            commits[0].to_string()
+        );
+
+        // Moving the tag upstream doesn't change what is checked out, since the commit is locked
+        tag_at(commits[1]);
+        let fetcher = CleanFetcher::new(grit_dir.join(GRIT_MODULE_DIR), None);
+        fetcher.fetch_grit_module(&repo).unwrap();
+        assert_eq!(
+            fs_err::read_to_string(module_dir.join("pattern.md")).unwrap(),
+            "first"
+        );
+    }
+
+    #[test]
+    fn module_repo_from_local_path() {
+        let repo = ModuleRepo::from_repo_str("file:../shared-patterns").unwrap();
+
+        assert!(repo.is_local());
+        assert_eq!(repo.remote, "../shared-patterns");
+        assert_eq!(repo.provider_name, "file/__/shared-patterns");
+    }
+
+    #[test]
+    fn module_repo_with_revision() {
+        let repo = ModuleRepo::from_repo_str("github.com/getgrit/stdlib@v0.1.0").unwrap();
+
+        assert_eq!(repo.full_name, "getgrit/stdlib");
+        assert_eq!(repo.provider_name, "github.com/getgrit/stdlib");
+        assert_eq!(repo.remote, "https://github.com/getgrit/stdlib.git");
+        assert_eq!(repo.revision, Some("v0.1.0".to_string()));
+        assert!(ModuleRepo::from_repo_str("github.com/getgrit/stdlib@").is_err());
+    }
+
     #[tokio::test]
     async fn module_repo_from_dir() {
         let dir = tempdir().unwrap().into_path();
@@ -563,6 +827,7 @@ mod tests {
             full_name: "getgrit/stdlib".to_string(),
             remote: "https://github.com/getgrit/stdlib.git".to_string(),
             provider_name: "github.com/getgrit/stdlib".to_string(),
+            revision: None,
         };
 
         assert_eq!(module_repo, expected_repo);
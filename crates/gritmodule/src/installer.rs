@@ -1,4 +1,8 @@
-use std::{collections::HashSet, path::PathBuf, str::FromStr};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use crate::{
     config::DEFAULT_STDLIBS,
@@ -91,7 +95,14 @@ async fn parse_grit_module(
     };
     let referenced_modules = extract_grit_modules(&module_config.content, &module_config.path)?;
     for referenced_module in referenced_modules {
-        let referenced_module = ModuleRepo::from_repo_str(&referenced_module)?;
+        let mut referenced_module = ModuleRepo::from_repo_str(&referenced_module)?;
+        // Local paths are relative to the repo that references them
+        if referenced_module.is_local() {
+            referenced_module.remote = Path::new(repo_dir)
+                .join(&referenced_module.remote)
+                .to_string_lossy()
+                .to_string();
+        }
         processing_modules.push(referenced_module);
     }
 
@@ -139,6 +150,7 @@ patterns:
             full_name: "getgrit/rewriter".to_string(),
             remote: "https://github.com/getgrit/rewriter.git".to_string(),
             provider_name: "github.com/getgrit/rewriter".to_string(),
+            revision: None,
         };
         let curr_dir = dir.path().to_str().unwrap();
 
@@ -172,6 +184,7 @@ patterns:
             full_name: "getgrit/rewriter".to_string(),
             remote: "https://github.com/getgrit/rewriter.git".to_string(),
             provider_name: "github.com/getgrit/rewriter".to_string(),
+            revision: None,
         };
         let curr_dir = dir.path().to_str().unwrap();
         let installed = install_grit_modules(&fetcher, &curr_repo, curr_dir)
@@ -0,0 +1,328 @@
+use anyhow::{bail, Context, Result};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    WalkBuilder,
+};
+use marzano_core::api::EnforcementLevel;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{GritConfig, ResolvedGritDefinition, CONFIG_FILE_NAMES, REPO_CONFIG_DIR_NAME},
+    yaml::get_grit_config,
+};
+
+/// Level overrides from a single grit.yaml
+#[derive(Debug)]
+struct LevelLayer {
+    /// The directory containing the `.grit` directory, which the layer applies to
+    dir: PathBuf,
+    /// Levels set by pattern entries in a nested config, applying to the whole directory
+    levels: BTreeMap<String, EnforcementLevel>,
+    overrides: Vec<(Gitignore, BTreeMap<String, EnforcementLevel>)>,
+}
+
+/// Patterns in grit.yaml can be referenced by their full module name
+fn local_name(name: &str) -> &str {
+    name.rsplit('#').next().unwrap_or(name)
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl LevelLayer {
+    /// Read the level overrides from the grit.yaml in `dir/.grit`, if there is one
+    fn read(dir: &Path, nested: bool) -> Result<Option<Self>> {
+        let grit_dir = dir.join(REPO_CONFIG_DIR_NAME);
+        let Some(config_path) = CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| grit_dir.join(name))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let content = fs_err::read_to_string(&config_path)?;
+        let config_name = config_path.to_string_lossy();
+        let config = get_grit_config(&content, &config_name)?;
+        Self::from_config(dir, config, nested, &config_name).map(Some)
+    }
+
+    fn from_config(dir: &Path, config: GritConfig, nested: bool, path: &str) -> Result<Self> {
+        let mut levels = BTreeMap::new();
+        if nested {
+            if config.pattern_files.is_some() {
+                bail!("Nested config {} cannot import pattern files, import them in the root grit.yaml instead", path);
+            }
+            for pattern in config.patterns {
+                if pattern.body.is_some() {
+                    bail!("Nested config {} cannot define pattern {}, define it in the root grit.yaml instead", path, pattern.name);
+                }
+                if let Some(level) = pattern.meta.level {
+                    levels.insert(local_name(&pattern.name).to_string(), level);
+                }
+            }
+        }
+
+        let mut overrides = vec![];
+        for level_override in config.overrides {
+            let mut builder = GitignoreBuilder::new(dir);
+            for glob in level_override.files.iter() {
+                builder
+                    .add_line(None, glob)
+                    .with_context(|| format!("Invalid glob {} in {}", glob, path))?;
+            }
+            let matcher = builder.build()?;
+            let override_levels = level_override
+                .levels
+                .into_iter()
+                .map(|(name, level)| (local_name(&name).to_string(), level))
+                .collect();
+            overrides.push((matcher, override_levels));
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            levels,
+            overrides,
+        })
+    }
+
+    /// Every level this layer can give a pattern
+    fn levels_for<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a EnforcementLevel> {
+        self.levels.get(name).into_iter().chain(
+            self.overrides
+                .iter()
+                .filter_map(move |(_, levels)| levels.get(name)),
+        )
+    }
+}
+
+/// Enforcement levels that vary by path, from the `overrides` in grit.yaml and nested `.grit/grit.yaml` files.
+/// Deeper configs take precedence over the root, and later overrides over earlier ones.
+#[derive(Debug, Default)]
+pub struct PathLevels {
+    layers: Vec<LevelLayer>,
+}
+
+impl PathLevels {
+    /// Load the root config in `repo_dir` and every nested config below it
+    pub fn load(repo_dir: &Path) -> Result<Self> {
+        let repo_dir = absolute(repo_dir);
+        let mut layers = vec![];
+        if let Some(root) = LevelLayer::read(&repo_dir, false)? {
+            layers.push(root);
+        }
+
+        // Only look for .grit directories themselves, not their contents
+        let walker = WalkBuilder::new(&repo_dir)
+            .hidden(false)
+            .filter_entry(|entry| {
+                entry.file_name() != ".git"
+                    && entry.path().parent().and_then(|p| p.file_name())
+                        != Some(REPO_CONFIG_DIR_NAME.as_ref())
+            })
+            .build();
+        for entry in walker {
+            let entry = entry?;
+            if entry.file_name() != REPO_CONFIG_DIR_NAME || entry.depth() <= 1 {
+                continue;
+            }
+            let Some(dir) = entry.path().parent() else {
+                continue;
+            };
+            if let Some(layer) = LevelLayer::read(dir, true)? {
+                layers.push(layer);
+            }
+        }
+        layers.sort_by_key(|layer| layer.dir.components().count());
+
+        Ok(Self { layers })
+    }
+
+    /// Load only the configs that apply to `file`, from the root config in `repo_dir` down to its directory
+    pub fn load_for_file(repo_dir: &Path, file: &Path) -> Result<Self> {
+        let repo_dir = absolute(repo_dir);
+        let file = absolute(file);
+        let mut layers = vec![];
+        if let Some(root) = LevelLayer::read(&repo_dir, false)? {
+            layers.push(root);
+        }
+        let Ok(relative) = file.strip_prefix(&repo_dir) else {
+            return Ok(Self { layers });
+        };
+        let mut dir = repo_dir.clone();
+        let parents = relative.parent().map(|p| p.components()).into_iter();
+        for component in parents.flatten() {
+            dir.push(component);
+            if let Some(layer) = LevelLayer::read(&dir, true)? {
+                layers.push(layer);
+            }
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// The effective level of a pattern for a file
+    pub fn level_for(&self, pattern: &ResolvedGritDefinition, file: &Path) -> EnforcementLevel {
+        let mut level = pattern.level();
+        if self.layers.is_empty() {
+            return level;
+        }
+        let file = absolute(file);
+        for layer in self.layers.iter() {
+            let Ok(relative) = file.strip_prefix(&layer.dir) else {
+                continue;
+            };
+            if let Some(layer_level) = layer.levels.get(&pattern.local_name) {
+                level = layer_level.clone();
+            }
+            for (matcher, levels) in layer.overrides.iter() {
+                let Some(override_level) = levels.get(&pattern.local_name) else {
+                    continue;
+                };
+                if matcher
+                    .matched_path_or_any_parents(relative, false)
+                    .is_ignore()
+                {
+                    level = override_level.clone();
+                }
+            }
+        }
+        level
+    }
+
+    /// The highest level a pattern has for any file, used to decide whether it needs to run at all
+    pub fn max_level(&self, pattern: &ResolvedGritDefinition) -> EnforcementLevel {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.levels_for(&pattern.local_name))
+            .cloned()
+            .chain(std::iter::once(pattern.level()))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DefinitionSource, GritDefinitionConfig, GritUserConfig};
+    use marzano_language::target_language::PatternLanguage;
+    use tempfile::tempdir;
+
+    fn pattern(name: &str, level: EnforcementLevel) -> ResolvedGritDefinition {
+        let mut config = GritDefinitionConfig {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        config.meta.level = Some(level);
+        ResolvedGritDefinition {
+            config,
+            module: DefinitionSource::Config(GritUserConfig {
+                path: PathBuf::from(".grit/grit.yaml"),
+            }),
+            local_name: name.to_string(),
+            body: String::new(),
+            kind: Default::default(),
+            language: PatternLanguage::Tsx,
+            visibility: Default::default(),
+        }
+    }
+
+    #[test]
+    fn resolves_levels_by_path() {
+        let dir = tempdir().unwrap();
+        let repo = dir.path();
+        fs_err::create_dir_all(repo.join(".grit")).unwrap();
+        fs_err::write(
+            repo.join(".grit/grit.yaml"),
+            r#"version: 0.0.1
+patterns:
+  - name: no_console_log
+    level: warn
+    body: "`console.log($_)`"
+overrides:
+  - files: ["scripts/**"]
+    levels:
+      no_console_log: none
+  - files: ["packages/core/**"]
+    levels:
+      github.com/getgrit/stdlib#no_any: error
+"#,
+        )
+        .unwrap();
+        fs_err::create_dir_all(repo.join("packages/web/.grit")).unwrap();
+        fs_err::write(
+            repo.join("packages/web/.grit/grit.yaml"),
+            r#"patterns:
+  - name: no_console_log
+    level: error
+overrides:
+  - files: ["*.test.js"]
+    levels:
+      no_console_log: info
+"#,
+        )
+        .unwrap();
+
+        let no_console_log = pattern("no_console_log", EnforcementLevel::Warn);
+        let no_any = pattern("no_any", EnforcementLevel::Info);
+
+        let levels = PathLevels::load(repo).unwrap();
+        let level = |pattern, path: &str| levels.level_for(pattern, &repo.join(path));
+        assert_eq!(
+            level(&no_console_log, "src/index.js"),
+            EnforcementLevel::Warn
+        );
+        assert_eq!(
+            level(&no_console_log, "scripts/build.js"),
+            EnforcementLevel::None
+        );
+        assert_eq!(level(&no_any, "src/index.ts"), EnforcementLevel::Info);
+        assert_eq!(
+            level(&no_any, "packages/core/index.ts"),
+            EnforcementLevel::Error
+        );
+        assert_eq!(
+            level(&no_console_log, "packages/web/index.js"),
+            EnforcementLevel::Error
+        );
+        assert_eq!(
+            level(&no_console_log, "packages/web/src/app.test.js"),
+            EnforcementLevel::Info
+        );
+        assert_eq!(levels.max_level(&no_console_log), EnforcementLevel::Error);
+        assert_eq!(levels.max_level(&no_any), EnforcementLevel::Error);
+
+        // Loading only the configs above a file gives the same result
+        let file = repo.join("packages/web/src/app.test.js");
+        let file_levels = PathLevels::load_for_file(repo, &file).unwrap();
+        assert_eq!(
+            file_levels.level_for(&no_console_log, &file),
+            EnforcementLevel::Info
+        );
+    }
+
+    #[test]
+    fn nested_configs_cannot_define_patterns() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("packages/web");
+        fs_err::create_dir_all(nested.join(".grit")).unwrap();
+        fs_err::write(
+            nested.join(".grit/grit.yaml"),
+            "patterns:\n  - name: my_pattern\n    body: \"`foo`\"\n",
+        )
+        .unwrap();
+
+        let err = PathLevels::load(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("cannot define pattern my_pattern"));
+    }
+}
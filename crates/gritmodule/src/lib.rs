@@ -4,6 +4,8 @@ mod dot_grit;
 pub mod fetcher;
 pub mod formatting;
 pub mod installer;
+pub mod levels;
+pub mod lockfile;
 pub mod markdown;
 pub mod parser;
 pub mod patterns_directory;
@@ -11,6 +13,7 @@ pub mod resolver;
 pub mod searcher;
 pub mod testing;
 pub mod utils;
+pub mod vendor;
 mod yaml;
 
 #[cfg(test)]
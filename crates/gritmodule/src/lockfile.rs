@@ -0,0 +1,173 @@
+use anyhow::{bail, Context, Result};
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::fetcher::{grit_dir_for_clone_dir, ModuleRepo};
+
+pub const LOCKFILE_NAME: &str = "grit.lock";
+
+const LOCKFILE_VERSION: u32 = 1;
+
+/// The commit a module was resolved to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockedModule {
+    /// The tag, branch or commit requested with `@` in grit.yaml, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    pub commit: String,
+}
+
+/// Contents of `.grit/grit.lock`, keyed by module (like `github.com/getgrit/stdlib`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GritLock {
+    pub version: u32,
+    #[serde(default)]
+    pub modules: BTreeMap<String, LockedModule>,
+}
+
+impl Default for GritLock {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl GritLock {
+    /// Read the lockfile in a .grit directory, or an empty lock if there is none
+    pub fn read(grit_dir: &Path) -> Result<Self> {
+        let path = grit_dir.join(LOCKFILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs_err::read_to_string(&path)?;
+        let lock: GritLock = serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid lockfile {}", path.display()))?;
+        if lock.version != LOCKFILE_VERSION {
+            bail!(
+                "Unsupported lockfile version {} in {}",
+                lock.version,
+                path.display()
+            );
+        }
+        Ok(lock)
+    }
+
+    pub fn write(&self, grit_dir: &Path) -> Result<()> {
+        let content = format!(
+            "# Generated by grit. Run `grit modules update` to update it.\n{}",
+            serde_yaml::to_string(self)?
+        );
+        fs_err::write(grit_dir.join(LOCKFILE_NAME), content)?;
+        Ok(())
+    }
+}
+
+/// The commit currently checked out in a cloned module
+pub fn head_commit(module_dir: &Path) -> Option<String> {
+    let repo = Repository::open(module_dir).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+/// The lock used by a fetcher, kept next to the .gritmodules directory it clones into
+pub(crate) struct ModuleLock {
+    grit_dir: Option<PathBuf>,
+    lock: Mutex<GritLock>,
+}
+
+impl ModuleLock {
+    pub(crate) fn for_clone_dir(clone_dir: &Path) -> Self {
+        let grit_dir = grit_dir_for_clone_dir(clone_dir);
+        let lock = match &grit_dir {
+            Some(grit_dir) => GritLock::read(grit_dir).unwrap_or_else(|e| {
+                log::warn!("Ignoring lockfile: {}", e);
+                GritLock::default()
+            }),
+            None => GritLock::default(),
+        };
+        Self {
+            grit_dir,
+            lock: Mutex::new(lock),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, GritLock> {
+        self.lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The module to fetch: pinned to its locked commit, unless grit.yaml now asks for a different revision
+    pub(crate) fn pin(&self, repo: &ModuleRepo) -> ModuleRepo {
+        let lock = self.lock();
+        match lock.modules.get(&repo.provider_name) {
+            Some(locked) if locked.revision == repo.revision => ModuleRepo {
+                revision: Some(locked.commit.clone()),
+                ..repo.clone()
+            },
+            _ => repo.clone(),
+        }
+    }
+
+    /// Record the commit a module was fetched at, updating the lockfile if it changed
+    pub(crate) fn record(&self, repo: &ModuleRepo, module_dir: &Path) -> Result<()> {
+        let Some(grit_dir) = &self.grit_dir else {
+            return Ok(());
+        };
+        let Some(commit) = head_commit(module_dir) else {
+            return Ok(());
+        };
+        let locked = LockedModule {
+            revision: repo.revision.clone(),
+            commit,
+        };
+        let mut lock = self.lock();
+        if lock.modules.get(&repo.provider_name) == Some(&locked) {
+            return Ok(());
+        }
+        lock.modules.insert(repo.provider_name.clone(), locked);
+        lock.write(grit_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GRIT_MODULE_DIR;
+    use tempfile::tempdir;
+
+    #[test]
+    fn pins_locked_commits() {
+        let dir = tempdir().unwrap();
+        let grit_dir = dir.path().join(".grit");
+        fs_err::create_dir_all(&grit_dir).unwrap();
+        let mut lock = GritLock::default();
+        lock.modules.insert(
+            "github.com/getgrit/stdlib".to_string(),
+            LockedModule {
+                revision: None,
+                commit: "abc123".to_string(),
+            },
+        );
+        lock.write(&grit_dir).unwrap();
+        assert_eq!(GritLock::read(&grit_dir).unwrap(), lock);
+
+        let module_lock = ModuleLock::for_clone_dir(&grit_dir.join(GRIT_MODULE_DIR));
+        let stdlib = ModuleRepo::from_repo_str("github.com/getgrit/stdlib").unwrap();
+        assert_eq!(
+            module_lock.pin(&stdlib).revision,
+            Some("abc123".to_string())
+        );
+
+        // Asking for a different revision than the one that was locked resolves it again
+        let tagged = ModuleRepo::from_repo_str("github.com/getgrit/stdlib@v1").unwrap();
+        assert_eq!(module_lock.pin(&tagged).revision, Some("v1".to_string()));
+    }
+}
@@ -237,6 +237,7 @@ js"hello world"
                         content: src.to_string(),
                         format: crate::parser::PatternFileExt::Md,
                     }),
+                    ..Default::default()
                 },
                 module: source_module.clone(),
                 local_name,
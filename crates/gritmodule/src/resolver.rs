@@ -1,7 +1,7 @@
 use crate::{
     config::{get_stdlib_modules, DefinitionSource, GritUserConfig},
     fetcher::GritModuleFetcher,
-    searcher::find_grit_dir_from,
+    searcher::find_root_grit_dir_from,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -12,8 +12,8 @@ use std::{
 
 use crate::{
     config::{
-        is_namespace_import, ModuleGritPattern, ResolvedGritDefinition, GRIT_MODULE_DIR,
-        REPO_CONFIG_DIR_NAME, REPO_CONFIG_PATTERNS_DIR,
+        is_namespace_import, module_dir, ModuleGritPattern, ResolvedGritDefinition,
+        GRIT_MODULE_DIR, REPO_CONFIG_DIR_NAME, REPO_CONFIG_PATTERNS_DIR,
     },
     fetcher::{FetcherType, ModuleRepo},
     installer::{install_default_stdlib, install_grit_modules},
@@ -247,13 +247,10 @@ pub async fn get_grit_files(
         if processed_modules.contains(&module.provider_name) {
             continue;
         }
-        let repo_dir = PathBuf::from_str(grit_parent_dir)
+        let grit_dir = PathBuf::from_str(grit_parent_dir)
             .unwrap()
-            .join(REPO_CONFIG_DIR_NAME)
-            .join(GRIT_MODULE_DIR)
-            .join(module.provider_name.clone())
-            .to_string_lossy()
-            .to_string();
+            .join(REPO_CONFIG_DIR_NAME);
+        let repo_dir = module_dir(&grit_dir, &module).to_string_lossy().to_string();
         let provider_name = module.provider_name.clone();
         get_grit_files_for_module(
             &Some(module),
@@ -303,13 +300,10 @@ pub async fn resolve_patterns(
         if processed_modules.contains(&module.provider_name) {
             continue;
         }
-        let repo_dir = PathBuf::from_str(grit_parent_dir)
+        let grit_dir = PathBuf::from_str(grit_parent_dir)
             .unwrap()
-            .join(REPO_CONFIG_DIR_NAME)
-            .join(GRIT_MODULE_DIR)
-            .join(module.provider_name.clone())
-            .to_string_lossy()
-            .to_string();
+            .join(REPO_CONFIG_DIR_NAME);
+        let repo_dir = module_dir(&grit_dir, &module).to_string_lossy().to_string();
         let provider_name = module.provider_name.clone();
         let res = resolve_patterns_for_module(
             &Some(module),
@@ -389,7 +383,11 @@ pub async fn resolve_patterns(
                 .await?;
             }
         } else {
-            let resolved = if let Some(resolved) = resolved_patterns.get(&local_name) {
+            let target_name: &str = match &pattern.config.pattern {
+                Some(target) => target.rsplit('#').next().unwrap_or(target),
+                None => &local_name,
+            };
+            let resolved = if let Some(resolved) = resolved_patterns.get(target_name) {
                 if let Some(body) = &pattern.config.body {
                     resolved.values().find(|p| {
                         if p.body != *body {
@@ -402,8 +400,10 @@ pub async fn resolve_patterns(
                         }
                     })
                 } else {
+                    let reference = pattern.config.pattern.as_ref();
+                    let reference = reference.unwrap_or(&pattern.config.name);
                     let found = resolved.values().find(|p| {
-                        if let Some(name) = pattern.config.name.split('#').next() {
+                        if let Some(name) = reference.split('#').next() {
                             let module_repo = match ModuleRepo::from_repo_str(name) {
                                 Ok(module_repo) => module_repo,
                                 Err(_) => {
@@ -623,6 +623,14 @@ async fn resolve_patterns_for_module(
     let mut parser = MarzanoGritParser::new()?;
 
     for referenced_pattern in patterns {
+        // Instances are resolved like references, from the pattern they call
+        if referenced_pattern.config.is_instance() {
+            module_patterns
+                .entry(referenced_pattern.local_name.to_string())
+                .or_default()
+                .push(referenced_pattern);
+            continue;
+        }
         if let Some(module) = referenced_pattern
             .module
             .as_ref()
@@ -715,10 +723,19 @@ fn merge_local_with_remote(
     if let Some(level) = local.config.meta.level {
         config.meta.level = Some(level);
     }
+    // Instances keep their own name, and call the pattern they were resolved from
+    let local_name = if local.config.is_instance() {
+        config.name = local.config.name;
+        config.pattern = Some(remote.local_name);
+        config.args = local.config.args;
+        local.local_name
+    } else {
+        remote.local_name
+    };
     ResolvedGritDefinition {
         config,
         module: remote.module,
-        local_name: remote.local_name,
+        local_name,
         body: remote.body,
         language: remote.language,
         kind: remote.kind,
@@ -749,7 +766,7 @@ pub async fn find_and_resolve_grit_dir(
     global_dir: Option<PathBuf>,
 ) -> Result<PatternsDirectory> {
     let existing_config = if let Some(cwd) = cwd {
-        find_grit_dir_from(cwd).await
+        find_root_grit_dir_from(cwd).await
     } else {
         None
     };
@@ -777,6 +794,7 @@ mod tests {
     };
 
     use insta::assert_yaml_snapshot;
+    use marzano_core::api::EnforcementLevel;
 
     use crate::{fetcher::ModuleRepo, resolver::dir_has_config};
 
@@ -956,4 +974,55 @@ mod tests {
         resolved_patterns.sort_by(|a, b| a.local_name.cmp(&b.local_name));
         assert_yaml_snapshot!(resolved_patterns);
     }
+
+    #[tokio::test]
+    async fn resolves_pattern_instances_with_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let grit_dir = dir.path().join(".grit");
+        fs_err::create_dir_all(grit_dir.join("patterns")).unwrap();
+        fs_err::write(
+            grit_dir.join("patterns/no_import.grit"),
+            "language js\n\npattern no_import($module) {\n  `import $_ from $source` where { $source <: includes $module }\n}\n",
+        )
+        .unwrap();
+        fs_err::write(
+            grit_dir.join("grit.yaml"),
+            r#"version: 0.0.1
+patterns:
+  - name: no_lodash
+    pattern: no_import
+    level: error
+    args:
+      module: lodash
+  - name: no_moment
+    pattern: no_import
+    level: warn
+    args:
+      module:
+        snippet: moment
+"#,
+        )
+        .unwrap();
+
+        let module_repo = ModuleRepo::from_host_repo("github.com", "getgrit/rewriter").unwrap();
+        let (resolved_patterns, errored_patterns) =
+            super::resolve_patterns(&module_repo, &dir.path().to_string_lossy(), None)
+                .await
+                .unwrap();
+        assert_eq!(errored_patterns.len(), 0);
+
+        let find = |name: &str| {
+            resolved_patterns
+                .iter()
+                .find(|p| p.local_name == name)
+                .unwrap()
+        };
+        let no_lodash = find("no_lodash");
+        assert_eq!(no_lodash.call(), "no_import(module = \"lodash\")");
+        assert_eq!(no_lodash.level(), EnforcementLevel::Error);
+        let no_moment = find("no_moment");
+        assert_eq!(no_moment.call(), "no_import(module = `moment`)");
+        assert_eq!(no_moment.level(), EnforcementLevel::Warn);
+        assert_eq!(find("no_import").call(), "no_import()");
+    }
 }
@@ -220,6 +220,33 @@ pub async fn find_git_dir_from(dir: PathBuf) -> Option<PathBuf> {
     search(dir, &[".git".to_string()], None).await
 }
 
+/// Find the root `.grit` directory for `dir`, which is the outermost one below the git root.
+/// Nearer `.grit` directories are nested configs that only layer levels on top of it.
+/// Outside of a git repo, this is the nearest `.grit` directory.
+pub async fn find_root_grit_dir_from(dir: PathBuf) -> Option<PathBuf> {
+    let nearest = find_grit_dir_from(dir).await?;
+    let Some(git_root) = find_git_dir_from(nearest.clone())
+        .await
+        .and_then(|git_dir| git_dir.parent().map(Path::to_path_buf))
+    else {
+        return Some(nearest);
+    };
+
+    let mut root = nearest.clone();
+    let mut current = nearest.parent().and_then(Path::parent);
+    while let Some(dir) = current {
+        if !dir.starts_with(&git_root) {
+            break;
+        }
+        let grit_dir = dir.join(REPO_CONFIG_DIR_NAME);
+        if fs::metadata(&grit_dir).await.is_ok() {
+            root = grit_dir;
+        }
+        current = dir.parent();
+    }
+    Some(root)
+}
+
 pub async fn find_repo_root_from(dir: PathBuf) -> Result<Option<String>> {
     let git_dir = find_git_dir_from(dir).await;
     if let Some(git_path) = git_dir {
@@ -239,7 +266,7 @@ pub async fn find_repo_root_from(dir: PathBuf) -> Result<Option<String>> {
 }
 
 pub async fn find_grit_modules_dir(dir: PathBuf) -> Result<PathBuf> {
-    let grit_dir = find_grit_dir_from(dir).await;
+    let grit_dir = find_root_grit_dir_from(dir).await;
     if let Some(grit_dir) = grit_dir {
         let grit_modules_dir = grit_dir.join(GRIT_MODULE_DIR);
         if grit_modules_dir.exists() {
@@ -311,6 +338,25 @@ mod tests {
         assert!(config_file.is_none());
     }
 
+    #[tokio::test]
+    async fn finds_root_grit_dir_from_nested_config() {
+        let temp_dir = tempdir().unwrap();
+        let repo = temp_dir.path();
+        let nested = repo.join("packages/web");
+        fs::create_dir_all(repo.join(".git")).await.unwrap();
+        fs::create_dir_all(repo.join(".grit")).await.unwrap();
+        fs::create_dir_all(nested.join(".grit")).await.unwrap();
+        fs::create_dir_all(nested.join("src")).await.unwrap();
+
+        let root = find_root_grit_dir_from(nested.join("src")).await.unwrap();
+        assert_eq!(root, repo.join(".grit"));
+
+        // Without a git root, the nearest config is used
+        fs::remove_dir(repo.join(".git")).await.unwrap();
+        let nearest = find_root_grit_dir_from(nested.join("src")).await.unwrap();
+        assert_eq!(nearest, nested.join(".grit"));
+    }
+
     #[tokio::test]
     async fn grit_searcher_stops_traversal_at_repo_boundary() {
         let temp_dir = tempdir().unwrap();
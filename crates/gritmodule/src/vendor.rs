@@ -0,0 +1,179 @@
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::{GRIT_MODULE_DIR, REPO_CONFIG_DIR_NAME, VENDOR_DIR},
+    utils::remove_dir_all_safe,
+};
+
+/// Directories that are never copied into a module: git metadata and nested module caches
+fn is_skipped(name: &std::ffi::OsStr) -> bool {
+    name == ".git" || name == GRIT_MODULE_DIR
+}
+
+fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Copy a module directory, leaving out git metadata and nested module caches
+pub(crate) fn copy_module_dir(from: &Path, to: &Path) -> Result<()> {
+    fs_err::create_dir_all(to)?;
+    for entry in fs_err::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if is_skipped(&name) {
+            continue;
+        }
+        let target = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_module_dir(&entry.path(), &target)?;
+        } else {
+            fs_err::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Unpack a `.tar.gz` module into `to`.
+/// Archives that wrap the module in a single top-level directory are unwrapped.
+fn unpack_module_archive(archive: &Path, to: &Path) -> Result<()> {
+    let parent = to.parent().context("Module directory has no parent")?;
+    fs_err::create_dir_all(parent)?;
+    let unpacked = tempfile::tempdir_in(parent)?;
+    let file = fs_err::File::open(archive)?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(unpacked.path())
+        .with_context(|| format!("Failed to unpack {}", archive.display()))?;
+
+    let mut root = unpacked.path().to_path_buf();
+    if !root.join(REPO_CONFIG_DIR_NAME).exists() {
+        let entries = fs_err::read_dir(&root)?.collect::<std::io::Result<Vec<_>>>()?;
+        if let [entry] = entries.as_slice() {
+            if entry.file_type()?.is_dir() {
+                root = entry.path();
+            }
+        }
+    }
+    copy_module_dir(&root, to)
+}
+
+/// Copy a module from a local directory or archive into the modules directory.
+/// Local modules are copied on every fetch, since they are usually edited in place.
+pub(crate) fn fetch_local_module(source: &Path, target_dir: &Path) -> Result<()> {
+    if !source.exists() {
+        bail!("Local grit module {} does not exist", source.display());
+    }
+    if target_dir.exists() {
+        remove_dir_all_safe(target_dir)?;
+    }
+    if is_archive(source) {
+        unpack_module_archive(source, target_dir)
+    } else {
+        copy_module_dir(source, target_dir)
+    }
+}
+
+/// Module directories below `dir`, identified by their `.grit` directory
+fn find_module_dirs(dir: &Path, modules: &mut Vec<PathBuf>) -> Result<()> {
+    if dir.join(REPO_CONFIG_DIR_NAME).is_dir() {
+        modules.push(dir.to_path_buf());
+        return Ok(());
+    }
+    for entry in fs_err::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() && !is_skipped(&entry.file_name()) {
+            find_module_dirs(&entry.path(), modules)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy every fetched module in a .grit directory into `.grit/vendor`, replacing any earlier copies.
+/// Returns the names of the vendored modules.
+pub fn vendor_modules(grit_dir: &Path) -> Result<Vec<String>> {
+    let modules_dir = grit_dir.join(GRIT_MODULE_DIR);
+    let vendor_dir = grit_dir.join(VENDOR_DIR);
+    if !modules_dir.exists() {
+        bail!(
+            "No modules have been fetched into {}, run `grit init` first",
+            modules_dir.display()
+        );
+    }
+    if vendor_dir.exists() {
+        remove_dir_all_safe(&vendor_dir)?;
+    }
+
+    let mut module_dirs = vec![];
+    find_module_dirs(&modules_dir, &mut module_dirs)?;
+    let mut vendored = vec![];
+    for module_dir in module_dirs {
+        let name = module_dir.strip_prefix(&modules_dir)?;
+        copy_module_dir(&module_dir, &vendor_dir.join(name))?;
+        vendored.push(name.to_string_lossy().to_string());
+    }
+    vendored.sort();
+    Ok(vendored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use tempfile::tempdir;
+
+    fn write_module(dir: &Path) {
+        fs_err::create_dir_all(dir.join(".grit/patterns")).unwrap();
+        fs_err::create_dir_all(dir.join(".git")).unwrap();
+        fs_err::write(dir.join(".grit/patterns/shared.grit"), "`foo` => `bar`").unwrap();
+    }
+
+    #[test]
+    fn fetches_local_directories_and_archives() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("shared-patterns");
+        write_module(&source);
+
+        let copied = dir.path().join("copied");
+        fetch_local_module(&source, &copied).unwrap();
+        assert!(copied.join(".grit/patterns/shared.grit").exists());
+        assert!(!copied.join(".git").exists());
+
+        // Archives usually wrap the module in a directory named after it
+        let archive = dir.path().join("shared-patterns.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            fs_err::File::create(&archive).unwrap(),
+            Compression::default(),
+        ));
+        builder
+            .append_dir_all("shared-patterns/.grit", source.join(".grit"))
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let unpacked = dir.path().join("unpacked");
+        fetch_local_module(&archive, &unpacked).unwrap();
+        assert!(unpacked.join(".grit/patterns/shared.grit").exists());
+    }
+
+    #[test]
+    fn vendors_fetched_modules() {
+        let dir = tempdir().unwrap();
+        let grit_dir = dir.path().join(".grit");
+        write_module(
+            &grit_dir
+                .join(GRIT_MODULE_DIR)
+                .join("github.com/getgrit/stdlib"),
+        );
+        write_module(&grit_dir.join(GRIT_MODULE_DIR).join("file/shared-patterns"));
+
+        let vendored = vendor_modules(&grit_dir).unwrap();
+        assert_eq!(
+            vendored,
+            vec!["file/shared-patterns", "github.com/getgrit/stdlib"]
+        );
+        let vendored_stdlib = grit_dir.join(VENDOR_DIR).join("github.com/getgrit/stdlib");
+        assert!(vendored_stdlib.join(".grit/patterns/shared.grit").exists());
+        assert!(!vendored_stdlib.join(".git").exists());
+    }
+}
@@ -3,7 +3,7 @@ use futures::{future::BoxFuture, FutureExt as _};
 use grit_util::{Position, Range};
 use marzano_util::rich_path::RichFile;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 use tokio::fs;
@@ -52,6 +52,8 @@ pub fn get_grit_config(source: &str, source_path: &str) -> Result<GritConfig> {
 
     let new_config = GritConfig {
         github: serialized.github,
+        overrides: serialized.overrides,
+        limits: serialized.limits,
         pattern_files: if pattern_files.is_empty() {
             None
         } else {
@@ -126,10 +128,26 @@ pub fn extract_grit_modules(content: &str, path: &str) -> Result<Vec<String>> {
     let config = get_grit_config(content, path)?;
 
     let mut unique_names: HashSet<String> = HashSet::new();
+    let mut revisions: HashMap<&str, &str> = HashMap::new();
 
-    for pattern in config.patterns {
-        if let Some(hash_index) = pattern.name.find('#') {
-            unique_names.insert(pattern.name[..hash_index].to_string());
+    for pattern in config.patterns.iter() {
+        let reference = pattern.pattern.as_ref().unwrap_or(&pattern.name);
+        if let Some(hash_index) = reference.find('#') {
+            let module = &reference[..hash_index];
+            // Each module can only be checked out at one revision
+            let (repo, revision) = module.rsplit_once('@').unwrap_or((module, ""));
+            if let Some(existing) = revisions.insert(repo, revision) {
+                if existing != revision {
+                    bail!(
+                        "Module {} is requested at conflicting revisions in {}: '{}' and '{}'",
+                        repo,
+                        path,
+                        existing,
+                        revision
+                    );
+                }
+            }
+            unique_names.insert(module.to_string());
         }
     }
 
@@ -190,6 +208,23 @@ patterns:
         assert_eq!(gritmodule_set, expected_set);
     }
 
+    #[test]
+    fn gets_pinned_grit_modules() {
+        let grit_yaml = r#"version: 0.0.1
+patterns:
+  - name: github.com/getgrit/stdlib@v0.1.0#no_console_log
+    level: error
+  - name: github.com/getgrit/stdlib@v0.1.0#no_debugger
+    level: error
+    "#;
+        let gritmodules = extract_grit_modules(grit_yaml, ".grit/grit.yaml").unwrap();
+        assert_eq!(gritmodules, vec!["github.com/getgrit/stdlib@v0.1.0"]);
+
+        let conflicting = grit_yaml.replace("@v0.1.0#no_debugger", "#no_debugger");
+        let err = extract_grit_modules(&conflicting, ".grit/grit.yaml").unwrap_err();
+        assert!(err.to_string().contains("conflicting revisions"));
+    }
+
     #[test]
     fn invalid_grit_yaml() {
         let grit_yaml = "invalid config";
@@ -247,4 +282,16 @@ github:
         println!("{:?}", config);
         assert_eq!(config.github.unwrap().reviewers.len(), 2);
     }
+
+    #[test]
+    fn gets_file_limits() {
+        let grit_yaml = r#"version: 0.1.0
+patterns: []
+limits:
+  max_file_millis: 5000
+    "#;
+        let config = get_grit_config(grit_yaml, ".grit/grit.yaml").unwrap();
+        assert_eq!(config.limits.max_file_millis, Some(5000));
+        assert_eq!(config.limits.max_file_steps, None);
+    }
 }
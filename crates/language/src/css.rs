@@ -1,4 +1,5 @@
 use crate::{
+    embedded::{is_embedding_host, parse_embedded, EmbeddedKind},
     language::{
         fields_for_nodes, Field, MarzanoLanguage, MarzanoParser, NodeTypes, SortId, TSLanguage,
         Tree,
@@ -129,6 +130,8 @@ impl Parser for MarzanoCssParser {
                 .parse(body, None)
                 .ok()?
                 .map(|tree| Tree::new(tree, body))
+        } else if is_embedding_host(path, EmbeddedKind::Style) && old_tree.is_fresh() {
+            parse_embedded(&mut self.0.parser, body, path, EmbeddedKind::Style)
         } else {
             self.0.parse_file(body, path, logs, old_tree)
         }
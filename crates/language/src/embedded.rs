@@ -0,0 +1,359 @@
+use crate::{
+    html::Html,
+    language::{MarzanoLanguage, Tree},
+    markdown_block::MarkdownBlock,
+    sourcemap::{EmbeddedSourceMap, SourceMapSection, SourceValueFormat},
+};
+use anyhow::{anyhow, Result};
+use grit_util::{traverse, AstNode, ByteRange, Order};
+use marzano_util::{cursor_wrapper::CursorWrapper, node_with_source::NodeWithSource};
+use std::{ops::Range, path::Path};
+use tree_sitter::Parser;
+
+/// Files that embed script and style blocks in markup.
+/// Vue is handled separately, with its own grammar.
+const MARKUP_EXTENSIONS: &[&str] = &["html", "htm", "svelte", "astro"];
+
+/// Files that embed code in fenced blocks
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "mdx"];
+
+/// The kind of code to extract from a host file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EmbeddedKind {
+    Script,
+    Style,
+    Python,
+}
+
+impl EmbeddedKind {
+    fn element_kind(&self) -> Option<&'static str> {
+        match self {
+            EmbeddedKind::Script => Some("script_element"),
+            EmbeddedKind::Style => Some("style_element"),
+            EmbeddedKind::Python => None,
+        }
+    }
+
+    /// The info strings of fenced code blocks that contain this kind of code
+    fn fence_tags(&self) -> &'static [&'static str] {
+        match self {
+            EmbeddedKind::Script => &[
+                "js",
+                "jsx",
+                "ts",
+                "tsx",
+                "javascript",
+                "typescript",
+                "mjs",
+                "cjs",
+            ],
+            EmbeddedKind::Style => &["css"],
+            EmbeddedKind::Python => &["python", "py", "python3"],
+        }
+    }
+
+    /// Whether a block with the given `lang` or `type` attribute contains this kind of code
+    fn accepts(&self, lang: &str) -> bool {
+        match self {
+            EmbeddedKind::Script => matches!(
+                lang,
+                "js" | "jsx"
+                    | "ts"
+                    | "tsx"
+                    | "javascript"
+                    | "typescript"
+                    | "module"
+                    | "text/javascript"
+                    | "application/javascript"
+                    | "text/typescript"
+            ),
+            EmbeddedKind::Style => matches!(lang, "css" | "text/css"),
+            EmbeddedKind::Python => false,
+        }
+    }
+}
+
+fn has_extension(path: Option<&Path>, extensions: &[&str]) -> bool {
+    path.and_then(Path::extension)
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext))
+}
+
+/// Whether the file at `path` may contain embedded code of the given kind
+pub(crate) fn is_embedding_host(path: Option<&Path>, kind: EmbeddedKind) -> bool {
+    has_extension(path, MARKDOWN_EXTENSIONS)
+        || (kind.element_kind().is_some() && has_extension(path, MARKUP_EXTENSIONS))
+}
+
+fn attribute_value(attribute: &NodeWithSource) -> Option<String> {
+    let value = attribute.named_children().find(|n| {
+        n.node.kind() == "attribute_value" || n.node.kind() == "quoted_attribute_value"
+    })?;
+    let text = value.text().ok()?;
+    Some(
+        text.trim_matches(|c| c == '"' || c == '\'')
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Blocks without a `lang` or `type` attribute are plain JavaScript or CSS
+fn is_accepted_element(element: &NodeWithSource, kind: EmbeddedKind) -> bool {
+    let Some(start_tag) = element
+        .named_children()
+        .find(|n| n.node.kind() == "start_tag")
+    else {
+        return false;
+    };
+    start_tag
+        .named_children()
+        .filter(|n| n.node.kind() == "attribute")
+        .filter(|attribute| {
+            attribute
+                .named_children()
+                .find(|n| n.node.kind() == "attribute_name")
+                .and_then(|name| name.text().ok())
+                .is_some_and(|name| matches!(name.trim(), "lang" | "type"))
+        })
+        .all(|attribute| {
+            attribute_value(&attribute).is_some_and(|lang| kind.accepts(&lang.to_lowercase()))
+        })
+}
+
+/// Astro components start with a frontmatter script, fenced by `---` lines
+fn astro_frontmatter_range(file: &str) -> Option<Range<usize>> {
+    let start = file.len() - file.trim_start().len();
+    let rest = file[start..].strip_prefix("---")?;
+    let rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))?;
+    let content_start = file.len() - rest.len();
+    let mut offset = content_start;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some(content_start..offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Find fenced code blocks whose info string starts with one of `tags`, like ```` ```ts title="a.ts" ````
+fn get_fenced_ranges(file: &str, tags: &[&str]) -> Result<Vec<Range<usize>>> {
+    let markdown = MarkdownBlock::new(None);
+    let mut parser = Parser::new()?;
+    parser.set_language(markdown.get_ts_language())?;
+    let tree = parser.parse(file, None)?.ok_or(anyhow!("missing tree"))?;
+    let cursor = tree.walk();
+    let mut ranges = Vec::new();
+    for n in traverse(CursorWrapper::new(cursor, file), Order::Pre) {
+        if n.node.kind() != "fenced_code_block" {
+            continue;
+        }
+        let is_tagged = n
+            .named_children()
+            .find(|n| n.node.kind() == "info_string")
+            .and_then(|info| info.text().ok().map(|t| t.to_string()))
+            .and_then(|info| info.split_whitespace().next().map(str::to_lowercase))
+            .is_some_and(|tag| tags.contains(&tag.as_str()));
+        let Some(content) = n
+            .named_children()
+            .find(|n| n.node.kind() == "code_fence_content")
+        else {
+            continue;
+        };
+        // Blocks inside quotes and lists carry the markers of every line, so they aren't valid code
+        let is_nested = content
+            .named_children()
+            .any(|n| n.node.kind() == "block_continuation");
+        if is_tagged && !is_nested {
+            let range = content.byte_range();
+            ranges.push(range.start..range.end);
+        }
+    }
+    Ok(ranges)
+}
+
+/// Find the byte ranges of the embedded code blocks of the given kind
+pub(crate) fn get_embedded_ranges(
+    file: &str,
+    path: Option<&Path>,
+    kind: EmbeddedKind,
+) -> Result<Vec<Range<usize>>> {
+    if has_extension(path, MARKDOWN_EXTENSIONS) {
+        return get_fenced_ranges(file, kind.fence_tags());
+    }
+    let Some(element_kind) = kind.element_kind() else {
+        return Ok(vec![]);
+    };
+
+    let mut ranges = Vec::new();
+    if kind == EmbeddedKind::Script
+        && path
+            .and_then(Path::extension)
+            .is_some_and(|ext| ext == "astro")
+    {
+        ranges.extend(astro_frontmatter_range(file));
+    }
+
+    let html = Html::new(None);
+    let mut parser = Parser::new()?;
+    parser.set_language(html.get_ts_language())?;
+    let tree = parser.parse(file, None)?.ok_or(anyhow!("missing tree"))?;
+    let cursor = tree.walk();
+    for n in traverse(CursorWrapper::new(cursor, file), Order::Pre) {
+        if n.node.kind() != element_kind || !is_accepted_element(&n, kind) {
+            continue;
+        }
+        if let Some(code) = n.named_children().find(|n| n.node.kind() == "raw_text") {
+            let range = code.byte_range();
+            // The frontmatter is not markup, so skip anything the HTML parser found inside it
+            if ranges.iter().all(|r| range.start >= r.end) {
+                ranges.push(range.start..range.end);
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+/// Parse the embedded code blocks of a host file as a single document,
+/// with a source map so rewrites are written back into the host file.
+pub(crate) fn parse_embedded(
+    parser: &mut Parser,
+    body: &str,
+    path: Option<&Path>,
+    kind: EmbeddedKind,
+) -> Option<Tree> {
+    let ranges = get_embedded_ranges(body, path, kind).ok()?;
+    let mut inner_code_body = String::new();
+    let mut source_map = EmbeddedSourceMap::new(body);
+    for range in ranges {
+        inner_code_body.push_str(&body[range.clone()]);
+        // Add a newline to separate blocks
+        inner_code_body.push('\n');
+        source_map.add_section(SourceMapSection::new(
+            ByteRange::new(range.start, range.end),
+            inner_code_body.len(),
+            SourceValueFormat::Raw,
+            1,
+        ));
+    }
+    parser
+        .parse(inner_code_body.clone(), None)
+        .ok()?
+        .map(|tree| {
+            let mut tree = Tree::new(tree, inner_code_body);
+            tree.source_map = Some(source_map);
+            tree
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typescript::TypeScript;
+
+    fn blocks<'a>(file: &'a str, path: &str, kind: EmbeddedKind) -> Vec<&'a str> {
+        get_embedded_ranges(file, Some(Path::new(path)), kind)
+            .unwrap()
+            .into_iter()
+            .map(|range| &file[range])
+            .collect()
+    }
+
+    #[test]
+    fn html_script_and_style_blocks() {
+        let file = r#"<html>
+<head>
+<style>.a { color: red; }</style>
+<script type="application/ld+json">{"a": 1}</script>
+<script type="module">console.log("module");</script>
+</head>
+<body><script>console.log("body");</script></body>
+</html>"#;
+        assert_eq!(
+            blocks(file, "index.html", EmbeddedKind::Script),
+            vec![r#"console.log("module");"#, r#"console.log("body");"#]
+        );
+        assert_eq!(
+            blocks(file, "index.html", EmbeddedKind::Style),
+            vec![".a { color: red; }"]
+        );
+    }
+
+    #[test]
+    fn astro_frontmatter() {
+        let file = "---\nconst title = 'Hi';\n---\n<h1>{title}</h1>\n<script>console.log(title);</script>\n";
+        assert_eq!(
+            blocks(file, "page.astro", EmbeddedKind::Script),
+            vec!["const title = 'Hi';\n", "console.log(title);"]
+        );
+        assert_eq!(
+            blocks(file, "page.html", EmbeddedKind::Script),
+            vec!["console.log(title);"]
+        );
+    }
+
+    #[test]
+    fn markdown_fenced_blocks() {
+        let file = r#"# Usage
+
+```python
+print("hello")
+```
+
+```ts title="example.ts"
+console.log("hello");
+```
+
+> ```py
+> print("quoted")
+> ```
+
+```
+print("untagged")
+```
+"#;
+        assert_eq!(
+            blocks(file, "README.md", EmbeddedKind::Python),
+            vec!["print(\"hello\")\n"]
+        );
+        assert_eq!(
+            blocks(file, "README.md", EmbeddedKind::Script),
+            vec!["console.log(\"hello\");\n"]
+        );
+        assert!(!is_embedding_host(
+            Some(Path::new("index.html")),
+            EmbeddedKind::Python
+        ));
+    }
+
+    #[test]
+    fn rewrites_map_back_into_host() {
+        let file = "<script lang=\"ts\">\nlet a = 1;\n</script>\n<p>Hi</p>\n<script>\nlet b = 2;\n</script>\n";
+        let mut parser = Parser::new().unwrap();
+        parser
+            .set_language(TypeScript::new(None).get_ts_language())
+            .unwrap();
+        let tree = parse_embedded(
+            &mut parser,
+            file,
+            Some(Path::new("app.svelte")),
+            EmbeddedKind::Script,
+        )
+        .unwrap();
+        assert_eq!(tree.source, "\nlet a = 1;\n\n\nlet b = 2;\n\n");
+        let source_map = tree.source_map.unwrap();
+        assert_eq!(source_map.fill_with_inner(&tree.source).unwrap(), file);
+
+        // let a -> const a
+        let adjustments = [(1..4, 5)];
+        let adjusted = source_map.clone_with_edits(adjustments.iter()).unwrap();
+        assert_eq!(
+            adjusted
+                .fill_with_inner("\nconst a = 1;\n\n\nlet b = 2;\n\n")
+                .unwrap(),
+            "<script lang=\"ts\">\nconst a = 1;\n</script>\n<p>Hi</p>\n<script>\nlet b = 2;\n</script>\n"
+        );
+    }
+}
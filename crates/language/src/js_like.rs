@@ -1,4 +1,5 @@
 use crate::{
+    embedded::{is_embedding_host, parse_embedded, EmbeddedKind},
     language::{
         FieldExpectationCondition, FieldExpectationCondition::Always,
         FieldExpectationCondition::OnlyIf, MarzanoLanguage, MarzanoParser, SortId, TSLanguage,
@@ -136,6 +137,8 @@ impl Parser for MarzanoJsLikeParser {
                 .parse(body, None)
                 .ok()?
                 .map(|tree| Tree::new(tree, body))
+        } else if is_embedding_host(path, EmbeddedKind::Script) && old_tree.is_fresh() {
+            parse_embedded(&mut self.0.parser, body, path, EmbeddedKind::Script)
         } else {
             self.0.parse_file(body, path, logs, old_tree)
         }
@@ -1,13 +1,14 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use enum_dispatch::enum_dispatch;
 use grit_util::{
     traverse, AnalysisLogBuilder, AnalysisLogs, Ast, AstNode, CodeRange, EffectRange, FileOrigin,
-    Language, Order, Parser, SnippetTree,
+    Language, Order, Parser, Range, SnippetTree,
 };
 use itertools::Itertools;
 use marzano_util::{cursor_wrapper::CursorWrapper, node_with_source::NodeWithSource};
 use serde_json::Value;
 use std::{borrow::Cow, cmp::max, collections::HashMap, path::Path};
+use tree_sitter::{InputEdit, Point};
 pub(crate) use tree_sitter::{Language as TSLanguage, Parser as TSParser, Tree as TSTree};
 
 use crate::sourcemap::EmbeddedSourceMap;
@@ -212,6 +213,66 @@ impl Tree {
             None => &self.source,
         }
     }
+
+    /// Replace the source between `start_byte` and `old_end_byte` with `replacement`,
+    /// and edit the syntax tree to match so it can be handed back to the parser
+    /// with `FileOrigin::Edited`.
+    ///
+    /// Only trees without a source map can be edited, since their source is the
+    /// file content as-is.
+    pub fn edit(
+        &mut self,
+        start_byte: usize,
+        old_end_byte: usize,
+        replacement: &str,
+    ) -> Result<()> {
+        if self.source_map.is_some() {
+            bail!("Cannot edit a tree with a source map");
+        }
+        if start_byte > old_end_byte
+            || !self.source.is_char_boundary(start_byte)
+            || !self.source.is_char_boundary(old_end_byte)
+        {
+            bail!("Invalid edit range {start_byte}..{old_end_byte}");
+        }
+        let new_end_byte = start_byte + replacement.len();
+        let start_position = byte_to_point(&self.source, start_byte);
+        let old_end_position = byte_to_point(&self.source, old_end_byte);
+        self.source
+            .replace_range(start_byte..old_end_byte, replacement);
+        let new_end_position = byte_to_point(&self.source, new_end_byte);
+        self.tree.edit(&InputEdit::new(
+            start_byte as u32,
+            old_end_byte as u32,
+            new_end_byte as u32,
+            &start_position,
+            &old_end_position,
+            &new_end_position,
+        ));
+        Ok(())
+    }
+
+    /// The ranges of ERROR and MISSING nodes, where the source failed to parse
+    pub fn syntax_errors(&self) -> Vec<Range> {
+        if !self.tree.root_node().has_error() {
+            return vec![];
+        }
+        traverse(
+            CursorWrapper::new(self.tree.walk(), &self.source),
+            Order::Pre,
+        )
+        .filter(|n| n.node.is_error() || n.node.is_missing())
+        .map(|n| n.range())
+        .collect()
+    }
+}
+
+/// Tree-sitter points are zero-based rows with byte columns
+fn byte_to_point(source: &str, byte: usize) -> Point {
+    let before = &source[..byte];
+    let row = before.matches('\n').count();
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1);
+    Point::new(row as u32, column as u32)
 }
 
 impl Ast for Tree {
@@ -257,7 +318,11 @@ impl Parser for MarzanoParser {
         logs: &mut AnalysisLogs,
         old_tree: FileOrigin<'_, Tree>,
     ) -> Option<Tree> {
-        let tree = self.parser.parse(body, None).ok()??;
+        let previous_tree = old_tree
+            .previous_tree()
+            .filter(|tree| tree.source_map.is_none())
+            .map(|tree| &tree.tree);
+        let tree = self.parser.parse(body, previous_tree).ok()??;
 
         if let Some(path) = path {
             let mut errors = file_parsing_error(&tree, path, body, !old_tree.is_fresh()).ok()?;
@@ -661,9 +726,38 @@ mod tests {
         language::{MarzanoLanguage, MarzanoParser},
         tsx::Tsx,
     };
-    use grit_util::{Language, Parser};
+    use grit_util::{Ast, FileOrigin, Language, Parser};
     use trim_margin::MarginTrimmable;
 
+    #[test]
+    fn reparse_edited_tree() {
+        let lang = Tsx::new(None);
+        let mut parser = MarzanoParser::new(&lang);
+        let mut logs = vec![].into();
+        let mut tree = parser
+            .parse_file(
+                "const a = 1;\nfoo('bar');\n",
+                None,
+                &mut logs,
+                FileOrigin::Fresh,
+            )
+            .unwrap();
+        tree.edit(18, 21, "bazz").unwrap();
+        assert_eq!(tree.source, "const a = 1;\nfoo('bazz');\n");
+
+        let reparsed = parser
+            .parse_file(&tree.source, None, &mut logs, FileOrigin::Edited(&tree))
+            .unwrap();
+        let fresh = parser
+            .parse_file(&tree.source, None, &mut logs, FileOrigin::Fresh)
+            .unwrap();
+        assert_eq!(
+            reparsed.root_node().node.to_sexp(),
+            fresh.root_node().node.to_sexp()
+        );
+        assert!(tree.edit(22, 19, "").is_err());
+    }
+
     #[test]
     fn extract_nodes_from_snippet() {
         let pre = "class Pattern1a {\n  pattern0(param1) {";
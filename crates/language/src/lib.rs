@@ -59,6 +59,7 @@ macro_rules! use_marzano_delegate {
 pub mod csharp;
 pub mod css;
 pub mod elixir;
+mod embedded;
 pub mod foreign_language;
 pub mod go;
 pub mod grit_parser;
@@ -13,6 +13,7 @@ use grit_util::Order;
 use grit_util::{AnalysisLogs, SnippetTree};
 use marzano_util::cursor_wrapper::CursorWrapper;
 
+use crate::embedded::{is_embedding_host, parse_embedded, EmbeddedKind};
 use crate::sourcemap::EmbeddedSourceMap;
 use crate::sourcemap::SourceMapSection;
 use crate::sourcemap::SourceValueFormat;
@@ -195,7 +196,7 @@ fn is_magic_cell<'a>(lines: impl Iterator<Item = &'a str>) -> bool {
     })
 }
 
-/// Custom Python parser, to include notebooks
+/// Custom Python parser, to include notebooks and Markdown code blocks
 pub(crate) struct MarzanoNotebookParser {
     parser: MarzanoParser,
     language: &'static str,
@@ -415,6 +416,10 @@ impl grit_util::Parser for MarzanoNotebookParser {
             }
         }
 
+        if is_embedding_host(path, EmbeddedKind::Python) && old_tree.is_fresh() {
+            return parse_embedded(&mut self.parser.parser, body, path, EmbeddedKind::Python);
+        }
+
         self.parser.parse_file(body, path, logs, old_tree)
     }
 
@@ -1,9 +1,10 @@
 use anyhow::Result;
-use grit_util::ByteRange;
+use grit_util::{ByteRange, Range};
 use serde_json::json;
 
 /// A source map is used when the code we are parsing is embedded inside a larger file.
-/// For example, we want to focus on the Python code inside a Jupyter notebook.
+/// For example, we want to focus on the Python code inside a Jupyter notebook,
+/// or the `<script>` blocks inside an HTML page.
 #[derive(Debug, Clone)]
 pub struct EmbeddedSourceMap {
     sections: Vec<SourceMapSection>,
@@ -75,6 +76,46 @@ impl EmbeddedSourceMap {
         Ok(new_map)
     }
 
+    /// Map a byte offset in the inner document to the outer document.
+    /// Only raw sections can be mapped, since escaping shifts offsets inside the others.
+    pub fn outer_byte(&self, inner_byte: usize) -> Option<usize> {
+        let mut inner_start = 0;
+        for (index, section) in self.sections.iter().enumerate() {
+            let inner_end = section.inner_range_end - section.inner_end_trim;
+            // The trimmed content at the end of the document maps to the end of the last section
+            let is_last = index + 1 == self.sections.len();
+            if inner_byte >= inner_start
+                && (inner_byte <= inner_end || (is_last && inner_byte <= section.inner_range_end))
+            {
+                return match section.format {
+                    SourceValueFormat::Raw => {
+                        Some(section.outer_range.start + inner_byte.min(inner_end) - inner_start)
+                    }
+                    SourceValueFormat::String | SourceValueFormat::Array => None,
+                };
+            }
+            inner_start = section.inner_range_end;
+        }
+        None
+    }
+
+    /// Whether every section is inserted as-is, so positions can be mapped to the outer document
+    pub fn is_raw(&self) -> bool {
+        self.sections
+            .iter()
+            .all(|section| matches!(section.format, SourceValueFormat::Raw))
+    }
+
+    /// Map a range in the inner document to the outer document, with lines and columns of the outer document
+    pub fn outer_range(&self, range: &Range) -> Option<Range> {
+        let start = self.outer_byte(range.start_byte as usize)?;
+        let end = self.outer_byte(range.end_byte as usize)?;
+        Some(Range::from_byte_range(
+            &self.outer_source,
+            &ByteRange::new(start, end),
+        ))
+    }
+
     pub fn fill_with_inner(&self, new_inner_source: &str) -> Result<String> {
         let mut outer_source = self.outer_source.clone();
 
@@ -94,16 +135,16 @@ impl EmbeddedSourceMap {
                 new_inner_source.len()
             ))?;
 
-            let json = section.as_json(replacement_code);
+            let value = section.format_value(replacement_code);
 
             let outer_range = (section.outer_range.start as i32 + current_outer_offset) as usize
                 ..(section.outer_range.end as i32 + current_outer_offset) as usize;
 
-            let length_diff = json.len() as i32 - (outer_range.end - outer_range.start) as i32;
+            let length_diff = value.len() as i32 - (outer_range.end - outer_range.start) as i32;
             current_outer_offset += length_diff;
             current_inner_offset = section.inner_range_end;
 
-            outer_source.replace_range(outer_range, &json);
+            outer_source.replace_range(outer_range, &value);
         }
 
         Ok(outer_source)
@@ -122,12 +163,13 @@ pub struct SourceMapSection {
 }
 
 impl SourceMapSection {
-    pub fn as_json(&self, code: &str) -> String {
+    pub fn format_value(&self, code: &str) -> String {
         let structure = match self.format {
             SourceValueFormat::String => serde_json::Value::String(code.to_string()),
             SourceValueFormat::Array => {
                 json!(vec![code])
             }
+            SourceValueFormat::Raw => return code.to_string(),
         };
         structure.to_string()
     }
@@ -151,6 +193,8 @@ impl SourceMapSection {
 pub enum SourceValueFormat {
     String,
     Array,
+    /// The code is inserted as-is, like a `<script>` block inside HTML
+    Raw,
 }
 
 #[cfg(test)]
@@ -256,6 +300,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_outer_range_for_raw_sections() {
+        let outer = "<script>\nfoo();\n</script>\n<script>bar();</script>";
+        let mut source_map = EmbeddedSourceMap::new(outer);
+        source_map.new_section(8..16, 9, SourceValueFormat::Raw, 1);
+        source_map.new_section(34..40, 16, SourceValueFormat::Raw, 1);
+        let inner = "\nfoo();\n\nbar();\n";
+        assert_eq!(source_map.fill_with_inner(inner).unwrap(), outer);
+
+        let bar = Range::from_byte_range(inner, &ByteRange::new(9, 14));
+        let mapped = source_map.outer_range(&bar).unwrap();
+        assert_eq!(
+            &outer[mapped.start_byte as usize..mapped.end_byte as usize],
+            "bar()"
+        );
+        assert_eq!(mapped.start.line, 4);
+        assert_eq!(mapped.start.column, 9);
+        assert!(source_map.is_raw());
+
+        // The whole inner document spans both blocks
+        let program = Range::from_byte_range(inner, &ByteRange::new(0, inner.len()));
+        let mapped = source_map.outer_range(&program).unwrap();
+        assert_eq!(mapped.start_byte, 8);
+        assert_eq!(mapped.end_byte, 40);
+
+        let mut json_map = EmbeddedSourceMap::new(r#"["abcd"]"#);
+        json_map.new_section(1..7, 5, SourceValueFormat::String, 1);
+        assert!(json_map.outer_byte(1).is_none());
+    }
+
     #[test]
     fn test_five_sections_with_single_edit() {
         let mut source_map = EmbeddedSourceMap::new(r#"["abcd", "efgh", "zko", "znzo"]"#);
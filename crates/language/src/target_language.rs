@@ -288,10 +288,9 @@ impl PatternLanguage {
     fn get_file_extensions(&self) -> &'static [&'static str] {
         match self {
             PatternLanguage::JavaScript => &["js", "jsx", "cjs", "mjs", "vue"],
-            PatternLanguage::TypeScript => {
+            PatternLanguage::TypeScript | PatternLanguage::Tsx => {
                 &["js", "jsx", "ts", "tsx", "cjs", "mjs", "cts", "mts", "vue"]
             }
-            PatternLanguage::Tsx => &["js", "jsx", "ts", "tsx", "cjs", "mjs", "cts", "mts", "vue"],
             PatternLanguage::Html => &["html"],
             PatternLanguage::Css => &["css", "vue"],
             PatternLanguage::Json => &["json"],
@@ -317,6 +316,20 @@ impl PatternLanguage {
         }
     }
 
+    /// Extensions of files that embed this language, like script and style blocks in markup
+    /// and fenced code blocks in Markdown.
+    /// They are only used to find files, not as aliases for the language.
+    fn get_embedded_host_extensions(&self) -> &'static [&'static str] {
+        match self {
+            PatternLanguage::JavaScript
+            | PatternLanguage::TypeScript
+            | PatternLanguage::Tsx
+            | PatternLanguage::Css => &["html", "htm", "svelte", "astro", "md", "mdx"],
+            PatternLanguage::Python => &["md", "mdx"],
+            _ => &[],
+        }
+    }
+
     pub fn get_default_extension(&self) -> Option<&'static str> {
         match self {
             PatternLanguage::JavaScript => Some("js"),
@@ -376,6 +389,7 @@ impl PatternLanguage {
 
     pub fn match_extension(&self, ext: &str) -> bool {
         self.get_file_extensions().contains(&ext)
+            || self.get_embedded_host_extensions().contains(&ext)
     }
 
     // slightly inefficient but ensures the names are consistent
@@ -452,7 +466,11 @@ pub fn expand_paths(
                     }
                     PatternLanguage::Universal => {}
                     _ => {
-                        for ext in target_language.get_file_extensions() {
+                        for ext in target_language
+                            .get_file_extensions()
+                            .iter()
+                            .chain(target_language.get_embedded_host_extensions())
+                        {
                             file_types.add(ext, &format!("*.{}", ext)).unwrap();
                             file_types.select(ext);
                         }
@@ -948,4 +966,24 @@ mod tests {
             .to_module_language();
         assert_eq!(lang, PatternLanguage::Go);
     }
+
+    #[test]
+    fn embedding_hosts_are_not_language_aliases() {
+        assert_eq!(
+            PatternLanguage::from_str("html", true).unwrap(),
+            PatternLanguage::Html
+        );
+        assert!(PatternLanguage::Tsx.match_extension("html"));
+        assert!(PatternLanguage::Css.match_extension("svelte"));
+    }
+
+    #[test]
+    fn markdown_is_not_a_language_alias() {
+        assert_eq!(
+            PatternLanguage::from_str("md", true).unwrap(),
+            PatternLanguage::MarkdownBlock
+        );
+        assert!(PatternLanguage::Python.match_extension("md"));
+        assert!(PatternLanguage::Tsx.match_extension("mdx"));
+    }
 }
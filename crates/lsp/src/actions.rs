@@ -25,6 +25,7 @@ pub fn get_code_actions(
 ) -> Result<Vec<CodeActionOrCommand>> {
     let pattern_results = check_file(
         &document,
+        None,
         &check_info,
         #[cfg(feature = "caching")]
         cache,
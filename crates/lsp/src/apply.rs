@@ -1,5 +1,5 @@
-use grit_util::{FileRange, Range};
-use marzano_core::api::MatchResult;
+use grit_util::{format_trace, FileRange, Range};
+use marzano_core::api::{Explanation, MatchResult};
 use marzano_core::pattern_compiler::src_to_problem_libs;
 use marzano_gritmodule::patterns_directory::PatternsDirectory;
 use marzano_language::target_language::PatternLanguage;
@@ -107,6 +107,66 @@ pub async fn apply_pattern_body(
     }
 }
 
+/// Run a pattern against a document, recording how it was evaluated against the code that starts on `line`.
+/// The formatted trace is sent to the client's log.
+pub async fn explain_pattern_body(
+    document: &TextDocumentItem,
+    body: &str,
+    grit_files: PatternsDirectory,
+    lang: PatternLanguage,
+    line: u32,
+    client: &Client,
+) -> Option<Explanation> {
+    let file_path = match uri_to_file_path(document.uri.as_ref()) {
+        Ok(path) => path,
+        Err(e) => {
+            client.show_message(MessageType::ERROR, e).await;
+            return None;
+        }
+    };
+    let pattern_libs = match grit_files.get_language_directory_or_default(Some(lang)) {
+        Ok(lib) => lib,
+        Err(e) => {
+            client.show_message(MessageType::ERROR, e).await;
+            return None;
+        }
+    };
+    let problem = match src_to_problem_libs(
+        body.to_owned(),
+        &pattern_libs,
+        lang.try_into().unwrap(),
+        None,
+        None,
+        get_ai_built_in_functions_for_feature(),
+        None,
+    ) {
+        Ok(p) => p.problem,
+        Err(e) => {
+            client.show_message(MessageType::ERROR, e.to_string()).await;
+            return None;
+        }
+    };
+
+    let explanation = problem.explain_file(
+        &RichFile::new(
+            file_path.to_string_lossy().to_string(),
+            document.text.to_owned(),
+        ),
+        line,
+        &ExecutionContext::default(),
+    );
+    let message = if explanation.trace.is_empty() {
+        format!(
+            "The pattern was not evaluated against any code that starts on line {}.",
+            line
+        )
+    } else {
+        format_trace(&explanation.trace)
+    };
+    client.log_message(MessageType::INFO, message).await;
+    Some(explanation)
+}
+
 pub async fn apply_edits(
     text_edits: HashMap<Url, Vec<TextEdit>>,
     client: &Client,
@@ -12,7 +12,10 @@ use marzano_util::hasher::hash;
 use marzano_core::api::{EnforcementLevel, MatchResult};
 use marzano_core::pattern_compiler::{src_to_problem_libs, CompilationResult};
 use marzano_gritmodule::config::ResolvedGritDefinition;
-use marzano_language::target_language::{PatternLanguage, TargetLanguage};
+use marzano_language::{
+    language::Tree,
+    target_language::{PatternLanguage, TargetLanguage},
+};
 use marzano_util::rich_path::RichFile;
 use marzano_util::runtime::ExecutionContext;
 use tower_lsp::{lsp_types::TextDocumentItem, Client};
@@ -20,7 +23,7 @@ use tower_lsp::{lsp_types::TextDocumentItem, Client};
 use crate::{
     apply::apply_edits,
     language::language_id_to_pattern_language,
-    patterns::{get_grit_files_from_uri, resolve_from_uri},
+    patterns::{get_grit_files_from_uri, get_path_levels_from_uri, resolve_from_uri},
     util::{get_ai_built_in_functions_for_feature, rewrite_as_edit, uri_to_file_path},
 };
 
@@ -40,12 +43,18 @@ pub async fn get_check_info(document: &TextDocumentItem) -> Result<Option<CheckI
     };
     let patterns = resolve_from_uri(document.uri.as_ref(), Some(language), false).await;
     let grit_files = get_grit_files_from_uri(document.uri.as_ref(), false).await;
+    let path_levels = get_path_levels_from_uri(document.uri.as_ref()).await?;
 
+    // Patterns carry their level for this document, so diagnostics use it directly
     let enforced = patterns
         .into_iter()
-        .filter(|p| {
-            (matches!(&p.level(), EnforcementLevel::Error | EnforcementLevel::Warn))
-                && p.language.language_name() == language.language_name()
+        .filter_map(|mut p| {
+            if let Some((levels, file_path)) = &path_levels {
+                p.config.meta.level = Some(levels.level_for(&p, file_path));
+            }
+            ((matches!(&p.level(), EnforcementLevel::Error | EnforcementLevel::Warn))
+                && p.language.language_name() == language.language_name())
+            .then_some(p)
         })
         .collect::<Vec<_>>();
     let pattern_libs = grit_files.get_language_directory_or_default(Some(language))?;
@@ -57,8 +66,11 @@ pub async fn get_check_info(document: &TextDocumentItem) -> Result<Option<CheckI
     }))
 }
 
+/// Run the enforced patterns on a document.
+/// If `tree` is provided, it must match the document content and is reused instead of reparsing.
 pub fn check_file(
     document: &TextDocumentItem,
+    tree: Option<&Tree>,
     check_info: &CheckInfo,
     #[cfg(feature = "caching")] cache: &Option<Arc<RwLock<Cache>>>,
 ) -> Result<Vec<(ResolvedGritDefinition, Vec<MatchResult>)>> {
@@ -82,7 +94,11 @@ pub fn check_file(
             problem,
             compilation_warnings,
         } = src_to_problem_libs(
-            pattern.body.to_string(),
+            // Patterns with arguments from grit.yaml are called from the pattern libs
+            match pattern.config.args {
+                Some(_) => pattern.call(),
+                None => pattern.body.to_string(),
+            },
             pattern_libs,
             language,
             Some(pattern.local_name.to_string()),
@@ -103,13 +119,14 @@ pub fn check_file(
                 continue;
             }
         }
-        let execution_result = problem.execute_file(
-            &RichFile::new(
-                file_path.to_string_lossy().to_string(),
-                file_content.to_owned(),
-            ),
-            &context,
+        let file = RichFile::new(
+            file_path.to_string_lossy().to_string(),
+            file_content.to_owned(),
         );
+        let execution_result = match tree {
+            Some(tree) => problem.execute_edited_file(&file, tree, &context),
+            None => problem.execute_file(&file, &context),
+        };
         #[cfg(feature = "caching")]
         if let Some(cache) = &cache {
             if execution_result.is_empty() {
@@ -127,6 +144,7 @@ pub async fn fix_file(document: &TextDocumentItem, client: &Client) -> Result<bo
     let results = match get_check_info(document).await? {
         Some(info) => check_file(
             document,
+            None,
             &info,
             #[cfg(feature = "caching")]
             &None,
@@ -15,6 +15,7 @@ pub enum LspCommand {
     FixFile,
     SearchGritQL,
     PingGrit,
+    ExplainPattern,
 }
 
 impl LspCommand {
@@ -28,6 +29,7 @@ impl LspCommand {
             "grit.fixSelectedFile" => Some(Self::FixFile),
             "grit.searchGritQL" => Some(Self::SearchGritQL),
             "grit.ping" => Some(Self::PingGrit),
+            "grit.explainPattern" => Some(Self::ExplainPattern),
             _ => None,
         }
     }
@@ -44,6 +46,7 @@ impl fmt::Display for LspCommand {
             Self::FixFile => write!(f, "grit.fixSelectedFile"),
             Self::SearchGritQL => write!(f, "grit.searchGritQL"),
             Self::PingGrit => write!(f, "grit.ping"),
+            Self::ExplainPattern => write!(f, "grit.explainPattern"),
         }
     }
 }
@@ -0,0 +1,237 @@
+use grit_util::Ast;
+use marzano_core::{
+    api::{derive_log_level, AnalysisLog, AnalysisLogLevel},
+    constants::DEFAULT_FILE_NAME,
+    pattern_compiler::{src_to_problem_libs, CompilationResult},
+};
+use marzano_gritmodule::patterns_directory::PatternsDirectory;
+use marzano_language::{grit_parser::MarzanoGritParser, target_language::PatternLanguage};
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, NumberOrString, Range, TextDocumentItem,
+};
+
+use crate::util::{
+    byte_to_lsp_position, get_ai_built_in_functions_for_feature, grit_regions, uri_to_file_path,
+};
+
+/// Pattern files are `.grit` files, and markdown files in a `.grit/patterns` directory
+pub fn is_pattern_file(document: &TextDocumentItem) -> bool {
+    match document.language_id.as_str() {
+        "grit" => true,
+        "markdown" => uri_to_file_path(document.uri.as_ref())
+            .is_ok_and(|path| path.to_string_lossy().contains(".grit/patterns")),
+        _ => false,
+    }
+}
+
+/// Files that only hold definitions have no pattern to run, so give them one that never matches.
+/// This lets the compiler check the definitions without reporting a missing pattern.
+fn with_main_pattern(source: &str, parser: &mut MarzanoGritParser) -> String {
+    let has_pattern = parser
+        .parse(source)
+        .map(|tree| tree.root_node().child_by_field_name("pattern").is_some())
+        .unwrap_or(true);
+    if has_pattern {
+        source.to_string()
+    } else {
+        format!("{}\n.", source)
+    }
+}
+
+fn log_severity(log: &AnalysisLog) -> DiagnosticSeverity {
+    match derive_log_level(log) {
+        AnalysisLogLevel::Error => DiagnosticSeverity::ERROR,
+        AnalysisLogLevel::Warn => DiagnosticSeverity::WARNING,
+        AnalysisLogLevel::Info => DiagnosticSeverity::INFORMATION,
+        AnalysisLogLevel::Debug => DiagnosticSeverity::HINT,
+    }
+}
+
+fn floor_char_boundary(source: &str, byte: usize) -> usize {
+    let mut byte = byte.min(source.len());
+    while !source.is_char_boundary(byte) {
+        byte -= 1;
+    }
+    byte
+}
+
+/// Place a log from compiling `source`, which starts at `offset` in `text`.
+/// Logs from other pattern files are placed at the start of the source.
+fn log_range(log: &AnalysisLog, text: &str, offset: usize, source: &str) -> Range {
+    let (start, end) = if log.file.is_empty() || log.file == DEFAULT_FILE_NAME {
+        let start = floor_char_boundary(source, log.position.byte_index(source));
+        let end = log
+            .range
+            .map(|range| floor_char_boundary(source, range.end.byte_index(source)))
+            .unwrap_or(start)
+            .max(start);
+        (start, end)
+    } else {
+        (0, 0)
+    };
+    Range::new(
+        byte_to_lsp_position(text, offset + start),
+        byte_to_lsp_position(text, offset + end),
+    )
+}
+
+fn log_to_diagnostic(
+    log: AnalysisLog,
+    severity: DiagnosticSeverity,
+    text: &str,
+    offset: usize,
+    source: &str,
+) -> Diagnostic {
+    let message = if log.file.is_empty() || log.file == DEFAULT_FILE_NAME {
+        log.message.clone()
+    } else {
+        format!("{}: {}", log.file, log.message)
+    };
+    Diagnostic {
+        range: log_range(&log, text, offset, source),
+        severity: Some(severity),
+        code: Some(NumberOrString::Number(log.level.into())),
+        source: Some("grit".into()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Compile a single GritQL source, reporting the compiler error or warnings
+fn compile_source(
+    source: &str,
+    grit_files: &PatternsDirectory,
+    parser: &mut MarzanoGritParser,
+) -> Vec<(AnalysisLog, DiagnosticSeverity)> {
+    let language = PatternLanguage::get_language_with_parser(parser, source);
+    let libs = match grit_files.get_language_directory_or_default(language) {
+        Ok(libs) => libs,
+        Err(e) => {
+            return vec![(
+                AnalysisLog::floating_error(e.to_string()),
+                DiagnosticSeverity::ERROR,
+            )]
+        }
+    };
+    let compiled = src_to_problem_libs(
+        with_main_pattern(source, parser),
+        &libs,
+        language.and_then(|l| l.try_into().ok()).unwrap_or_default(),
+        None,
+        None,
+        get_ai_built_in_functions_for_feature(),
+        None,
+    );
+    match compiled {
+        Ok(CompilationResult {
+            compilation_warnings,
+            ..
+        }) => compilation_warnings
+            .logs()
+            .into_iter()
+            .map(|log| {
+                let log = AnalysisLog::from(log);
+                let severity = log_severity(&log);
+                (log, severity)
+            })
+            .collect(),
+        // Compilation stops at the first error, so there is only ever one
+        Err(e) => {
+            let log = match e.downcast::<grit_util::AnalysisLog>() {
+                Ok(log) => AnalysisLog::from(log),
+                Err(e) => AnalysisLog::floating_error(e.to_string()),
+            };
+            vec![(log, DiagnosticSeverity::ERROR)]
+        }
+    }
+}
+
+/// Compile every GritQL pattern in a pattern file, and report errors and warnings as diagnostics.
+/// Markdown files are compiled one `grit` code block at a time.
+pub fn get_compile_diagnostics(
+    document: &TextDocumentItem,
+    grit_files: &PatternsDirectory,
+) -> Vec<Diagnostic> {
+    let Ok(mut parser) = MarzanoGritParser::new() else {
+        return vec![];
+    };
+    let text = &document.text;
+    let markdown = document.language_id == "markdown";
+    let mut diagnostics = vec![];
+    for (start, end) in grit_regions(text, markdown) {
+        let source = &text[start..end];
+        if source.trim().is_empty() {
+            continue;
+        }
+        for (log, severity) in compile_source(source, grit_files, &mut parser) {
+            diagnostics.push(log_to_diagnostic(log, severity, text, start, source));
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grit_util::Position;
+    use tower_lsp::lsp_types;
+
+    fn log(level: u16, file: &str, start: (u32, u32), end: (u32, u32)) -> AnalysisLog {
+        let start = Position::new(start.0, start.1);
+        let end = Position::new(end.0, end.1);
+        AnalysisLog {
+            level,
+            message: "Warning: unused variable".to_string(),
+            position: start,
+            file: file.to_string(),
+            engine_id: "marzano".to_string(),
+            range: Some(grit_util::Range::new(start, end, 0, 0)),
+            syntax_tree: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn places_logs_inside_markdown_code_blocks() {
+        let text = "# Log it\n\n```grit\nlanguage js\n\n`console.log($msg)`\n```\n";
+        let (start, end) = grit_regions(text, true)[0];
+        let source = &text[start..end];
+        let diagnostic = log_to_diagnostic(
+            log(330, DEFAULT_FILE_NAME, (3, 14), (3, 18)),
+            DiagnosticSeverity::WARNING,
+            text,
+            start,
+            source,
+        );
+        assert_eq!(
+            diagnostic.range,
+            Range::new(
+                lsp_types::Position::new(5, 13),
+                lsp_types::Position::new(5, 17)
+            )
+        );
+        assert_eq!(diagnostic.code, Some(NumberOrString::Number(330)));
+    }
+
+    #[test]
+    fn places_logs_from_other_files_at_the_start() {
+        let text = "language js\n\nmy_pattern()\n";
+        let diagnostic = log_to_diagnostic(
+            log(300, "my_pattern.grit", (8, 2), (8, 5)),
+            DiagnosticSeverity::ERROR,
+            text,
+            0,
+            text,
+        );
+        assert_eq!(diagnostic.range, Range::default());
+        assert!(diagnostic.message.starts_with("my_pattern.grit: "));
+    }
+
+    #[test]
+    fn severity_follows_log_level() {
+        let level = |level| log_severity(&log(level, DEFAULT_FILE_NAME, (1, 1), (1, 1)));
+        assert_eq!(level(299), DiagnosticSeverity::ERROR);
+        assert_eq!(level(339), DiagnosticSeverity::WARNING);
+        assert_eq!(level(441), DiagnosticSeverity::INFORMATION);
+    }
+}
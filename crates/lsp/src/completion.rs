@@ -0,0 +1,169 @@
+use std::collections::BTreeSet;
+
+use marzano_core::built_in_functions::BuiltIns;
+use marzano_gritmodule::config::DefinitionKind;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Documentation, Position, Range,
+    TextEdit,
+};
+
+use crate::{
+    definition::{definition_signature, ResolvedIdentifier},
+    hover::built_in_signature,
+    util::convert_lsp_position_to_grit_position,
+};
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Lines that start a new scope for metavariables: top-level definitions and markdown code fences
+fn is_scope_boundary(line: &str) -> bool {
+    ["pattern ", "predicate ", "function ", "private ", "```"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// The region of the file around `offset` that metavariables are scoped to
+fn enclosing_scope(content: &str, offset: usize) -> (usize, usize) {
+    let mut start = 0;
+    let mut end = content.len();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        if is_scope_boundary(line) {
+            if line_start <= offset {
+                start = line_start;
+            } else {
+                end = line_start;
+                break;
+            }
+        }
+        line_start += line.len();
+    }
+    (start, end)
+}
+
+/// Collect the names of metavariables used in `content`, skipping the one being typed at `cursor`
+fn collect_metavariables(content: &str, cursor: usize) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut chars = content.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '$' {
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while let Some((j, c)) = chars.peek() {
+            if !is_identifier_char(*c) {
+                break;
+            }
+            end = j + c.len_utf8();
+            chars.next();
+        }
+        let name = &content[start..end];
+        if name.is_empty() || name == "_" || (start <= cursor && cursor <= end) {
+            continue;
+        }
+        names.insert(format!("${}", name));
+    }
+    names
+}
+
+fn definition_kind(kind: &DefinitionKind) -> CompletionItemKind {
+    match kind {
+        DefinitionKind::Pattern => CompletionItemKind::CLASS,
+        DefinitionKind::Predicate => CompletionItemKind::INTERFACE,
+        DefinitionKind::Function => CompletionItemKind::FUNCTION,
+    }
+}
+
+/// Complete metavariables in scope after a `$`, or pattern, predicate, and function names otherwise
+pub fn get_completions(
+    resolved: &ResolvedIdentifier,
+    position: &Position,
+    built_ins: &BuiltIns,
+) -> Vec<CompletionItem> {
+    let content = &resolved.document.text;
+    let cursor = convert_lsp_position_to_grit_position(position).byte_index(content);
+    let typed = content[..cursor]
+        .chars()
+        .rev()
+        .take_while(|c| is_identifier_char(*c))
+        .count();
+    let word_start = cursor - typed;
+
+    if content[..word_start].ends_with('$') {
+        let (scope_start, scope_end) = enclosing_scope(content, cursor);
+        let replace = Range::new(
+            Position::new(
+                position.line,
+                position.character.saturating_sub(typed as u32 + 1),
+            ),
+            *position,
+        );
+        return collect_metavariables(&content[scope_start..scope_end], cursor - scope_start)
+            .into_iter()
+            .map(|name| CompletionItem {
+                label: name.clone(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(replace, name))),
+                ..Default::default()
+            })
+            .collect();
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut items = vec![];
+    for definition in resolved.visible_definitions() {
+        if !seen.insert(definition.local_name.clone()) {
+            continue;
+        }
+        items.push(CompletionItem {
+            label: definition.local_name.clone(),
+            kind: Some(definition_kind(&definition.kind)),
+            detail: Some(definition_signature(definition)),
+            documentation: definition
+                .description()
+                .map(|d| Documentation::String(d.trim().to_string())),
+            ..Default::default()
+        });
+    }
+    for built_in in built_ins.get_built_ins() {
+        if !seen.insert(built_in.name.to_string()) {
+            continue;
+        }
+        items.push(CompletionItem {
+            label: built_in.name.to_string(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some(built_in_signature(built_in)),
+            documentation: Some(Documentation::String("Built-in function".to_string())),
+            ..Default::default()
+        });
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_metavariables_to_the_enclosing_definition() {
+        let content =
+            "pattern first($a) {\n  `foo($a, $b)`\n}\n\npattern second($c) {\n  `bar($c, $`\n}\n";
+        let cursor = content.rfind("$`").unwrap() + 1;
+        let (start, end) = enclosing_scope(content, cursor);
+        assert_eq!(start, content.find("pattern second").unwrap());
+        assert_eq!(end, content.len());
+        let names = collect_metavariables(&content[start..end], cursor - start);
+        assert_eq!(names.into_iter().collect::<Vec<_>>(), vec!["$c"]);
+    }
+
+    #[test]
+    fn skips_anonymous_and_partial_metavariables() {
+        let content = "`console.log($message, $_, $mess)`";
+        let cursor = content.find("$mess)").unwrap() + 5;
+        let names = collect_metavariables(content, cursor);
+        assert_eq!(names.into_iter().collect::<Vec<_>>(), vec!["$message"]);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::util::convert_lsp_position_to_grit_position;
+use marzano_gritmodule::config::{DefinitionKind, ResolvedGritDefinition};
+use marzano_language::target_language::PatternLanguage;
 use tower_lsp::lsp_types::{Position, TextDocumentItem};
 
 pub fn get_identifier(document: &TextDocumentItem, position: &Position) -> String {
@@ -13,3 +15,101 @@ pub fn get_identifier(document: &TextDocumentItem, position: &Position) -> Strin
         .unwrap_or(content.len());
     content[(start_offset + 1)..end_offset].to_string()
 }
+
+/// The identifier under the cursor in a pattern file, and the definitions visible from that file
+pub struct ResolvedIdentifier {
+    pub document: TextDocumentItem,
+    pub identifier: String,
+    pub definitions: Vec<ResolvedGritDefinition>,
+    /// The language of the patterns defined in the current file, if known
+    pub language: Option<PatternLanguage>,
+}
+
+impl ResolvedIdentifier {
+    /// Find the definition the identifier refers to, among those visible from the current file
+    pub fn definition(&self) -> Option<&ResolvedGritDefinition> {
+        self.definitions
+            .iter()
+            .find(|p| p.local_name == self.identifier && self.is_visible(p))
+    }
+
+    /// Definitions that can be referenced from the current file
+    pub fn visible_definitions(&self) -> impl Iterator<Item = &ResolvedGritDefinition> {
+        self.definitions.iter().filter(|p| self.is_visible(p))
+    }
+
+    fn is_visible(&self, definition: &ResolvedGritDefinition) -> bool {
+        self.language.is_none()
+            || self.language.is_some_and(|l| {
+                l.language_name() == definition.language.language_name()
+                    || matches!(definition.language, PatternLanguage::Universal)
+            })
+    }
+}
+
+pub fn kind_keyword(kind: &DefinitionKind) -> &'static str {
+    match kind {
+        DefinitionKind::Pattern => "pattern",
+        DefinitionKind::Predicate => "predicate",
+        DefinitionKind::Function => "function",
+    }
+}
+
+/// Extract the signature of a definition, such as `pattern foo($bar)`, from its body
+pub fn definition_signature(definition: &ResolvedGritDefinition) -> String {
+    let keyword = kind_keyword(&definition.kind);
+    let header = format!("{} {}", keyword, definition.local_name);
+    let signature = definition.body.find(&header).and_then(|start| {
+        let rest = &definition.body[start + header.len()..];
+        let rest = rest.trim_start();
+        if !rest.starts_with('(') {
+            return None;
+        }
+        rest.find(')')
+            .map(|end| format!("{}{}", header, &rest[..=end]))
+    });
+    signature.unwrap_or(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use marzano_gritmodule::config::{DefinitionSource, GritDefinitionConfig, GritUserConfig};
+
+    fn definition(name: &str, kind: DefinitionKind, body: &str) -> ResolvedGritDefinition {
+        ResolvedGritDefinition {
+            config: GritDefinitionConfig::default(),
+            module: DefinitionSource::Config(GritUserConfig {
+                path: "grit.yaml".into(),
+            }),
+            local_name: name.to_string(),
+            body: body.to_string(),
+            kind,
+            language: PatternLanguage::Tsx,
+            visibility: Default::default(),
+        }
+    }
+
+    #[test]
+    fn extracts_signatures() {
+        let def = definition(
+            "log_it",
+            DefinitionKind::Pattern,
+            "language js\n\npattern log_it($message, $level) {\n  `console.log($message)`\n}",
+        );
+        assert_eq!(
+            definition_signature(&def),
+            "pattern log_it($message, $level)"
+        );
+
+        let def = definition(
+            "is_empty",
+            DefinitionKind::Predicate,
+            "predicate is_empty() { $x <: . }",
+        );
+        assert_eq!(definition_signature(&def), "predicate is_empty()");
+
+        let def = definition("no_console", DefinitionKind::Pattern, "`console.log($_)`");
+        assert_eq!(definition_signature(&def), "pattern no_console");
+    }
+}
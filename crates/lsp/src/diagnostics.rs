@@ -1,22 +1,76 @@
 use std::path::Path;
 #[cfg(feature = "caching")]
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use anyhow::Result;
+use dashmap::DashMap;
 #[cfg(feature = "caching")]
 use grit_cache::cache::Cache;
 use marzano_core::{api::EnforcementLevel, fs::extract_ranges};
 
 use marzano_gritmodule::fetcher::ModuleRepo;
+use marzano_language::language::Tree;
 use tower_lsp::lsp_types::{CodeDescription, Diagnostic, DiagnosticSeverity, TextDocumentItem};
 
 use crate::{
     check::{check_file, CheckInfo},
+    documents::DocumentKey,
     util::convert_grit_range_to_lsp_range,
 };
 
+/// How long to wait after the last edit to a document before checking it
+pub const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Tracks the latest version of each open document, so that diagnostics
+/// are only computed once the user stops typing.
+#[derive(Debug)]
+pub struct DiagnosticsDebouncer {
+    delay: Duration,
+    latest: DashMap<DocumentKey, i32>,
+}
+
+impl DiagnosticsDebouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            latest: DashMap::new(),
+        }
+    }
+
+    /// Record a new version of the document, superseding any pending run
+    pub fn schedule(&self, uri: DocumentKey, version: i32) {
+        self.latest.insert(uri, version);
+    }
+
+    /// Wait out the debounce delay, then report whether `version` is still the latest one
+    pub async fn settle(&self, uri: &DocumentKey, version: i32) -> bool {
+        tokio::time::sleep(self.delay).await;
+        self.is_latest(uri, version)
+    }
+
+    pub fn is_latest(&self, uri: &DocumentKey, version: i32) -> bool {
+        self.latest.get(uri).is_some_and(|v| *v == version)
+    }
+
+    /// Forget a document, such as when it is closed, so pending runs are dropped
+    pub fn cancel(&self, uri: &DocumentKey) {
+        self.latest.remove(uri);
+    }
+}
+
+pub fn level_to_severity(level: &EnforcementLevel) -> DiagnosticSeverity {
+    match level {
+        EnforcementLevel::Error => DiagnosticSeverity::ERROR,
+        EnforcementLevel::Warn => DiagnosticSeverity::WARNING,
+        EnforcementLevel::Info => DiagnosticSeverity::INFORMATION,
+        EnforcementLevel::None => DiagnosticSeverity::HINT,
+    }
+}
+
 pub fn get_diagnostics(
     document: TextDocumentItem,
+    tree: Option<Tree>,
     check_info: CheckInfo,
     local_repo: &ModuleRepo,
     local_path: &Path,
@@ -24,6 +78,7 @@ pub fn get_diagnostics(
 ) -> Result<Vec<Diagnostic>> {
     let pattern_results = check_file(
         &document,
+        tree.as_ref(),
         &check_info,
         #[cfg(feature = "caching")]
         cache,
@@ -33,11 +88,7 @@ pub fn get_diagnostics(
         for result in pattern_result {
             let ranges = extract_ranges(&result).cloned().unwrap_or_default();
             for range in ranges {
-                let severity = match &pattern.level() {
-                    EnforcementLevel::Error => Some(DiagnosticSeverity::ERROR),
-                    EnforcementLevel::Warn => Some(DiagnosticSeverity::WARNING),
-                    _ => None,
-                };
+                let severity = Some(level_to_severity(&pattern.level()));
                 let url = pattern.url(local_repo, local_path);
                 let diagnostic = Diagnostic {
                     range: convert_grit_range_to_lsp_range(&range),
@@ -62,3 +113,37 @@ pub fn get_diagnostics(
 
     Ok(diagnostics)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_follows_enforcement_level() {
+        assert_eq!(
+            level_to_severity(&EnforcementLevel::Error),
+            DiagnosticSeverity::ERROR
+        );
+        assert_eq!(
+            level_to_severity(&EnforcementLevel::Warn),
+            DiagnosticSeverity::WARNING
+        );
+        assert_eq!(
+            level_to_severity(&EnforcementLevel::Info),
+            DiagnosticSeverity::INFORMATION
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn only_latest_version_settles() {
+        let debouncer = DiagnosticsDebouncer::new(Duration::from_millis(1));
+        let uri = "file:///test.js".to_string();
+        debouncer.schedule(uri.clone(), 1);
+        debouncer.schedule(uri.clone(), 2);
+        assert!(!debouncer.settle(&uri, 1).await);
+        assert!(debouncer.settle(&uri, 2).await);
+
+        debouncer.cancel(&uri);
+        assert!(!debouncer.settle(&uri, 2).await);
+    }
+}
@@ -1,7 +1,14 @@
 use std::collections::HashMap;
 
+use grit_util::{AnalysisLogs, FileOrigin};
+use marzano_language::{
+    language::{MarzanoLanguage, Tree},
+    target_language::TargetLanguage,
+};
 use tokio::sync::{mpsc, oneshot};
-use tower_lsp::lsp_types::TextDocumentItem;
+use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, TextDocumentItem};
+
+use crate::{language::language_id_to_pattern_language, util::lsp_position_to_byte};
 
 /// A document manager that handles all the documents in the workspace.
 /// The document manager goes on its own thread, so we don't have to deal with locking
@@ -10,6 +17,16 @@ pub type DocumentCommander = mpsc::Sender<DocumentAction>;
 pub type DocumentKey = String;
 type Responder = oneshot::Sender<Option<TextDocumentItem>>;
 
+/// The result of applying a set of incremental changes to a document
+#[derive(Debug)]
+pub struct EditedDocument {
+    pub document: TextDocumentItem,
+    /// The syntax tree for the new content, if the document language has one
+    pub tree: Option<Tree>,
+    /// Whether the content actually changed, so checks need to rerun
+    pub changed: bool,
+}
+
 #[derive(Debug)]
 pub enum DocumentAction {
     /// Upsert a full document into the document manager
@@ -18,11 +35,12 @@ pub enum DocumentAction {
     Get { uri: DocumentKey, resp: Responder },
     /// Drop a document from the document manager, such as when closing a file
     Drop { uri: DocumentKey },
-    /// Upsert content by key and return the old content
-    UpsertContent {
+    /// Apply incremental changes to a document and reparse it
+    Edit {
         uri: DocumentKey,
-        content: String,
-        resp: Responder,
+        version: i32,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        resp: oneshot::Sender<Option<EditedDocument>>,
     },
     /// Fetch a copy of all current documents
     GetAll {
@@ -35,11 +53,16 @@ pub fn run_doc_manager() -> (DocumentCommander, tokio::task::JoinHandle<()>) {
 
     let manager = tokio::spawn(async move {
         let mut document_map = HashMap::new();
+        let mut tree_map: HashMap<DocumentKey, Tree> = HashMap::new();
 
         while let Some(cmd) = rx.recv().await {
             match cmd {
                 DocumentAction::Upsert { document } => {
                     let uri = document.uri.to_string();
+                    match parse_document(&document, None) {
+                        Some(tree) => tree_map.insert(uri.clone(), tree),
+                        None => tree_map.remove(&uri),
+                    };
                     document_map.insert(uri, document);
                 }
                 DocumentAction::Get { uri, resp } => {
@@ -48,18 +71,38 @@ pub fn run_doc_manager() -> (DocumentCommander, tokio::task::JoinHandle<()>) {
                 }
                 DocumentAction::Drop { uri } => {
                     document_map.remove(&uri);
+                    tree_map.remove(&uri);
                 }
-                DocumentAction::UpsertContent { uri, content, resp } => {
-                    let doc = document_map.get_mut(&uri);
-                    match doc {
-                        Some(doc) => {
-                            doc.text = content;
-                            let _ = resp.send(Some(doc.clone()));
-                        }
-                        None => {
-                            let _ = resp.send(None);
-                        }
+                DocumentAction::Edit {
+                    uri,
+                    version,
+                    changes,
+                    resp,
+                } => {
+                    let Some(doc) = document_map.get_mut(&uri) else {
+                        let _ = resp.send(None);
+                        continue;
+                    };
+                    let old_text = doc.text.clone();
+                    let mut tree = tree_map.remove(&uri);
+                    for change in changes {
+                        tree = apply_change(&mut doc.text, tree, change);
                     }
+                    doc.version = version;
+                    let changed = doc.text != old_text;
+                    let tree = if changed {
+                        parse_document(doc, tree.as_ref())
+                    } else {
+                        tree
+                    };
+                    if let Some(tree) = &tree {
+                        tree_map.insert(uri, tree.clone());
+                    }
+                    let _ = resp.send(Some(EditedDocument {
+                        document: doc.clone(),
+                        tree,
+                        changed,
+                    }));
                 }
                 DocumentAction::GetAll { resp } => {
                     let docs = document_map.values().cloned().collect();
@@ -71,3 +114,89 @@ pub fn run_doc_manager() -> (DocumentCommander, tokio::task::JoinHandle<()>) {
 
     (tx, manager)
 }
+
+/// Parse a document, reusing `previous` if it has been edited to match the document content
+fn parse_document(document: &TextDocumentItem, previous: Option<&Tree>) -> Option<Tree> {
+    let language: TargetLanguage = language_id_to_pattern_language(&document.language_id)?
+        .try_into()
+        .ok()?;
+    let origin = previous.map_or(FileOrigin::Fresh, FileOrigin::Edited);
+    let tree = language.get_parser().parse_file(
+        &document.text,
+        None,
+        &mut AnalysisLogs::default(),
+        origin,
+    )?;
+    // Trees with a source map don't line up with the document, so they can't be edited
+    tree.source_map.is_none().then_some(tree)
+}
+
+/// Apply a single change to the document text, and edit the tree to match.
+/// The tree is dropped if it can't be edited, so it is never out of sync with the text.
+fn apply_change(
+    text: &mut String,
+    tree: Option<Tree>,
+    change: TextDocumentContentChangeEvent,
+) -> Option<Tree> {
+    let (start, end) = match change.range {
+        Some(range) => {
+            let start = lsp_position_to_byte(text, &range.start);
+            (start, lsp_position_to_byte(text, &range.end).max(start))
+        }
+        // A change without a range replaces the whole document
+        None => (0, text.len()),
+    };
+    text.replace_range(start..end, &change.text);
+    let mut tree = tree?;
+    tree.edit(start, end, &change.text).ok()?;
+    Some(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grit_util::Ast;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn applies_incremental_changes() {
+        let mut text = "const a = 1;\nconsole.log('héllo');\n".to_string();
+        apply_change(&mut text, None, change((1, 13), (1, 18), "bye"));
+        assert_eq!(text, "const a = 1;\nconsole.log('bye');\n");
+        apply_change(&mut text, None, change((0, 6), (0, 7), "answer"));
+        apply_change(&mut text, None, change((0, 15), (0, 16), "42"));
+        assert_eq!(text, "const answer = 42;\nconsole.log('bye');\n");
+        apply_change(&mut text, None, change((2, 0), (2, 0), "done();\n"));
+        assert_eq!(text, "const answer = 42;\nconsole.log('bye');\ndone();\n");
+    }
+
+    #[test]
+    fn edits_keep_tree_in_sync() {
+        let mut document = TextDocumentItem {
+            uri: "file:///test.ts".parse().unwrap(),
+            language_id: "typescript".to_string(),
+            version: 1,
+            text: "foo(1);\nbar(2);\n".to_string(),
+        };
+        let tree = parse_document(&document, None);
+        let tree = apply_change(&mut document.text, tree, change((1, 0), (1, 3), "baz")).unwrap();
+        assert_eq!(tree.source, document.text);
+        let edited = parse_document(&document, Some(&tree)).unwrap();
+        let fresh = parse_document(&document, None).unwrap();
+        assert_eq!(
+            edited.root_node().node.to_sexp(),
+            fresh.root_node().node.to_sexp()
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use marzano_core::built_in_functions::BuiltInFunction;
+use marzano_gritmodule::config::ResolvedGritDefinition;
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+
+use crate::definition::definition_signature;
+
+fn markdown_hover(signature: &str, details: Vec<String>) -> Hover {
+    let mut value = format!("```grit\n{}\n```", signature);
+    for detail in details {
+        value.push_str("\n\n");
+        value.push_str(&detail);
+    }
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: None,
+    }
+}
+
+/// Show the signature of a definition, with its title and description from the pattern metadata
+pub fn definition_hover(definition: &ResolvedGritDefinition) -> Hover {
+    let mut details = vec![];
+    if let Some(title) = definition.title() {
+        details.push(format!("**{}**", title.trim()));
+    }
+    if let Some(description) = definition.description() {
+        details.push(description.trim().to_string());
+    }
+    details.push(format!("Defined in `{}`", definition.module.short_name()));
+    markdown_hover(&definition_signature(definition), details)
+}
+
+pub fn built_in_signature(built_in: &BuiltInFunction) -> String {
+    let params = built_in
+        .params
+        .iter()
+        .map(|p| format!("${}", p))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("function {}({})", built_in.name, params)
+}
+
+pub fn built_in_hover(built_in: &BuiltInFunction) -> Hover {
+    markdown_hover(
+        &built_in_signature(built_in),
+        vec!["Built-in function".to_string()],
+    )
+}
@@ -2,14 +2,18 @@ mod actions;
 mod apply;
 mod check;
 mod commands;
+mod compile;
+mod completion;
 mod definition;
 mod diagnostics;
 mod documents;
 mod executor;
+mod hover;
 mod language;
 mod manager;
 mod notifications;
 mod patterns;
+mod rename;
 #[cfg(feature = "project_diagnostics")]
 mod scan;
 mod search;
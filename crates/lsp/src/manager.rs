@@ -7,11 +7,14 @@ use dashmap::DashMap;
 use marzano_gritmodule::fetcher::ModuleRepo;
 use tokio::sync::oneshot;
 use tower_lsp::{
-    lsp_types::{ClientCapabilities, ConfigurationItem, MessageType, TextDocumentItem, Url},
+    lsp_types::{
+        ClientCapabilities, ConfigurationItem, MessageType, TextDocumentContentChangeEvent,
+        TextDocumentItem, Url,
+    },
     Client,
 };
 
-use crate::documents::{DocumentAction, DocumentCommander, DocumentKey};
+use crate::documents::{DocumentAction, DocumentCommander, DocumentKey, EditedDocument};
 use crate::util::uri_to_file_path;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -69,18 +72,20 @@ impl GritServerManager {
         Ok(res)
     }
 
-    /// Fetch a document from the document manager if it exists
+    /// Apply incremental changes to a document in the document manager, if it exists
     /// This handles sending the error to the client as well
-    pub async fn maybe_upsert_document(
+    pub async fn maybe_edit_document(
         &self,
         client: &Client,
         uri: DocumentKey,
-        content: String,
-    ) -> Option<TextDocumentItem> {
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) -> Option<EditedDocument> {
         let (resp_tx, resp_rx) = oneshot::channel();
-        let cmd = DocumentAction::UpsertContent {
+        let cmd = DocumentAction::Edit {
             uri,
-            content,
+            version,
+            changes,
             resp: resp_tx,
         };
 
@@ -88,10 +93,7 @@ impl GritServerManager {
             Ok(_) => {}
             Err(e) => {
                 client
-                    .log_message(
-                        MessageType::ERROR,
-                        format!("error upserting document: {}", e),
-                    )
+                    .log_message(MessageType::ERROR, format!("error editing document: {}", e))
                     .await;
                 return None;
             }
@@ -101,10 +103,7 @@ impl GritServerManager {
             Ok(res) => res,
             Err(e) => {
                 client
-                    .log_message(
-                        MessageType::ERROR,
-                        format!("error upserting document: {}", e),
-                    )
+                    .log_message(MessageType::ERROR, format!("error editing document: {}", e))
                     .await;
                 None
             }
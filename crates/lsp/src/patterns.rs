@@ -2,11 +2,13 @@ use anyhow::{Context, Result};
 use marzano_gritmodule::{
     config::{get_stdlib_modules, ResolvedGritDefinition, REPO_CONFIG_DIR_NAME},
     fetcher::{KeepFetcherKind, ModuleRepo},
+    levels::PathLevels,
     patterns_directory::PatternsDirectory,
     resolver::{fetch_modules, get_grit_files, resolve_patterns},
-    searcher::find_grit_dir_from,
+    searcher::find_root_grit_dir_from,
 };
 use marzano_language::target_language::PatternLanguage;
+use std::path::PathBuf;
 
 use crate::util::uri_to_file_path;
 
@@ -15,7 +17,7 @@ pub async fn prep_grit_modules(
     fetch: bool,
 ) -> Result<(ModuleRepo, String, Option<Vec<ModuleRepo>>)> {
     let file_path = uri_to_file_path(uri).unwrap_or_else(|_| std::env::current_dir().unwrap());
-    let existing_config = find_grit_dir_from(file_path).await;
+    let existing_config = find_root_grit_dir_from(file_path).await;
     let stdlib_modules = get_stdlib_modules();
     let grit_parent = match existing_config {
         Some(config_path) => {
@@ -78,3 +80,70 @@ pub async fn get_grit_files_from_uri(uri: &str, fetch: bool) -> PatternsDirector
         Err(_) => PatternsDirectory::new(),
     }
 }
+
+/// The path-scoped levels that apply to the file at `uri`, if it is inside a repo with a grit config
+pub async fn get_path_levels_from_uri(uri: &str) -> Result<Option<(PathLevels, PathBuf)>> {
+    let Ok(file_path) = uri_to_file_path(uri) else {
+        return Ok(None);
+    };
+    let Some(grit_dir) = find_root_grit_dir_from(file_path.clone()).await else {
+        return Ok(None);
+    };
+    let Some(repo_dir) = grit_dir.parent() else {
+        return Ok(None);
+    };
+    let levels = PathLevels::load_for_file(repo_dir, &file_path)?;
+    Ok(Some((levels, file_path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use marzano_core::api::EnforcementLevel;
+    use tower_lsp::lsp_types::Url;
+
+    #[tokio::test]
+    async fn resolves_root_config_for_nested_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo_dir = temp_dir.path();
+        std::fs::create_dir_all(repo_dir.join(".git")).unwrap();
+        std::fs::create_dir_all(repo_dir.join(".grit")).unwrap();
+        std::fs::write(
+            repo_dir.join(".grit/grit.yaml"),
+            r#"version: 0.0.1
+patterns:
+  - name: no_console_log
+    level: warn
+    body: |
+      language js
+      `console.log($msg)`
+"#,
+        )
+        .unwrap();
+        let nested = repo_dir.join("packages/web");
+        std::fs::create_dir_all(nested.join(".grit")).unwrap();
+        std::fs::write(
+            nested.join(".grit/grit.yaml"),
+            "patterns:\n  - name: no_console_log\n    level: error\n",
+        )
+        .unwrap();
+        let file = nested.join("src/app.js");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "console.log('hello');\n").unwrap();
+        let uri = Url::from_file_path(&file).unwrap().to_string();
+
+        let (repo, parent_str, _) = prep_grit_modules(&uri, false).await.unwrap();
+        assert_eq!(PathBuf::from(&parent_str), repo_dir);
+        let (patterns, _) = resolve_patterns(&repo, &parent_str, None).await.unwrap();
+        let pattern = patterns
+            .iter()
+            .find(|p| p.local_name == "no_console_log")
+            .unwrap();
+
+        let (levels, file_path) = get_path_levels_from_uri(&uri).await.unwrap().unwrap();
+        assert_eq!(
+            levels.level_for(pattern, &file_path),
+            EnforcementLevel::Error
+        );
+    }
+}
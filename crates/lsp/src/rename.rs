@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Result};
+use marzano_gritmodule::{
+    config::{
+        DefinitionSource, ResolvedGritDefinition, REPO_CONFIG_DIR_NAME, REPO_CONFIG_PATTERNS_DIR,
+    },
+    fetcher::ModuleRepo,
+    utils::is_pattern_name,
+};
+use tower_lsp::lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    Range, RenameFile, ResourceOp, TextDocumentEdit, TextDocumentItem, TextEdit, Url,
+    WorkspaceEdit,
+};
+
+use crate::util::{byte_to_lsp_position, grit_regions, uri_to_file_path};
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Named arguments, like `name = $value`, refer to parameters rather than definitions
+fn is_named_argument(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.starts_with('=') && !rest.starts_with("==") && !rest.starts_with("=>")
+}
+
+/// Find references to `name` in GritQL source, skipping metavariables, snippets, strings, and comments
+fn find_references(content: &str, name: &str, markdown: bool) -> Vec<(usize, usize)> {
+    let mut references = vec![];
+    for (start, end) in grit_regions(content, markdown) {
+        let region = &content[start..end];
+        let mut chars = region.char_indices().peekable();
+        let mut previous = None;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '`' | '"' => {
+                    let mut escaped = false;
+                    for (_, s) in chars.by_ref() {
+                        if escaped {
+                            escaped = false;
+                        } else if s == '\\' {
+                            escaped = true;
+                        } else if s == c {
+                            break;
+                        }
+                    }
+                }
+                '/' if chars.peek().is_some_and(|(_, n)| *n == '/') => {
+                    for (_, s) in chars.by_ref() {
+                        if s == '\n' {
+                            break;
+                        }
+                    }
+                }
+                c if is_identifier_char(c)
+                    && !previous.is_some_and(|p: char| is_identifier_char(p) || p == '$') =>
+                {
+                    let mut word_end = i + c.len_utf8();
+                    while let Some((j, n)) = chars.peek() {
+                        if !is_identifier_char(*n) {
+                            break;
+                        }
+                        word_end = j + n.len_utf8();
+                        chars.next();
+                    }
+                    if &region[i..word_end] == name && !is_named_argument(&region[word_end..]) {
+                        references.push((start + i, start + word_end));
+                    }
+                    previous = Some('_');
+                    continue;
+                }
+                _ => {}
+            }
+            previous = Some(c);
+        }
+    }
+    references
+}
+
+/// Edits renaming every reference to `name` in a pattern file
+pub fn rename_edits(content: &str, name: &str, new_name: &str, markdown: bool) -> Vec<TextEdit> {
+    find_references(content, name, markdown)
+        .into_iter()
+        .map(|(start, end)| {
+            TextEdit::new(
+                Range::new(
+                    byte_to_lsp_position(content, start),
+                    byte_to_lsp_position(content, end),
+                ),
+                new_name.to_string(),
+            )
+        })
+        .collect()
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "md")
+}
+
+fn collect_pattern_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_pattern_files(&path, files);
+        } else if path.extension().is_some_and(|e| e == "grit" || e == "md") {
+            files.push(path);
+        }
+    }
+}
+
+/// Rename a pattern definition and every reference to it in the workspace's `.grit` and markdown files.
+/// Open documents are used in place of their content on disk.
+pub fn rename_definition(
+    definition: &ResolvedGritDefinition,
+    new_name: &str,
+    local_repo: &ModuleRepo,
+    local_path: &Path,
+    current: &TextDocumentItem,
+    open_documents: Vec<TextDocumentItem>,
+) -> Result<WorkspaceEdit> {
+    if !is_pattern_name(new_name) {
+        bail!("Invalid pattern name: {}", new_name);
+    }
+    if let DefinitionSource::Module(module) = &definition.module {
+        if module != local_repo {
+            bail!(
+                "Cannot rename {}, since it is defined in {}",
+                definition.local_name,
+                module.full_name
+            );
+        }
+    }
+    let definition_url = Url::parse(&definition.url(local_repo, local_path))?;
+    let definition_path = uri_to_file_path(definition_url.as_str())?;
+
+    let mut paths = vec![];
+    collect_pattern_files(
+        &local_path
+            .join(REPO_CONFIG_DIR_NAME)
+            .join(REPO_CONFIG_PATTERNS_DIR),
+        &mut paths,
+    );
+    let mut files: HashMap<PathBuf, Option<TextDocumentItem>> =
+        paths.into_iter().map(|path| (path, None)).collect();
+    for document in open_documents.into_iter().chain([current.clone()]) {
+        if let Ok(path) = uri_to_file_path(document.uri.as_str()) {
+            if path.extension().is_some_and(|e| e == "grit" || e == "md") {
+                files.insert(path, Some(document));
+            }
+        }
+    }
+
+    let mut operations = vec![];
+    for (path, document) in files {
+        let (uri, version, content) = match document {
+            Some(document) => (document.uri, Some(document.version), document.text),
+            None => match std::fs::read_to_string(&path) {
+                Ok(content) => (
+                    Url::from_file_path(&path)
+                        .map_err(|_| anyhow!("Invalid path {}", path.display()))?,
+                    None,
+                    content,
+                ),
+                Err(_) => continue,
+            },
+        };
+        let edits = rename_edits(
+            &content,
+            &definition.local_name,
+            new_name,
+            is_markdown(&path),
+        );
+        if edits.is_empty() {
+            continue;
+        }
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+            edits: edits.into_iter().map(OneOf::Left).collect(),
+        }));
+    }
+
+    // Markdown patterns are named after their file, so the file itself needs to move
+    if is_markdown(&definition_path) {
+        let new_path = definition_path.with_file_name(format!("{}.md", new_name));
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+            RenameFile {
+                old_uri: definition_url,
+                new_uri: Url::from_file_path(&new_path)
+                    .map_err(|_| anyhow!("Invalid path {}", new_path.display()))?,
+                options: None,
+                annotation_id: None,
+            },
+        )));
+    }
+
+    Ok(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_grit_references() {
+        let content = r#"pattern old_name($old_name) {
+  `old_name($old_name)` where {
+    $msg <: "old_name",
+    // old_name in a comment
+    $old_name <: old_name()
+  }
+}
+
+pattern uses_it() { old_name(old_name=.) }
+"#;
+        let edits = rename_edits(content, "old_name", "new_name", false);
+        let lines = edits
+            .iter()
+            .map(|e| (e.range.start.line, e.range.start.character))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![(0, 8), (4, 17), (8, 20)]);
+    }
+
+    #[test]
+    fn renames_only_in_grit_code_blocks() {
+        let content = r#"# old_name
+
+```grit
+language js
+
+old_name()
+```
+
+```js
+old_name()
+```
+"#;
+        let edits = rename_edits(content, "old_name", "new_name", true);
+        let lines = edits.iter().map(|e| e.range.start.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![5]);
+    }
+}
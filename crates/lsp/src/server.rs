@@ -2,7 +2,7 @@ use anyhow::bail;
 use anyhow::Context;
 
 use marzano_gritmodule::parser::extract_relative_path;
-use marzano_language::target_language::PatternLanguage;
+use marzano_language::{language::Tree, target_language::PatternLanguage};
 use serde_json::Value;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
@@ -10,8 +10,8 @@ use tower_lsp::{Client, LanguageServer};
 use tracing::instrument;
 
 use crate::actions::get_code_actions;
-use crate::apply::{apply_named_pattern, apply_pattern_body};
-use crate::check::{fix_file, get_check_info};
+use crate::apply::{apply_named_pattern, apply_pattern_body, explain_pattern_body};
+use crate::check::{fix_file, get_check_info, CheckInfo};
 use crate::commands::GritHighlightKind;
 use crate::commands::ShowGritHighlights;
 use crate::commands::ShowGritHighlightsRequest;
@@ -19,18 +19,23 @@ use crate::commands::{
     GritPongNotification, GritPongNotificationParams, LspCommand, ShowPatternSelector,
     ShowPatternSelectorParams,
 };
-use crate::definition::get_identifier;
-use crate::diagnostics::get_diagnostics;
-use crate::documents::run_doc_manager;
+use crate::compile::{get_compile_diagnostics, is_pattern_file};
+use crate::completion::get_completions;
+use crate::definition::{get_identifier, ResolvedIdentifier};
+use crate::diagnostics::{get_diagnostics, DiagnosticsDebouncer, DIAGNOSTICS_DEBOUNCE};
+use crate::documents::{run_doc_manager, EditedDocument};
 use crate::executor::IntenseExecutor;
+use crate::hover::{built_in_hover, definition_hover};
 use crate::language::language_id_to_pattern_language;
 use crate::manager::GritServerManager;
 use crate::patterns::{get_grit_files_from_uri, resolve_from_uri};
+use crate::rename::rename_definition;
 use crate::search::search_query;
 use crate::testing::maybe_test_pattern;
 use crate::util::uri_to_file_path;
 use crate::util::{
-    convert_grit_position_to_lsp_position, convert_lsp_range_to_grit_range, trim_one_match,
+    convert_grit_position_to_lsp_position, convert_lsp_range_to_grit_range, get_all_built_ins,
+    trim_one_match,
 };
 
 #[cfg(feature = "project_diagnostics")]
@@ -45,6 +50,7 @@ struct GritServer {
     client: Client,
     manager: GritServerManager,
     executor: IntenseExecutor,
+    diagnostics: DiagnosticsDebouncer,
     #[cfg(feature = "project_diagnostics")]
     watched_files: DashSet<String>,
 }
@@ -92,6 +98,52 @@ impl GritServer {
         Ok(())
     }
 
+    /// Find the identifier under the cursor in a pattern file, along with the definitions visible from it
+    async fn resolve_identifier(
+        &self,
+        params: &TextDocumentPositionParams,
+        fetch: bool,
+    ) -> Option<ResolvedIdentifier> {
+        let document = self
+            .manager
+            .maybe_get_document(&self.client, params.text_document.uri.to_string())
+            .await?;
+        match document.language_id.as_str() {
+            "markdown" | "grit" | "yaml" => {}
+            _ => {
+                return None;
+            }
+        }
+
+        let identifier = get_identifier(&document, &params.position);
+        let definitions = resolve_from_uri(document.uri.as_ref(), None, fetch).await;
+        let root_path = self.manager.get_root_path()?;
+        let our_path = match uri_to_file_path(document.uri.as_ref()) {
+            Ok(path) => {
+                let maybe_path = root_path.to_string_lossy().to_string();
+                let maybe_path = if maybe_path.starts_with("/var") {
+                    None
+                } else {
+                    Some(maybe_path)
+                };
+                extract_relative_path(&path.to_string_lossy(), &maybe_path)
+            }
+            Err(_) => {
+                return None;
+            }
+        };
+        let language = definitions
+            .iter()
+            .find(|p| p.config.path == our_path)
+            .map(|p| p.language);
+        Some(ResolvedIdentifier {
+            document,
+            identifier,
+            definitions,
+            language,
+        })
+    }
+
     async fn compute_search(&self, query: String) -> anyhow::Result<()> {
         let documents = self.manager.must_get_documents(&self.client).await?;
         let (errors, results) = self
@@ -148,24 +200,15 @@ impl GritServer {
         Ok(Some(actions))
     }
 
-    async fn on_change(&self, params: &TextDocumentItem) -> anyhow::Result<()> {
-        if params.uri.as_str().contains("node_modules") {
-            self.client
-                .log_message(
-                    MessageType::LOG,
-                    format!("Skipping file {} in node_modules", params.uri),
-                )
-                .await;
-            return Ok(());
-        }
-        let check_info = match get_check_info(params).await? {
-            Some(info) => info,
-            None => return Ok(()),
-        };
-
+    /// Run the enforced patterns on a document, and report the matches as diagnostics
+    async fn check_diagnostics(
+        &self,
+        document: &TextDocumentItem,
+        tree: Option<Tree>,
+        check_info: CheckInfo,
+    ) -> anyhow::Result<Vec<Diagnostic>> {
         // TODO: remove these clones
-        let check_clone = check_info.clone();
-        let doc_clone = params.clone();
+        let doc_clone = document.clone();
         let Some(our_repo) = self.manager.get_root_module().await else {
             self.client
                 .log_message(
@@ -173,7 +216,7 @@ impl GritServer {
                     "No repo found when checking document".to_string(),
                 )
                 .await;
-            return Ok(());
+            return Ok(vec![]);
         };
         let Some(our_path) = self.manager.get_root_path() else {
             self.client
@@ -182,7 +225,7 @@ impl GritServer {
                     "No path found when checking document".to_string(),
                 )
                 .await;
-            return Ok(());
+            return Ok(vec![]);
         };
 
         let diagnostics = self
@@ -190,7 +233,8 @@ impl GritServer {
             .spawn(move || {
                 get_diagnostics(
                     doc_clone,
-                    check_clone,
+                    tree,
+                    check_info,
                     &our_repo,
                     &our_path,
                     #[cfg(feature = "caching")]
@@ -198,6 +242,38 @@ impl GritServer {
                 )
             })
             .await??;
+        Ok(diagnostics)
+    }
+
+    async fn on_change(&self, params: &TextDocumentItem, tree: Option<Tree>) -> anyhow::Result<()> {
+        if params.uri.as_str().contains("node_modules") {
+            self.client
+                .log_message(
+                    MessageType::LOG,
+                    format!("Skipping file {} in node_modules", params.uri),
+                )
+                .await;
+            return Ok(());
+        }
+        let check_info = get_check_info(params).await?;
+        let is_pattern_file = is_pattern_file(params);
+        if check_info.is_none() && !is_pattern_file {
+            return Ok(());
+        }
+
+        let mut diagnostics = match check_info {
+            Some(check_info) => self.check_diagnostics(params, tree, check_info).await?,
+            None => vec![],
+        };
+        if is_pattern_file {
+            let grit_files = get_grit_files_from_uri(params.uri.as_ref(), false).await;
+            let document = params.clone();
+            let compile_diagnostics = self
+                .executor
+                .spawn(move || get_compile_diagnostics(&document, &grit_files))
+                .await?;
+            diagnostics.extend(compile_diagnostics);
+        }
 
         self.client
             .log_message(
@@ -207,7 +283,7 @@ impl GritServer {
             .await;
 
         self.client
-            .publish_diagnostics(params.uri.to_owned(), diagnostics, None)
+            .publish_diagnostics(params.uri.to_owned(), diagnostics, Some(params.version))
             .await;
 
         match maybe_test_pattern(&self.client, &self.manager, params).await {
@@ -242,8 +318,14 @@ impl LanguageServer for GritServer {
             capabilities: ServerCapabilities {
                 code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["$".to_string()]),
+                    ..Default::default()
+                }),
+                rename_provider: Some(OneOf::Left(true)),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 execute_command_provider: Some(ExecuteCommandOptions {
                     commands: vec![
@@ -254,6 +336,7 @@ impl LanguageServer for GritServer {
                         LspCommand::ApplyResult.to_string(),
                         LspCommand::FixFile.to_string(),
                         LspCommand::PingGrit.to_string(),
+                        LspCommand::ExplainPattern.to_string(),
                     ],
                     ..Default::default()
                 }),
@@ -301,7 +384,11 @@ impl LanguageServer for GritServer {
 
     #[instrument(skip(self, params), fields(path = params.text_document.uri.to_string()))]
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        match self.on_change(&params.text_document).await {
+        self.diagnostics.schedule(
+            params.text_document.uri.to_string(),
+            params.text_document.version,
+        );
+        match self.on_change(&params.text_document, None).await {
             Ok(_) => {}
             Err(e) => {
                 self.client
@@ -319,34 +406,45 @@ impl LanguageServer for GritServer {
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let DidChangeTextDocumentParams {
             text_document,
-            mut content_changes,
+            content_changes,
         } = params;
-        let uri = text_document.uri;
-        let new_content = match content_changes.pop() {
-            // Doing this is safe, since we specify `TextDocumentSyncKind::FULL` in `initialize`.
-            Some(change) => change.text,
-            None => {
-                self.client
-                    .log_message(MessageType::ERROR, "no content chang found")
-                    .await;
-                return;
-            }
-        };
+        let VersionedTextDocumentIdentifier { uri, version } = text_document;
+        if content_changes.is_empty() {
+            self.client
+                .log_message(MessageType::ERROR, "no content change found")
+                .await;
+            return;
+        }
 
-        let Some(document) = self
+        let Some(EditedDocument {
+            document,
+            tree,
+            changed,
+        }) = self
             .manager
-            .maybe_upsert_document(&self.client, uri.into(), new_content)
+            .maybe_edit_document(&self.client, uri.to_string(), content_changes, version)
             .await
         else {
             return;
         };
+        // Only rerun checks on documents whose content was actually edited
+        if !changed {
+            return;
+        }
+
+        // Only check the document once edits have settled
+        let key = uri.to_string();
+        self.diagnostics.schedule(key.clone(), version);
+        if !self.diagnostics.settle(&key, version).await {
+            return;
+        }
         #[cfg(feature = "grit_tracing")]
         {
             tracing::Span::current().record("extension", &extension);
             tracing::Span::current().record("language", language.to_string());
         }
 
-        match self.on_change(&document).await {
+        match self.on_change(&document, tree).await {
             Ok(_) => {}
             Err(e) => {
                 self.client
@@ -406,73 +504,24 @@ impl LanguageServer for GritServer {
         &self,
         params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
-        let document = match self
-            .manager
-            .must_get_document(
-                &self.client,
-                params
-                    .text_document_position_params
-                    .text_document
-                    .uri
-                    .to_string(),
-            )
-            .await
-        {
-            Ok(doc) => doc,
-            Err(_e) => {
-                return Ok(None);
-            }
-        };
-        match document.language_id.as_str() {
-            "markdown" | "grit" | "yaml" => {}
-            _ => {
-                return Ok(None);
-            }
-        }
-
-        let position = params.text_document_position_params.position;
-        let identifier = get_identifier(&document, &position);
-
         #[cfg(feature = "grit_tracing")]
         {
+            let position = params.text_document_position_params.position;
             tracing::Span::current().record("uri", &uri);
             tracing::Span::current().record("position", format!("{position:?}"));
         }
 
-        let resolved = resolve_from_uri(document.uri.as_ref(), None, true).await;
-        let Some(root_path) = self.manager.get_root_path() else {
+        let Some(resolved) = self
+            .resolve_identifier(&params.text_document_position_params, true)
+            .await
+        else {
             return Ok(None);
         };
-        let our_path = match uri_to_file_path(document.uri.as_ref()) {
-            Ok(path) => {
-                let maybe_path = root_path.to_string_lossy().to_string();
-                let maybe_path = if maybe_path.starts_with("/var") {
-                    None
-                } else {
-                    Some(maybe_path)
-                };
-                extract_relative_path(&path.to_string_lossy(), &maybe_path)
-            }
-            Err(_) => {
-                return Ok(None);
-            }
+        let Some(definition) = resolved.definition() else {
+            return Ok(None);
         };
-        let our_language = resolved
-            .iter()
-            .find(|p| p.config.path == our_path)
-            .map(|p| &p.language);
-        let definition = match resolved.iter().find(|p| {
-            p.local_name == identifier
-                && (our_language.is_none()
-                    || our_language.is_some_and(|l| {
-                        l.language_name() == p.language.language_name()
-                            || matches!(p.language, PatternLanguage::Universal)
-                    }))
-        }) {
-            Some(definition) => definition,
-            None => {
-                return Ok(None);
-            }
+        let Some(root_path) = self.manager.get_root_path() else {
+            return Ok(None);
         };
         let Some(our_repo) = self.manager.get_root_module().await else {
             return Ok(None);
@@ -493,10 +542,84 @@ impl LanguageServer for GritServer {
         })))
     }
 
+    #[instrument(skip(self, params))]
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let Some(resolved) = self
+            .resolve_identifier(&params.text_document_position_params, false)
+            .await
+        else {
+            return Ok(None);
+        };
+        if let Some(definition) = resolved.definition() {
+            return Ok(Some(definition_hover(definition)));
+        }
+        let built_ins = get_all_built_ins();
+        Ok(built_ins
+            .get_built_ins()
+            .iter()
+            .find(|b| b.name == resolved.identifier)
+            .map(built_in_hover))
+    }
+
+    #[instrument(skip(self, params))]
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let Some(resolved) = self
+            .resolve_identifier(&params.text_document_position, false)
+            .await
+        else {
+            return Ok(None);
+        };
+        let items = get_completions(
+            &resolved,
+            &params.text_document_position.position,
+            &get_all_built_ins(),
+        );
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    #[instrument(skip(self, params))]
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let Some(resolved) = self
+            .resolve_identifier(&params.text_document_position, false)
+            .await
+        else {
+            return Ok(None);
+        };
+        let Some(definition) = resolved.definition() else {
+            return Ok(None);
+        };
+        let Some(root_path) = self.manager.get_root_path() else {
+            return Ok(None);
+        };
+        let Some(our_repo) = self.manager.get_root_module().await else {
+            return Ok(None);
+        };
+        let open_documents = self
+            .manager
+            .must_get_documents(&self.client)
+            .await
+            .unwrap_or_default();
+        match rename_definition(
+            definition,
+            &params.new_name,
+            &our_repo,
+            &root_path,
+            &resolved.document,
+            open_documents,
+        ) {
+            Ok(edit) => Ok(Some(edit)),
+            Err(e) => Err(tower_lsp::jsonrpc::Error::invalid_params(e.to_string())),
+        }
+    }
+
     #[instrument(skip(self, params), fields(path = params.text_document.uri.to_string()))]
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri_string = params.text_document.uri.to_string();
+        self.diagnostics.cancel(&uri_string);
         let _ = self.manager.drop_document(uri_string).await;
+        self.client
+            .publish_diagnostics(params.text_document.uri, vec![], None)
+            .await;
     }
 
     #[instrument(skip(self), fields(command=params.command))]
@@ -646,6 +769,36 @@ impl LanguageServer for GritServer {
                         }
                     };
                 }
+                LspCommand::ExplainPattern => {
+                    let (Some(line), Some(body), Some(uri)) = (args.pop(), args.pop(), args.pop())
+                    else {
+                        return Ok(None);
+                    };
+                    let Ok(line) = line.parse::<u32>() else {
+                        self.client
+                            .show_message(MessageType::ERROR, format!("Invalid line {}", line))
+                            .await;
+                        return Ok(None);
+                    };
+                    let grit_files = get_grit_files_from_uri(&uri, true).await;
+                    let lang = PatternLanguage::get_language(&body).unwrap_or_default();
+                    let document = match self.manager.must_get_document(&self.client, uri).await {
+                        Ok(doc) => doc,
+                        Err(_) => {
+                            return Ok(None);
+                        }
+                    };
+                    let explanation = explain_pattern_body(
+                        &document,
+                        &body,
+                        grit_files,
+                        lang,
+                        line,
+                        &self.client,
+                    )
+                    .await;
+                    return Ok(explanation.and_then(|e| serde_json::to_value(e).ok()));
+                }
             }
         }
         Ok(None)
@@ -677,6 +830,7 @@ pub async fn start_language_server() -> anyhow::Result<()> {
         client,
         manager: GritServerManager::new(docs),
         executor: IntenseExecutor::new(),
+        diagnostics: DiagnosticsDebouncer::new(DIAGNOSTICS_DEBOUNCE),
         #[cfg(feature = "project_diagnostics")]
         watched_files: DashSet::new(),
     });
@@ -709,6 +863,7 @@ mod test {
             client,
             manager: GritServerManager::new(docs),
             executor: IntenseExecutor::new(),
+            diagnostics: DiagnosticsDebouncer::new(DIAGNOSTICS_DEBOUNCE),
             #[cfg(feature = "project_diagnostics")]
             watched_files: DashSet::new(),
         });
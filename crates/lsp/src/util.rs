@@ -124,6 +124,63 @@ pub fn check_intersection(range1: &Range, range2: &Range) -> bool {
         && range1.end.character >= range2.start.character
 }
 
+/// Convert an LSP position to a byte offset, clamping to the end of the line.
+/// LSP positions count UTF-16 code units by default.
+pub fn lsp_position_to_byte(text: &str, position: &Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// Convert a byte offset to an LSP position, counting UTF-16 code units
+pub fn byte_to_lsp_position(text: &str, byte: usize) -> Position {
+    let before = &text[..byte];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count();
+    let character = before[line_start..].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// The regions of a file that contain GritQL: the whole file, or `grit` code blocks in markdown
+pub fn grit_regions(content: &str, markdown: bool) -> Vec<(usize, usize)> {
+    if !markdown {
+        return vec![(0, content.len())];
+    }
+    let mut regions = vec![];
+    // The start of the current grit block, or None when in another kind of code block
+    let mut block: Option<Option<usize>> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if let Some(info) = line.trim().strip_prefix("```") {
+            block = match block {
+                Some(Some(start)) => {
+                    regions.push((start, offset));
+                    None
+                }
+                Some(None) => None,
+                None if info.trim() == "grit" => Some(Some(offset + line.len())),
+                None => Some(None),
+            };
+        }
+        offset += line.len();
+    }
+    regions
+}
+
 pub(crate) fn get_ai_built_in_functions_for_feature() -> Option<BuiltIns> {
     #[cfg(not(feature = "ai_builtins"))]
     return marzano_core::built_in_functions::get_ai_placeholder_functions();
@@ -131,11 +188,40 @@ pub(crate) fn get_ai_built_in_functions_for_feature() -> Option<BuiltIns> {
     return Some(ai_builtins::ai_builtins::get_ai_built_in_functions());
 }
 
+/// All the built-in functions available to patterns, including AI built-ins if enabled
+pub(crate) fn get_all_built_ins() -> BuiltIns {
+    let mut built_ins = BuiltIns::get_built_in_functions();
+    if let Some(ai_built_ins) = get_ai_built_in_functions_for_feature() {
+        // The AI built-ins never overlap with the standard ones
+        let _ = built_ins.extend_builtins(ai_built_ins);
+    }
+    built_ins
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
+    #[test]
+    fn counts_utf16_code_units() {
+        let text = "let 😀 = x;";
+        assert_eq!(lsp_position_to_byte(text, &Position::new(0, 6)), 8);
+        assert_eq!(
+            lsp_position_to_byte(text, &Position::new(0, 100)),
+            text.len()
+        );
+        assert_eq!(lsp_position_to_byte(text, &Position::new(5, 0)), text.len());
+    }
+
+    #[test]
+    fn byte_to_utf16_position() {
+        let text = "a\nlet 😀 = x;";
+        assert_eq!(byte_to_lsp_position(text, 0), Position::new(0, 0));
+        assert_eq!(byte_to_lsp_position(text, 10), Position::new(1, 6));
+        assert_eq!(lsp_position_to_byte(text, &Position::new(1, 6)), 10);
+    }
+
     #[test]
     fn zero_offset_to_position() {
         let content = "function foo() {\n    return 1;\n}\n";
@@ -1,23 +1,32 @@
 use anyhow::Result;
 use std::future::Future;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    path::Path,
     sync::atomic::{AtomicI32, Ordering},
 };
 
 use clap::ValueEnum;
 use colored::Colorize;
-use dialoguer::Input;
+use dialoguer::{Editor, Input};
 use indicatif::ProgressBar;
 use log::info;
 use marzano_core::{
-    api::{derive_log_level, is_match, AnalysisLog, AnalysisLogLevel, MatchResult},
+    api::{
+        derive_log_level, is_match, AnalysisLog, AnalysisLogLevel, MatchResult, Rewrite,
+        INVALID_REWRITE_LOG_LEVEL,
+    },
     fs::apply_rewrite,
 };
 use marzano_language::target_language::TargetLanguage;
 use serde::{Deserialize, Serialize};
 
-use crate::{format::format_result, workflows::PackagedWorkflowOutcome, SimpleLogMessage};
+use crate::{
+    format::format_result,
+    hunks::{merge_hunks, split_hunks, Hunk},
+    workflows::PackagedWorkflowOutcome,
+    SimpleLogMessage,
+};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct ApplyDetails {
@@ -25,6 +34,82 @@ pub struct ApplyDetails {
     pub matched: i32,
     pub rewritten: i32,
     pub named_pattern: Option<String>,
+    /// How many rewrites introduced syntax errors
+    #[serde(default)]
+    pub invalid_rewrites: i32,
+}
+
+/// Decisions that carry over between results while applying interactively
+#[derive(Debug, Default)]
+pub struct InteractiveState {
+    /// Whether to ask before applying each change
+    pub enabled: bool,
+    /// Patterns whose remaining changes are accepted without asking
+    accepted_patterns: HashSet<String>,
+}
+
+impl InteractiveState {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            accepted_patterns: HashSet::new(),
+        }
+    }
+
+    fn should_ask(&self, pattern: &str) -> bool {
+        self.enabled && !self.accepted_patterns.contains(pattern)
+    }
+}
+
+const HUNK_ACTIONS: &str =
+    "[(y)es,(n)o,(e)dit,(s)uppress,(f) skip file,(p) accept pattern,(a)ccept all,(q)uit]";
+
+/// The pattern a result came from, so all of its changes can be accepted at once
+fn pattern_key(result: &MatchResult, details: &ApplyDetails) -> String {
+    let reason = match result {
+        MatchResult::Match(m) => m.reason.as_ref(),
+        MatchResult::Rewrite(r) => r.reason.as_ref(),
+        _ => None,
+    };
+    reason
+        .and_then(|r| r.name.clone())
+        .or_else(|| details.named_pattern.clone())
+        .unwrap_or_default()
+}
+
+/// Ask the user to pick one of `valid_chars`
+fn prompt_choice(question: &str, actions: &str, valid_chars: &[&str]) -> Result<String> {
+    let actions_bold = actions.bold().blue();
+    let selection = Input::new()
+        .with_prompt(format!("{question} {actions_bold}"))
+        .validate_with(|input: &String| -> Result<(), String> {
+            if valid_chars.contains(
+                &input
+                    .chars()
+                    .next()
+                    .unwrap_or('_')
+                    .to_lowercase()
+                    .to_string()
+                    .as_str(),
+            ) {
+                Ok(())
+            } else {
+                Err(format!("Not a valid choice in {actions:}"))
+            }
+        })
+        .interact_text()?;
+    Ok(selection.trim().to_lowercase())
+}
+
+/// A copy of `rewrite` with only one hunk applied, replaced by `text`
+fn hunk_rewrite(rewrite: &Rewrite, hunks: &[Hunk], index: usize, text: &str) -> MatchResult {
+    let original = rewrite.original.content.as_deref().unwrap_or_default();
+    let mut replacements = vec![None; hunks.len()];
+    replacements[index] = Some(text.to_string());
+    let mut hunk_rewrite = rewrite.clone();
+    hunk_rewrite.rewritten.content = Some(merge_hunks(original, hunks, &replacements));
+    hunk_rewrite.rewritten.byte_ranges = None;
+    MatchResult::Rewrite(hunk_rewrite)
 }
 
 pub trait Messager: Send + Sync {
@@ -65,7 +150,7 @@ pub trait Messager: Send + Sync {
         details: &mut ApplyDetails,
         dry_run: bool,
         should_format: bool,
-        interactive: &mut bool,
+        interactive: &mut InteractiveState,
         pg: Option<&ProgressBar>,
         processed: Option<&AtomicI32>,
         parse_errors: Option<&mut HashMap<String, usize>>,
@@ -99,7 +184,7 @@ pub trait Messager: Send + Sync {
         details: &mut ApplyDetails,
         dry_run: bool,
         should_format: bool,
-        interactive: &mut bool,
+        interactive: &mut InteractiveState,
         pg: Option<&ProgressBar>,
         processed: Option<&AtomicI32>,
         mut parse_errors: Option<&mut HashMap<String, usize>>,
@@ -110,8 +195,10 @@ pub trait Messager: Send + Sync {
                 let count = r.get_ranges().map(|ranges| ranges.len()).unwrap_or(0);
                 details.matched += count.max(1) as i32;
             }
-            if let MatchResult::Rewrite(_) = r {
-                details.rewritten += 1;
+            if let MatchResult::AnalysisLog(log) = &r {
+                if log.level == INVALID_REWRITE_LOG_LEVEL {
+                    details.invalid_rewrites += 1;
+                }
             }
 
             if let MatchResult::DoneFile(_) = r {
@@ -145,6 +232,40 @@ pub trait Messager: Send + Sync {
                 }
             }
 
+            // Rewrites of a file in place are reviewed hunk by hunk
+            if let MatchResult::Rewrite(rewrite) = &r {
+                let pattern = pattern_key(&r, details);
+                if !dry_run
+                    && interactive.should_ask(&pattern)
+                    && rewrite.original.source_file == rewrite.rewritten.source_file
+                {
+                    let (kept, keep_going) = self.review_hunks(
+                        &r,
+                        rewrite,
+                        &pattern,
+                        interactive,
+                        pg,
+                        details,
+                        language,
+                    )?;
+                    if let Some(kept) = kept {
+                        details.rewritten += 1;
+                        self.emit(&kept)?;
+                        self.apply_rewrite(&kept)?;
+                        if should_format {
+                            format_result(kept)?;
+                        }
+                    }
+                    if !keep_going {
+                        return Ok(false);
+                    }
+                    continue;
+                }
+            }
+
+            if let MatchResult::Rewrite(_) = r {
+                details.rewritten += 1;
+            }
             self.emit(&r)?;
 
             if !dry_run {
@@ -152,7 +273,7 @@ pub trait Messager: Send + Sync {
                     let file_name = r
                         .file_name()
                         .ok_or_else(|| anyhow::Error::msg("File name is missing"))?;
-                    if *interactive {
+                    if interactive.should_ask(&pattern_key(&r, details)) {
                         let (prefix, question, valid_chars, actions) =
                             if let MatchResult::Match(_) = r {
                                 (
@@ -175,29 +296,11 @@ pub trait Messager: Send + Sync {
                         } else {
                             info!("{}", format!("{prefix} {file_name}").dimmed().bold());
                         }
-                        let actions_bold = actions.bold().blue();
-                        let selection = Input::new()
-                            .with_prompt(format!("{question} {actions_bold}"))
-                            .validate_with(|input: &String| -> Result<(), String> {
-                                if valid_chars.contains(
-                                    &input
-                                        .chars()
-                                        .next()
-                                        .unwrap_or('_')
-                                        .to_lowercase()
-                                        .to_string()
-                                        .as_str(),
-                                ) {
-                                    Ok(())
-                                } else {
-                                    Err(format!("Not a valid choice in {actions:}"))
-                                }
-                            })
-                            .interact_text()?;
+                        let selection = prompt_choice(question, actions, &valid_chars)?;
                         if let Some(pg) = pg {
                             pg.set_prefix("Analyzing")
                         }
-                        match selection.trim().to_lowercase().as_str() {
+                        match selection.as_str() {
                             "y" => {
                                 self.track_accept(&r)?;
                             }
@@ -218,11 +321,11 @@ pub trait Messager: Send + Sync {
                             }
                             "a" => {
                                 self.track_accept(&r)?;
-                                *interactive = false;
+                                interactive.enabled = false;
                             }
                             "q" => {
                                 self.track_reject(&r)?;
-                                *interactive = false;
+                                interactive.enabled = false;
                                 return Ok(false);
                             }
                             _ => panic!("invalid selection received"),
@@ -240,6 +343,128 @@ pub trait Messager: Send + Sync {
         Ok(true)
     }
 
+    /// Ask about each hunk of a rewrite, returning a rewrite with the kept changes (if any)
+    /// and whether the process should continue
+    #[allow(clippy::too_many_arguments)]
+    fn review_hunks(
+        &mut self,
+        result: &MatchResult,
+        rewrite: &Rewrite,
+        pattern: &str,
+        interactive: &mut InteractiveState,
+        pg: Option<&ProgressBar>,
+        details: &ApplyDetails,
+        language: &TargetLanguage,
+    ) -> anyhow::Result<(Option<MatchResult>, bool)> {
+        let original = rewrite.original.content.as_deref().unwrap_or_default();
+        let rewritten = rewrite.rewritten.content.as_deref().unwrap_or_default();
+        let hunks = split_hunks(original, rewritten);
+        let mut replacements: Vec<Option<String>> = vec![None; hunks.len()];
+        let file_name = &rewrite.original.source_file;
+        let mut keep_going = true;
+
+        if let Some(pg) = pg {
+            pg.set_prefix("Reviewing");
+        }
+        for (index, hunk) in hunks.iter().enumerate() {
+            let hunk_result = hunk_rewrite(rewrite, &hunks, index, &hunk.new_text);
+            if !interactive.should_ask(pattern) {
+                self.track_accept(&hunk_result)?;
+                replacements[index] = Some(hunk.new_text.clone());
+                continue;
+            }
+
+            info!(
+                "{}",
+                format!(
+                    "Change {}/{} in {}:{}",
+                    index + 1,
+                    hunks.len(),
+                    file_name,
+                    hunk.start_line()
+                )
+                .dimmed()
+                .bold()
+            );
+            info!("{}", hunk.diff());
+            let selection = prompt_choice(
+                "Apply this change",
+                HUNK_ACTIONS,
+                &["y", "n", "e", "s", "f", "p", "a", "q"],
+            )?;
+            match selection.as_str() {
+                "y" => {
+                    self.track_accept(&hunk_result)?;
+                    replacements[index] = Some(hunk.new_text.clone());
+                }
+                "n" => {
+                    self.track_reject(&hunk_result)?;
+                }
+                "e" => {
+                    let extension = Path::new(file_name)
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .map(|ext| format!(".{}", ext))
+                        .unwrap_or_default();
+                    // Closing the editor without saving rejects the change
+                    match Editor::new().extension(&extension).edit(&hunk.new_text)? {
+                        Some(mut edited) => {
+                            if hunk.new_text.ends_with('\n') && !edited.ends_with('\n') {
+                                edited.push('\n');
+                            }
+                            self.track_accept(&hunk_rewrite(rewrite, &hunks, index, &edited))?;
+                            replacements[index] = Some(edited);
+                        }
+                        None => self.track_reject(&hunk_result)?,
+                    }
+                }
+                "s" => {
+                    self.track_supress(result)?;
+                    let suppress_rewrite = result
+                        .get_rewrite_to_suppress(language, details.named_pattern.as_deref())
+                        .ok_or(anyhow::anyhow!("Failed to suppress rewrite"))?;
+                    self.apply_rewrite(&suppress_rewrite)?;
+                    return Ok((None, true));
+                }
+                "f" => {
+                    for (index, hunk) in hunks.iter().enumerate().skip(index) {
+                        self.track_reject(&hunk_rewrite(rewrite, &hunks, index, &hunk.new_text))?;
+                    }
+                    break;
+                }
+                "p" => {
+                    self.track_accept(&hunk_result)?;
+                    replacements[index] = Some(hunk.new_text.clone());
+                    interactive.accepted_patterns.insert(pattern.to_string());
+                }
+                "a" => {
+                    self.track_accept(&hunk_result)?;
+                    replacements[index] = Some(hunk.new_text.clone());
+                    interactive.enabled = false;
+                }
+                "q" => {
+                    self.track_reject(&hunk_result)?;
+                    interactive.enabled = false;
+                    keep_going = false;
+                    break;
+                }
+                _ => panic!("invalid selection received"),
+            }
+        }
+        if let Some(pg) = pg {
+            pg.set_prefix("Analyzing");
+        }
+
+        let merged = merge_hunks(original, &hunks, &replacements);
+        if merged == original {
+            return Ok((None, keep_going));
+        }
+        let mut kept = rewrite.clone();
+        kept.rewritten.content = Some(merged);
+        kept.rewritten.byte_ranges = None;
+        Ok((Some(MatchResult::Rewrite(kept)), keep_going))
+    }
+
     // Write a message to the output
     fn raw_emit(&mut self, message: &MatchResult) -> anyhow::Result<()>;
 
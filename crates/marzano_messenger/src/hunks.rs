@@ -0,0 +1,99 @@
+use colored::Colorize;
+use similar::TextDiff;
+use std::ops::Range;
+
+/// A contiguous run of changed lines in a rewritten file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The lines of the original file that were replaced (0-based)
+    pub old_lines: Range<usize>,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+impl Hunk {
+    /// The (1-based) line where the hunk starts in the original file
+    pub fn start_line(&self) -> usize {
+        self.old_lines.start + 1
+    }
+
+    /// The removed and added lines, for showing the hunk on its own
+    pub fn diff(&self) -> String {
+        let removed = self.old_text.lines().map(|l| format!("-{}", l).red());
+        let added = self.new_text.lines().map(|l| format!("+{}", l).green());
+        removed
+            .chain(added)
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Split the difference between two versions of a file into hunks, without context lines
+pub fn split_hunks(original: &str, rewritten: &str) -> Vec<Hunk> {
+    let diff = TextDiff::from_lines(original, rewritten);
+    let old_slices = diff.old_slices();
+    let new_slices = diff.new_slices();
+    diff.grouped_ops(0)
+        .into_iter()
+        .filter_map(|group| {
+            let (first, last) = (group.first()?, group.last()?);
+            let old_lines = first.old_range().start..last.old_range().end;
+            let new_lines = first.new_range().start..last.new_range().end;
+            Some(Hunk {
+                old_text: old_slices[old_lines.clone()].concat(),
+                new_text: new_slices[new_lines].concat(),
+                old_lines,
+            })
+        })
+        .collect()
+}
+
+/// Rebuild a file from its original content, replacing each hunk with the chosen text.
+/// Hunks with no replacement keep their original lines.
+pub fn merge_hunks(original: &str, hunks: &[Hunk], replacements: &[Option<String>]) -> String {
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let mut merged = String::with_capacity(original.len());
+    let mut next_line = 0;
+    for (hunk, replacement) in hunks.iter().zip(replacements) {
+        merged.extend(&lines[next_line..hunk.old_lines.start]);
+        match replacement {
+            Some(text) => merged.push_str(text),
+            None => merged.push_str(&hunk.old_text),
+        }
+        next_line = hunk.old_lines.end;
+    }
+    merged.extend(&lines[next_line..]);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = "a\nb\nc\nd\ne\n";
+    const REWRITTEN: &str = "a\nB\nc\nd\nE\nF\n";
+
+    #[test]
+    fn splits_separate_changes() {
+        let hunks = split_hunks(ORIGINAL, REWRITTEN);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old_text, "b\n");
+        assert_eq!(hunks[0].new_text, "B\n");
+        assert_eq!(hunks[0].start_line(), 2);
+        assert_eq!(hunks[1].old_text, "e\n");
+        assert_eq!(hunks[1].new_text, "E\nF\n");
+    }
+
+    #[test]
+    fn merges_chosen_hunks() {
+        let hunks = split_hunks(ORIGINAL, REWRITTEN);
+        let all: Vec<_> = hunks.iter().map(|h| Some(h.new_text.clone())).collect();
+        assert_eq!(merge_hunks(ORIGINAL, &hunks, &all), REWRITTEN);
+        assert_eq!(merge_hunks(ORIGINAL, &hunks, &[None, None]), ORIGINAL);
+        assert_eq!(
+            merge_hunks(ORIGINAL, &hunks, &[None, Some("edited\n".to_string())]),
+            "a\nb\nc\nd\nedited\n"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod emit;
 pub mod format;
+pub mod hunks;
 mod logs;
 pub mod output_mode;
 pub mod testing;
@@ -6,6 +6,17 @@ pub trait GritCache: Send + Sync {
 
     /// Mark that the file-pattern pair has no matches
     fn put_no_matches(&self, file_hash: [u8; 32], pattern_hash: [u8; 32]) -> Result<()>;
+
+    /// Get the serialized results for a file-pattern pair that has matches, if they are cached
+    fn get_matches(&self, file_hash: [u8; 32], pattern_hash: [u8; 32]) -> Option<&[u8]>;
+
+    /// Store the serialized results for a file-pattern pair that has matches
+    fn put_matches(
+        &self,
+        file_hash: [u8; 32],
+        pattern_hash: [u8; 32],
+        results: Vec<u8>,
+    ) -> Result<()>;
 }
 
 /// A cache that does nothing, useful for places where we don't have a cache available
@@ -31,4 +42,17 @@ impl GritCache for NullCache {
     fn put_no_matches(&self, _file_hash: [u8; 32], _pattern_hash: [u8; 32]) -> Result<()> {
         Ok(())
     }
+
+    fn get_matches(&self, _file_hash: [u8; 32], _pattern_hash: [u8; 32]) -> Option<&[u8]> {
+        None
+    }
+
+    fn put_matches(
+        &self,
+        _file_hash: [u8; 32],
+        _pattern_hash: [u8; 32],
+        _results: Vec<u8>,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
@@ -250,6 +250,44 @@ pub fn run_git_diff(path: &PathBuf) -> Result<String> {
     Ok(String::from_utf8(output.stdout)?)
 }
 
+/// The changes to diff with `run_git_diff_from`
+#[derive(Debug, Clone, Copy)]
+pub enum GitDiffBase<'a> {
+    /// Changes since the branch diverged from a ref, including uncommitted changes
+    Since(&'a str),
+    /// Changes staged for the next commit
+    Staged,
+}
+
+fn run_git(path: &PathBuf, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+pub fn run_git_diff_from(path: &PathBuf, base: GitDiffBase) -> Result<String> {
+    match base {
+        GitDiffBase::Since(git_ref) => {
+            // Diff from the merge base, so commits made on the ref after branching are not included
+            let merge_base = run_git(path, &["merge-base", git_ref, "HEAD"])?;
+            run_git(
+                path,
+                &["diff", merge_base.trim(), "--relative", "--unified=0"],
+            )
+        }
+        GitDiffBase::Staged => run_git(path, &["diff", "--cached", "--relative", "--unified=0"]),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,7 @@
 use anyhow::Result;
+use grit_util::{Profiler, Tracer};
 use http::HeaderMap;
-use std::env;
+use std::{env, sync::Arc};
 #[cfg(feature = "network_requests")]
 use tokio::runtime::Handle;
 
@@ -21,6 +22,18 @@ pub struct ExecutionContext {
     reqwest: reqwest::Client,
     /// Ignore limit patterns - this is important for scans
     pub ignore_limit_pattern: bool,
+    /// Only honor `grit-ignore` comments that give a reason
+    pub require_suppress_reason: bool,
+    /// Drop rewrites that introduce syntax errors, instead of only reporting them
+    pub reject_invalid_rewrites: bool,
+    /// Abort matching a file after this many milliseconds, overriding `GRIT_MAX_FILE_MILLIS`
+    pub max_file_millis: Option<u64>,
+    /// Abort matching a file after this many pattern steps, overriding `GRIT_MAX_FILE_STEPS`
+    pub max_file_steps: Option<u64>,
+    /// Records time spent in each definition and file, when profiling
+    pub profiler: Option<Arc<Profiler>>,
+    /// Records how the pattern was evaluated, when explaining a match
+    pub tracer: Option<Arc<Tracer>>,
 }
 
 #[cfg(all(
@@ -54,6 +67,14 @@ pub struct ExecutionContext {
     fetch: FetchFn,
     pub exec_external: ExecExternalFn,
     pub ignore_limit_pattern: bool,
+    pub require_suppress_reason: bool,
+    pub reject_invalid_rewrites: bool,
+    pub max_file_millis: Option<u64>,
+    pub max_file_steps: Option<u64>,
+    /// Records time spent in each definition and file, when profiling
+    pub profiler: Option<Arc<Profiler>>,
+    /// Records how the pattern was evaluated, when explaining a match
+    pub tracer: Option<Arc<Tracer>>,
 }
 
 #[cfg(not(any(test, feature = "network_requests_common")))]
@@ -61,6 +82,14 @@ pub struct ExecutionContext {
 pub struct ExecutionContext {
     llm_api: Option<LanguageModelAPI>,
     pub ignore_limit_pattern: bool,
+    pub require_suppress_reason: bool,
+    pub reject_invalid_rewrites: bool,
+    pub max_file_millis: Option<u64>,
+    pub max_file_steps: Option<u64>,
+    /// Records time spent in each definition and file, when profiling
+    pub profiler: Option<Arc<Profiler>>,
+    /// Records how the pattern was evaluated, when explaining a match
+    pub tracer: Option<Arc<Tracer>>,
 }
 
 impl ExecutionContext {
@@ -107,6 +136,12 @@ impl ExecutionContext {
             fetch,
             exec_external,
             ignore_limit_pattern: false,
+            require_suppress_reason: false,
+            reject_invalid_rewrites: false,
+            max_file_millis: None,
+            max_file_steps: None,
+            profiler: None,
+            tracer: None,
         }
     }
 
@@ -115,6 +150,16 @@ impl ExecutionContext {
         self
     }
 
+    pub fn with_profiler(mut self, profiler: Arc<Profiler>) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    pub fn with_tracer(mut self, tracer: Arc<Tracer>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
     #[cfg(feature = "network_requests")]
     pub fn send_request(
         &self,
@@ -191,6 +236,12 @@ impl Default for ExecutionContext {
             handle: Handle::try_current().ok(),
             reqwest: reqwest::Client::new(),
             ignore_limit_pattern: false,
+            require_suppress_reason: false,
+            reject_invalid_rewrites: false,
+            max_file_millis: None,
+            max_file_steps: None,
+            profiler: None,
+            tracer: None,
         }
     }
 
@@ -208,6 +259,12 @@ impl Default for ExecutionContext {
                 Err(anyhow::anyhow!("External functions are disabled"))
             },
             ignore_limit_pattern: false,
+            require_suppress_reason: false,
+            reject_invalid_rewrites: false,
+            max_file_millis: None,
+            max_file_steps: None,
+            profiler: None,
+            tracer: None,
         }
     }
 
@@ -216,6 +273,12 @@ impl Default for ExecutionContext {
         Self {
             llm_api: None,
             ignore_limit_pattern: false,
+            require_suppress_reason: false,
+            reject_invalid_rewrites: false,
+            max_file_millis: None,
+            max_file_steps: None,
+            profiler: None,
+            tracer: None,
         }
     }
 }
@@ -485,6 +485,7 @@ async fn search_internal(
                         has_results: Some(false),
                         file_hash: None,
                         from_cache: false,
+                        depends_on_date: false,
                     });
                     if let Err(e) = current_tx.send(vec![done_file]) {
                         println!("Error sending to parent: {:?}", e.to_string());
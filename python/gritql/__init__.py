@@ -1 +1,4 @@
 """Python bindings for GritQL."""
+from ._gritql import Binding, Match, QueryBuilder, Range
+
+__all__ = ["Binding", "Match", "QueryBuilder", "Range"]
@@ -0,0 +1,35 @@
+from os import PathLike
+from typing import Mapping, Sequence
+
+class Range:
+    start_line: int
+    start_column: int
+    end_line: int
+    end_column: int
+    start_byte: int
+    end_byte: int
+
+class Binding:
+    name: str
+    text: str | None
+    ranges: list[Range]
+
+class Match:
+    kind: str
+    path: str
+    ranges: list[Range]
+    bindings: list[Binding]
+    content: str | None
+    rewritten_path: str | None
+    rewritten_content: str | None
+
+class QueryBuilder:
+    def __init__(
+        self, base_query_src: str, grit_dir: str | PathLike[str] | None = None
+    ) -> None: ...
+    def set_replacement(self, replacement: str) -> None: ...
+    def add_insertion(self, insertion: str) -> None: ...
+    def compile(self) -> None: ...
+    def run_on_files(self, files: Mapping[str, str]) -> list[Match]: ...
+    def run_on_paths(self, paths: Sequence[str | PathLike[str]]) -> list[Match]: ...
+    def apply_to_file(self, path: str, content: str) -> str | None: ...
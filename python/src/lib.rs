@@ -0,0 +1,16 @@
+use pyo3::prelude::*;
+
+mod query;
+mod results;
+
+pub use query::QueryBuilder;
+
+/// Native GritQL bindings, re-exported by the `gritql` package
+#[pymodule]
+fn _gritql(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<QueryBuilder>()?;
+    m.add_class::<results::Match>()?;
+    m.add_class::<results::Binding>()?;
+    m.add_class::<results::Range>()?;
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use anyhow::Result;
+use grit_pattern_matcher::pattern::{DynamicPattern, Pattern, StringConstant};
+use marzano_core::{
+    api::MatchResult,
+    pattern_compiler::{CompilationResult, CompiledPatternBuilder},
+    problem::Problem,
+};
+use marzano_gritmodule::{
+    config::{init_config_from_path, init_global_grit_modules},
+    fetcher::KeepFetcherKind,
+    resolver::find_and_resolve_grit_dir,
+    utils::infer_pattern,
+};
+use marzano_language::{
+    grit_parser::MarzanoGritParser,
+    target_language::{expand_paths, PatternLanguage},
+};
+use marzano_util::{
+    rich_path::{RichFile, RichPath},
+    runtime::ExecutionContext,
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+use tokio::runtime::Runtime;
+
+use crate::results::Match;
+
+/// A GritQL query, compiled once and reused for every run
+#[pyclass(module = "gritql")]
+pub struct QueryBuilder {
+    base_query_src: String,
+    grit_dir: Option<PathBuf>,
+    replacement: Option<String>,
+    insertions: Vec<String>,
+    compiled: Option<Arc<Problem>>,
+}
+
+#[pymethods]
+impl QueryBuilder {
+    /// Construct a new query from a GritQL pattern, or the name of a pattern in `grit_dir`
+    #[new]
+    #[pyo3(signature = (base_query_src, grit_dir = None))]
+    fn new(base_query_src: String, grit_dir: Option<PathBuf>) -> Self {
+        Self {
+            base_query_src,
+            grit_dir,
+            replacement: None,
+            insertions: Vec::new(),
+            compiled: None,
+        }
+    }
+
+    /// Replace every match with the literal text `replacement`
+    fn set_replacement(&mut self, replacement: String) {
+        self.replacement = Some(replacement);
+        self.compiled = None;
+    }
+
+    /// Insert the literal text `insertion` after every match
+    fn add_insertion(&mut self, insertion: String) {
+        self.insertions.push(insertion);
+        self.compiled = None;
+    }
+
+    /// Compile the query, raising a `ValueError` if it is invalid.
+    /// Queries are compiled on first use, so calling this is only needed to check them up front.
+    fn compile(&mut self) -> PyResult<()> {
+        self.problem().map(|_| ())
+    }
+
+    /// Run the query over in-memory files, given as a mapping of paths to content
+    fn run_on_files(
+        &mut self,
+        py: Python<'_>,
+        files: HashMap<String, String>,
+    ) -> PyResult<Vec<Match>> {
+        let problem = self.problem()?;
+        let results = py.allow_threads(move || {
+            let files = files
+                .into_iter()
+                .map(|(path, content)| RichFile::new(path, content))
+                .collect();
+            problem.execute_files(files, &ExecutionContext::default())
+        });
+        Ok(to_matches(results))
+    }
+
+    /// Run the query over files and directories on disk, respecting `.gitignore` files
+    fn run_on_paths(&mut self, py: Python<'_>, paths: Vec<PathBuf>) -> PyResult<Vec<Match>> {
+        let problem = self.problem()?;
+        let results = py
+            .allow_threads(move || execute_paths(&problem, &paths))
+            .map_err(|e| PyRuntimeError::new_err(format!("{:#}", e)))?;
+        Ok(to_matches(results))
+    }
+
+    /// Apply the query to a single file, returning the new content if it changed
+    fn apply_to_file(
+        &mut self,
+        py: Python<'_>,
+        path: String,
+        content: String,
+    ) -> PyResult<Option<String>> {
+        let matches = self.run_on_files(py, HashMap::from([(path, content)]))?;
+        Ok(matches
+            .into_iter()
+            .find(|m| m.kind == "Rewrite")
+            .and_then(|m| m.rewritten_content))
+    }
+}
+
+impl QueryBuilder {
+    fn problem(&mut self) -> PyResult<Arc<Problem>> {
+        if let Some(problem) = &self.compiled {
+            return Ok(problem.clone());
+        }
+        let problem =
+            Arc::new(compile_query(self).map_err(|e| PyValueError::new_err(format!("{:#}", e)))?);
+        self.compiled = Some(problem.clone());
+        Ok(problem)
+    }
+}
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Module resolution is async, so every query shares one runtime to block on it
+fn runtime() -> Result<&'static Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Runtime::new()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+fn compile_query(query: &QueryBuilder) -> Result<Problem> {
+    let grit_files = runtime()?.block_on(async {
+        match &query.grit_dir {
+            Some(path) => {
+                init_config_from_path::<KeepFetcherKind>(path.clone(), false).await?;
+                find_and_resolve_grit_dir(Some(path.clone()), None).await
+            }
+            None => {
+                let init = init_global_grit_modules::<KeepFetcherKind>(None).await?;
+                init.get_grit_files().await
+            }
+        }
+    })?;
+
+    let (lang, _, pattern_body) = infer_pattern(&query.base_query_src, &grit_files);
+    let target_lang = lang.unwrap_or_default().try_into()?;
+    let libs = grit_files.get_language_directory_or_default(lang)?;
+    let mut grit_parser = MarzanoGritParser::new()?;
+
+    let mut builder = CompiledPatternBuilder::start(
+        pattern_body,
+        &libs,
+        target_lang,
+        None,
+        &mut grit_parser,
+        None,
+    )?;
+
+    for insertion in &query.insertions {
+        let insertion = Pattern::StringConstant(StringConstant::new(insertion.clone()));
+        builder = builder.wrap_with_accumulate(insertion);
+    }
+
+    if let Some(replacement) = &query.replacement {
+        let replacement = DynamicPattern::from_str_constant(replacement)?;
+        builder = builder.wrap_with_rewrite(replacement);
+    }
+
+    let CompilationResult { problem, .. } = builder.compile(None, None, true)?;
+    Ok(problem)
+}
+
+fn execute_paths(problem: &Problem, paths: &[PathBuf]) -> Result<Vec<MatchResult>> {
+    let language: PatternLanguage = problem.language.to_module_language();
+    let mut files = Vec::new();
+    for file in expand_paths(paths, Some(&[language]))? {
+        let Ok(file) = file else {
+            continue;
+        };
+        if file.file_type().is_some_and(|t| t.is_dir()) {
+            continue;
+        }
+        let extension = file.path().extension().unwrap_or_default();
+        if problem
+            .language
+            .match_extension(extension.to_str().unwrap_or_default())
+        {
+            files.push(RichPath::new(file.path().to_path_buf(), None));
+        }
+    }
+    let (results, _) = problem.execute_paths(files.iter().collect(), &ExecutionContext::default());
+    Ok(results)
+}
+
+fn to_matches(results: Vec<MatchResult>) -> Vec<Match> {
+    results.into_iter().filter_map(Match::from_result).collect()
+}
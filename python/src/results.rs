@@ -0,0 +1,136 @@
+use grit_util::VariableMatch;
+use marzano_core::api::{EntireFile, MatchResult};
+use pyo3::prelude::*;
+
+/// A range in a file, with 1-based lines and columns
+#[pyclass(module = "gritql", get_all, frozen)]
+#[derive(Clone, Debug)]
+pub struct Range {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+    pub start_byte: u32,
+    pub end_byte: u32,
+}
+
+#[pymethods]
+impl Range {
+    fn __repr__(&self) -> String {
+        format!(
+            "Range({}:{}-{}:{})",
+            self.start_line, self.start_column, self.end_line, self.end_column
+        )
+    }
+}
+
+impl From<&grit_util::Range> for Range {
+    fn from(range: &grit_util::Range) -> Self {
+        Self {
+            start_line: range.start.line,
+            start_column: range.start.column,
+            end_line: range.end.line,
+            end_column: range.end.column,
+            start_byte: range.start_byte,
+            end_byte: range.end_byte,
+        }
+    }
+}
+
+/// The value a metavariable was bound to in a match
+#[pyclass(module = "gritql", get_all, frozen)]
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub name: String,
+    /// The matched source text, from the first range the variable was bound to
+    pub text: Option<String>,
+    pub ranges: Vec<Range>,
+}
+
+#[pymethods]
+impl Binding {
+    fn __repr__(&self) -> String {
+        format!("Binding({}={:?})", self.name, self.text)
+    }
+}
+
+impl Binding {
+    fn new(variable: &VariableMatch, content: Option<&str>) -> Self {
+        let text = variable.ranges.first().and_then(|range| {
+            content?
+                .get(range.start_byte as usize..range.end_byte as usize)
+                .map(str::to_string)
+        });
+        Self {
+            name: variable.name.clone(),
+            text,
+            ranges: variable.ranges.iter().map(Range::from).collect(),
+        }
+    }
+}
+
+/// A file matched by a query, with its rewritten content if the query changed it
+#[pyclass(module = "gritql", get_all, frozen)]
+#[derive(Clone, Debug)]
+pub struct Match {
+    /// One of `Match`, `Rewrite`, `CreateFile`, or `RemoveFile`
+    pub kind: String,
+    pub path: String,
+    pub ranges: Vec<Range>,
+    pub bindings: Vec<Binding>,
+    /// The original content of the file
+    pub content: Option<String>,
+    pub rewritten_path: Option<String>,
+    pub rewritten_content: Option<String>,
+}
+
+#[pymethods]
+impl Match {
+    fn __repr__(&self) -> String {
+        format!("Match(kind={:?}, path={:?})", self.kind, self.path)
+    }
+}
+
+impl Match {
+    fn from_file(kind: &str, file: &EntireFile) -> Self {
+        let content = file.content.as_deref();
+        Self {
+            kind: kind.to_string(),
+            path: file.source_file.clone(),
+            ranges: file.ranges.iter().map(Range::from).collect(),
+            bindings: file
+                .variables
+                .iter()
+                .map(|variable| Binding::new(variable, content))
+                .collect(),
+            content: file.content.clone(),
+            rewritten_path: None,
+            rewritten_content: None,
+        }
+    }
+
+    /// Convert a result to a match, skipping results that aren't matches, like logs and done files
+    pub fn from_result(result: MatchResult) -> Option<Self> {
+        let kind = result.kind();
+        match result {
+            MatchResult::Match(m) => Some(Self::from_file(kind, &m.into())),
+            MatchResult::Rewrite(r) => Some(Self {
+                rewritten_path: Some(r.rewritten.source_file),
+                rewritten_content: r.rewritten.content,
+                ..Self::from_file(kind, &r.original)
+            }),
+            MatchResult::CreateFile(c) => Some(Self {
+                rewritten_path: Some(c.rewritten.source_file.clone()),
+                rewritten_content: c.rewritten.content.clone(),
+                content: None,
+                ..Self::from_file(kind, &c.rewritten)
+            }),
+            MatchResult::RemoveFile(r) => Some(Self::from_file(kind, &r.original)),
+            MatchResult::PatternInfo(_)
+            | MatchResult::AllDone(_)
+            | MatchResult::InputFile(_)
+            | MatchResult::DoneFile(_)
+            | MatchResult::AnalysisLog(_) => None,
+        }
+    }
+}
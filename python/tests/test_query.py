@@ -0,0 +1,47 @@
+import pytest
+
+from gritql import QueryBuilder
+
+
+def test_run_on_files():
+    query = QueryBuilder("`console.log($message)`")
+    matches = query.run_on_files(
+        {"index.js": "console.log('hello');\n", "other.js": "alert('hi');\n"}
+    )
+
+    assert [m.path for m in matches] == ["index.js"]
+    assert matches[0].kind == "Match"
+    assert matches[0].ranges[0].start_line == 1
+    message = next(b for b in matches[0].bindings if b.name == "$message")
+    assert message.text == "'hello'"
+
+
+def test_apply_to_file():
+    query = QueryBuilder("`console.log($message)` => `logger.info($message)`")
+
+    assert (
+        query.apply_to_file("index.js", "console.log('hello');\n")
+        == "logger.info('hello');\n"
+    )
+    assert query.apply_to_file("index.js", "alert('hi');\n") is None
+
+
+def test_set_replacement():
+    query = QueryBuilder("`console.log($message)`")
+    query.set_replacement("debugger")
+
+    assert query.apply_to_file("index.js", "console.log('hello');\n") == "debugger;\n"
+
+
+def test_run_on_paths(tmp_path):
+    (tmp_path / "index.js").write_text("console.log('hello');\n")
+    (tmp_path / "README.md").write_text("console.log('hello');\n")
+
+    matches = QueryBuilder("`console.log($message)`").run_on_paths([tmp_path])
+
+    assert [m.path for m in matches] == [str(tmp_path / "index.js")]
+
+
+def test_invalid_pattern():
+    with pytest.raises(ValueError):
+        QueryBuilder("`console.log(").compile()
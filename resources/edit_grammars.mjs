@@ -42,7 +42,9 @@ process.on("uncaughtException", (err) => {
 // Constants
 ///////////////////////////////////////////////////
 const allLanguages = [
+  "c",
   "c-sharp",
+  "cpp",
   "css",
   "go",
   "hcl",
@@ -239,7 +241,32 @@ async function buildLanguage(language) {
     throw new Error("Could not find Cargo.toml to update");
   }
 
-  if (language === "c-sharp") {
+  if (language === "c" || language === "cpp") {
+    // C and C++ extend the upstream grammar instead of replacing it
+    log(`Copying files`);
+    await fs.rename(
+      path.join(tsLangDir, "grammar.js"),
+      path.join(tsLangDir, "upstream-grammar.js")
+    );
+    await copyMvGrammar(language);
+    if (language === "cpp") {
+      // Point tree-sitter-c at our C grammar, so C++ inherits its metavariables
+      await fs.copyFile(
+        `${METAVARIABLE_GRAMMARS_DIR}/cpp-package.json`,
+        `${tsLangDir}/package.json`
+      );
+      await execPromise(`npm install`, tsLangDir);
+    }
+    log(`Running tree-sitter generate`);
+    await treeSitterGenerate(language);
+    log(`Copying output node types`);
+    await copyNodeTypes(language);
+    log(`Copying wasm parser`);
+    await copyWasmParser(language);
+    if (language === "cpp") {
+      await copyMyBuild("c", language);
+    }
+  } else if (language === "c-sharp") {
     log(`Copying C# files`);
     await copyMvGrammar(language);
     log(`Running C# tree-sitter generate`);
@@ -442,7 +469,13 @@ async function run() {
   } else {
     await Promise.all(languagesTobuild.map(rsyncGrammars));
   }
-  await Promise.all(languagesTobuild.map(buildLanguage));
+  // C++ builds on the generated C grammar, so C has to be done first
+  if (languagesTobuild.includes("c")) {
+    await buildLanguage("c");
+  }
+  await Promise.all(
+    languagesTobuild.filter((lang) => lang !== "c").map(buildLanguage)
+  );
 }
 
 run().catch(console.error);
@@ -0,0 +1,45 @@
+// The upstream grammar is kept as upstream-grammar.js by edit_grammars.mjs.
+// Rather than copying it, we extend it with metavariables wherever a snippet can hold a node.
+const C = require('./upstream-grammar');
+
+module.exports = grammar(C, {
+  name: 'c',
+
+  conflicts: ($, previous) => previous.concat([
+    [$._expression, $._type_specifier],
+    [$._expression, $._declarator],
+    [$._type_specifier, $._declarator],
+    [$._top_level_item, $._expression],
+    [$._block_item, $._expression],
+  ]),
+
+  rules: {
+    _top_level_item: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _block_item: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _expression: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _type_specifier: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _declarator: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _field_declarator: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _type_declarator: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _field_identifier: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _type_identifier: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _statement_identifier: ($, previous) => choice(previous, $.grit_metavariable),
+
+    string_literal: ($, previous) => choice(previous, seq(
+      choice('L"', 'u"', 'U"', 'u8"', '"'),
+      $.grit_metavariable,
+      '"',
+    )),
+
+    grit_metavariable: (_$) => token(prec(100, choice('µ...', /µ[a-zA-Z_][a-zA-Z0-9_]*/))),
+  },
+});
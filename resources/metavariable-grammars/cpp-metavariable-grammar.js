@@ -0,0 +1,27 @@
+// The upstream grammar is kept as upstream-grammar.js by edit_grammars.mjs.
+// It extends tree-sitter-c, which cpp-package.json points at our C grammar,
+// so the metavariables added there are inherited and only C++ rules are extended here.
+const CPP = require('./upstream-grammar');
+
+module.exports = grammar(CPP, {
+  name: 'cpp',
+
+  conflicts: ($, previous) => previous.concat([
+    [$._namespace_identifier, $._expression],
+    [$._class_name, $._expression],
+  ]),
+
+  rules: {
+    _namespace_identifier: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _class_name: ($, previous) => choice(previous, $.grit_metavariable),
+
+    _field_declaration_list_item: ($, previous) => choice(previous, $.grit_metavariable),
+
+    raw_string_literal: ($, previous) => choice(previous, seq(
+      'R"(',
+      $.grit_metavariable,
+      ')"',
+    )),
+  },
+});